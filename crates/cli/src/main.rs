@@ -1,10 +1,16 @@
 use clap::{Parser, Subcommand, ArgAction};
+use detectors::BuiltinDetectors;
+use indicatif::MultiProgress;
 use resourcer_core::*;
-use std::{collections::HashSet, path::PathBuf};
-use regex::Regex;
-use url::Url;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+mod progress;
+use progress::ProgressObserver;
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum Commands {
     #[command(
         about = "Point ReSourceR at any Webpack-powered page and instantly list its sources and sourcemap links",
@@ -17,35 +23,98 @@ enum Commands {
         /// Output list as pretty-printed JSON
         #[arg(long, help = "Print JSON array instead of plain text")]
         json: bool,
-        /// Also load located sourcemap files (local only) and print their `sources[]` entries
-        #[arg(long, help = "For each discovered .map file that is on disk, print its sources list")]
+        /// Also load located sourcemaps (on disk, inline or remote) and print their `sources[]` entries
+        #[arg(long, help = "For each discovered sourcemap (file, data: or http URL), print its sources list")]
         show_sources: bool,
     },
     #[command(
         about = "Give ReSourceR any Webpack-powered page & it will autodiscover all chunks/sourcemaps and rebuild the source",
-        long_about = "Just point ReSourceR at a public URL of a site built with Webpack/Next.js/etc. It fetches the HTML, follows the runtime `webpack*.js` & `_buildManifest.js`, enumerates every JS chunk & sourcemap it can find, downloads them (concurrent by default), then reconstructs the original source tree ‑ ready for review, auditing, or diffing.  A local bundle file can also be provided via `--input`."
+        long_about = "Just point ReSourceR at a public URL of a site built with Webpack/Next.js/Vite/etc. It fetches the HTML, follows the runtime `webpack*.js` & `_buildManifest.js` (or the Vite module graph), enumerates every JS chunk & sourcemap it can find, downloads them (concurrent by default), then reconstructs the original source tree ‑ ready for review, auditing, or diffing.  A local bundle file or build directory (`dist/`, `.next/`) can also be provided via `--input`; `file://` sourcemaps next to it are resolved from disk."
     )]
     Dump {
-        /// Target URL to fetch (mutually exclusive with --input)
-        #[arg(long, conflicts_with = "input", value_name = "URL")]
+        /// Target URL to fetch (mutually exclusive with --input); with --warc, the archived page to start from
+        #[arg(long, conflicts_with_all = ["input", "har"], value_name = "URL")]
         url: Option<String>,
-        /// Local JavaScript file to process
-        #[arg(long, conflicts_with = "url", value_name = "FILE")]
+        /// Local JavaScript file or build directory (e.g. `dist/`, `.next/`) to process
+        #[arg(long, conflicts_with_all = ["url", "har"], value_name = "PATH")]
         input: Option<String>,
-        /// Output directory for reconstructed sources
-        #[arg(long, value_name = "DIR", default_value = "out")]
-        out: String,
+        /// HAR capture exported from browser devtools; its scripts, stylesheets and sourcemaps are used offline
+        #[arg(long, value_name = "FILE")]
+        har: Option<PathBuf>,
+        /// With --har, fetch chunks and sourcemaps missing from the capture over the network
+        #[arg(long, requires = "har")]
+        allow_network: bool,
+        /// WARC crawl archive (`.warc` or `.warc.gz`) to dump offline as if it were the live site
+        #[arg(long, conflicts_with_all = ["input", "har"], value_name = "FILE")]
+        warc: Option<PathBuf>,
+        /// Output directory for reconstructed sources (default: out/<host> or out/<bundle name>)
+        #[arg(long, value_name = "DIR")]
+        out: Option<PathBuf>,
         /// Perform detection only; do not download or write files
         #[arg(long, help = "Detect and list actions without writing files")]
         dry_run: bool,
+        /// Continue an interrupted dump into the same --out, skipping chunks it already saved
+        #[arg(long, conflicts_with_all = ["dry_run", "input", "har"])]
+        resume: bool,
 
         /// Maximum number of concurrent downloads (1 = sequential)
         #[arg(long, value_name = "N", default_value = "8")]
         concurrency: usize,
 
+        /// Tries per request (page fetch, URL validation, downloads), including the first
+        #[arg(long, value_name = "N", default_value = "3")]
+        retries: usize,
+
+        /// Wait before the first retry, doubled for each further one (randomized by up to half)
+        #[arg(long, value_name = "MS", default_value = "200")]
+        retry_delay: u64,
+
+        /// Longest wait between tries; a longer Retry-After gives up instead
+        #[arg(long, value_name = "SECS", default_value = "30")]
+        max_retry_delay: u64,
+
+        /// Statuses worth retrying (default: 408,425,429,500,502,503,504)
+        #[arg(long, value_name = "STATUS", value_delimiter = ',')]
+        retry_on: Vec<u16>,
+
         /// Limit total number of chunk files to download (useful for testing)
         #[arg(long, value_name = "N")]
         max_files: Option<usize>,
+
+        /// Look for undeclared sourcemaps (`chunk.js.map`, ...) next to chunks without a sourceMappingURL
+        #[arg(long, help = "Probe conventional sourcemap locations for chunks that declare none (hidden-source-map builds)")]
+        probe_maps: bool,
+
+        /// Build-machine project root; absolute source paths under it are written relative to it
+        #[arg(long, value_name = "PATH", help = "Project root on the build machine (e.g. /home/ci/app); absolute sourcemap paths are made relative to it")]
+        project_root: Option<String>,
+
+        /// Do not create top-level folders for sourcemap namespaces (`webpack://<ns>/`, hosts, virtual modules)
+        #[arg(long, help = "Write namespaced sources without a top-level namespace folder")]
+        flat_namespaces: bool,
+
+        /// Drop loader query strings (`App.vue?vue&type=style`) instead of writing each variant to its own file
+        #[arg(long, help = "Strip query strings from source paths; variants of the same file overwrite each other")]
+        strip_queries: bool,
+
+        /// What to do when sourcemaps disagree on the contents of the same original file
+        #[arg(long, value_name = "POLICY", default_value = "variants", help = "Conflict policy for sources shared between sourcemaps: keep-first, keep-largest or variants (write `file.conflict-<hash>.ext`)")]
+        on_conflict: ConflictPolicy,
+
+        /// Write a machine-readable report of the run to FILE
+        #[arg(long, value_name = "FILE", help = "Write a structured report (runtime, chunks, maps, files, errors) to FILE")]
+        report: Option<PathBuf>,
+
+        /// Report format
+        #[arg(long, value_name = "FORMAT", default_value = "json", requires = "report", help = "Report format: json (one document) or ndjson (one record per line)")]
+        format: ReportFormat,
+
+        /// Do not draw a progress bar (it is only drawn when stderr is a terminal)
+        #[arg(long, help = "Disable the progress bar")]
+        no_progress: bool,
+
+        #[command(flatten)]
+        http: HttpArgs,
     },
     #[command(
         about = "Analyze Single-Page Apps using headless browser (placeholder)",
@@ -57,6 +126,171 @@ enum Commands {
     },
 }
 
+/// How requests are made; every request of a dump goes through one client built from these.
+#[derive(clap::Args)]
+#[command(group(clap::ArgGroup::new("cache_store").args(["cache", "cache_db"])))]
+struct HttpArgs {
+    /// User-Agent header sent with every request
+    #[arg(long, value_name = "UA", help = "User-Agent to send (default: a desktop Chrome UA)")]
+    user_agent: Option<String>,
+
+    /// Per-request timeout in seconds
+    #[arg(long, value_name = "SECS", default_value = "30")]
+    timeout: u64,
+
+    /// Maximum redirects to follow per request (0 = do not follow)
+    #[arg(long, value_name = "N", default_value = "10")]
+    max_redirects: usize,
+
+    /// Extra request header, e.g. `Authorization: Bearer ...` (repeatable)
+    #[arg(long = "header", short = 'H', value_name = "NAME: VALUE", value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+
+    /// Cookie sent to the target site, e.g. `session=abc` (repeatable)
+    #[arg(long = "cookie", value_name = "NAME=VALUE")]
+    cookies: Vec<String>,
+
+    /// Proxy for all requests (http://, https:// or socks5://); overrides HTTP(S)_PROXY
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Ignore HTTP_PROXY / HTTPS_PROXY / NO_PROXY from the environment
+    #[arg(long, help = "Do not use proxies configured in the environment")]
+    no_env_proxy: bool,
+
+    /// PEM file with additional root certificates to trust
+    #[arg(long, value_name = "FILE")]
+    ca_bundle: Option<PathBuf>,
+
+    /// Accept invalid TLS certificates (self-signed, expired, wrong host)
+    #[arg(long, help = "Disable TLS certificate verification")]
+    insecure: bool,
+
+    /// Save every HTTP request and response of the run to a cassette directory
+    #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Serve HTTP requests from a cassette recorded with --record instead of the network
+    #[arg(long, value_name = "DIR")]
+    replay: Option<PathBuf>,
+
+    /// Cache fetched documents in a directory and reuse them on later runs
+    #[arg(long, value_name = "DIR", conflicts_with = "cache_db")]
+    cache: Option<PathBuf>,
+
+    /// Like --cache, but in a single SQLite database file
+    #[arg(long, value_name = "FILE")]
+    cache_db: Option<PathBuf>,
+
+    /// Ask the server whether cached documents changed (If-None-Match / If-Modified-Since)
+    /// instead of reusing them as they are
+    #[arg(long, requires = "cache_store")]
+    revalidate: bool,
+
+    /// Maximum requests per second to each host
+    #[arg(long, value_name = "RPS")]
+    rate: Option<f64>,
+
+    /// Requests a host may receive back to back before --rate applies
+    #[arg(long, value_name = "N", default_value = "1", requires = "rate")]
+    burst: u32,
+
+    /// Maximum requests in flight to each host at once
+    #[arg(long, value_name = "N")]
+    per_host: Option<usize>,
+
+    /// Wait a random 0..MS milliseconds before each request
+    #[arg(long, value_name = "MS", default_value = "0")]
+    jitter: u64,
+
+    /// Keep the pace when a host answers 429 or 503 instead of slowing down
+    #[arg(long, help = "Do not slow down on 429 / 503 responses")]
+    no_slow_down: bool,
+}
+
+impl HttpArgs {
+    /// The fetcher for a dump of `target`: the configured client, throttled per host, recording to
+    /// or replaying from a cassette and behind a cache when asked. Only `local` dumps may read
+    /// `file:` URLs.
+    fn into_fetcher(mut self, target: Option<&str>, local: bool) -> Result<Arc<dyn Fetcher>, String> {
+        let (record, replay, revalidate) = (self.record.take(), self.replay.take(), self.revalidate);
+        let cache: Option<Arc<dyn HtmlStorage>> = match (self.cache.take(), self.cache_db.take()) {
+            (Some(dir), _) => {
+                Some(Arc::new(FsStorage::open(&dir).map_err(|e| format!("cannot cache in {}: {e}", dir.display()))?))
+            }
+            (None, Some(db)) => {
+                Some(Arc::new(SqliteStorage::open(&db).map_err(|e| format!("cannot cache in {}: {e}", db.display()))?))
+            }
+            (None, None) => None,
+        };
+        let throttle = Arc::new(Throttle::new(self.throttle_config()));
+        let client = HttpClient::new(self.into_config(target)?).map_err(|e| e.to_string())?;
+        let network: Arc<dyn Fetcher> = Arc::new(ThrottledFetcher::new(Arc::new(HttpFetcher::new(client.clone())), throttle));
+        let mut http: Arc<dyn Fetcher> = match (record, replay) {
+            (Some(dir), _) => {
+                let rec = RecordingFetcher::new(network, &dir)
+                    .map_err(|e| format!("cannot record to {}: {e}", dir.display()))?;
+                Arc::new(rec)
+            }
+            (None, Some(dir)) => {
+                let rep = ReplayFetcher::open(&dir).map_err(|e| format!("cannot replay {}: {e}", dir.display()))?;
+                Arc::new(rep)
+            }
+            (None, None) => network,
+        };
+        if let Some(storage) = cache {
+            let caching = CachingFetcher::new(http, storage);
+            http = Arc::new(if revalidate { caching.revalidating() } else { caching });
+        }
+        let mut fetcher = if local { CompositeFetcher::with_builtin(client) } else { CompositeFetcher::with_remote(client) };
+        fetcher.register("http", http.clone()).register("https", http);
+        Ok(Arc::new(fetcher))
+    }
+
+    /// Politeness towards the hosts of the dump; every network request goes through it.
+    fn throttle_config(&self) -> ThrottleConfig {
+        let defaults = ThrottleConfig::default();
+        ThrottleConfig {
+            requests_per_second: self.rate,
+            burst: self.burst,
+            per_host_concurrency: self.per_host,
+            jitter: std::time::Duration::from_millis(self.jitter),
+            slow_down_statuses: if self.no_slow_down { Vec::new() } else { defaults.slow_down_statuses },
+            ..defaults
+        }
+    }
+
+    /// Client configuration; cookies are scoped to `target` (they are dropped for local dumps).
+    fn into_config(self, target: Option<&str>) -> Result<HttpConfig, String> {
+        let cookies = match target {
+            Some(t) if !self.cookies.is_empty() => {
+                let url = url::Url::parse(t).map_err(|e| format!("invalid url '{t}': {e}"))?;
+                self.cookies.into_iter().map(|c| (url.clone(), c)).collect()
+            }
+            _ => Vec::new(),
+        };
+        let defaults = HttpConfig::default();
+        Ok(HttpConfig {
+            user_agent: self.user_agent.unwrap_or(defaults.user_agent),
+            timeout: std::time::Duration::from_secs(self.timeout),
+            max_redirects: self.max_redirects,
+            headers: self.headers,
+            cookies,
+            proxy: self.proxy,
+            use_env_proxy: !self.no_env_proxy,
+            ca_bundle: self.ca_bundle,
+            insecure: self.insecure,
+            ..defaults
+        })
+    }
+}
+
+/// `Name: value` -> (`Name`, `value`).
+fn parse_header(s: &str) -> Result<(String, String), String> {
+    let (name, value) = s.split_once(':').ok_or_else(|| format!("expected 'Name: value', got '{s}'"))?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
 #[derive(Parser)]
 #[command(version, about = "ReSourceR – Rust-powered tool to extract bundles, resolve sourcemaps, and reconstruct original source code from minified JavaScript")]
 struct Cli {
@@ -72,7 +306,8 @@ struct Cli {
     command: Commands,
 }
 
-fn init_logging(verbosity: u8, quiet: bool) {
+/// Log through `multi` so log lines are printed above any progress bar instead of tearing it.
+fn init_logging(verbosity: u8, quiet: bool, multi: &MultiProgress) {
     use log::LevelFilter::*;
     let level = if quiet {
         Error
@@ -83,16 +318,20 @@ fn init_logging(verbosity: u8, quiet: bool) {
             _ => Trace,
         }
     };
-    env_logger::Builder::new()
+    let logger = env_logger::Builder::new()
         .filter_level(level)
         .format_timestamp(None)
-        .init();
+        .build();
+    if indicatif_log_bridge::LogWrapper::new(multi.clone(), logger).try_init().is_ok() {
+        log::set_max_level(level);
+    }
 }
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
-    init_logging(cli.verbose, cli.quiet);
+    let multi = MultiProgress::new();
+    init_logging(cli.verbose, cli.quiet, &multi);
 
     match cli.command {
         Commands::ListUrls { input, json, show_sources } => {
@@ -101,243 +340,131 @@ async fn main() {
                 std::process::exit(1);
             }
         }
-        Commands::Dump { url, input, out, dry_run, concurrency, max_files } => {
-            if let Some(page_url) = url {
-                if let Err(e) = run_dump_remote(&page_url, &out, dry_run, concurrency, max_files).await {
-                    eprintln!("error: {:?}", e);
+        Commands::Dump { url, input, har, allow_network, warc, out, dry_run, resume, concurrency, retries, retry_delay, max_retry_delay, retry_on, max_files, probe_maps, project_root, flat_namespaces, strip_queries, on_conflict, report: report_path, format, no_progress, http } => {
+            let local = url.is_none() && har.is_none() && warc.is_none();
+            let fetcher = match http.into_fetcher(url.as_deref(), local) {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("error: {}", e);
                     std::process::exit(1);
                 }
-            } else if let Some(local) = input {
-                eprintln!("local dump not yet implemented: {}", local);
-                std::process::exit(1);
-            } else {
-                eprintln!("error: either --url or --input must be provided");
-                std::process::exit(1);
-            }
-        }
-        Commands::Browser { .. } => {
-            eprintln!("browser command not yet implemented");
-            std::process::exit(1);
-        }
-    }
-}
-
-async fn run_dump_remote(page_url: &str, _out_dir: &str, dry_run: bool, concurrency: usize, max_files: Option<usize>) -> Result<(), CLIError> {
-    println!("Fetching HTML from {}", page_url);
-    let html = fetch_html(page_url).await?;
-    let base = Url::parse(page_url)?;
-
-    // Extract script URLs using core function
-    let script_urls = extract_script_urls(&html, &base);
-    
-    // Look for the Webpack runtime
-    let runtime_re = Regex::new(r"/webpack-[^/]+\.js(?:\?.*)?$").unwrap();
-    let runtime_url = script_urls.iter().find(|u| runtime_re.is_match(u.path()));
-
-    let runtime_url = match runtime_url {
-        Some(u) => {
-            println!("Identified Webpack runtime: {}", u);
-            u.clone()
-        }
-        None => {
-            println!("No Webpack runtime script found matching pattern /webpack-*.js");
-            return Ok(());
-        }
-    };
-
-    println!("Fetching runtime content...");
-    let runtime_js = fetch_html(runtime_url.as_str()).await?;
-
-    // Log if sourcemap is enabled in the runtime JS using core function
-    if let Some(sm_url) = find_sourcemap_url_in_js(&runtime_js) {
-        println!("Sourcemap enabled in runtime: {}", sm_url);
-    } else {
-        println!("No sourcemap comment found in runtime JS");
-    }
-
-    // Determine chunk filename template (fallback) and helpers
-    let template_opt = infer_chunk_filename_template(&runtime_js);
-
-    // First attempt: use _buildManifest.js to list all asset paths
-    let manifest_re = Regex::new(r"/_?buildManifest\.js$",).unwrap();
-    let manifest_url_opt = script_urls.iter().find(|u| manifest_re.is_match(u.path()));
-
-    let chunk_urls: Vec<Url> = if let Some(manifest_url) = manifest_url_opt {
-        println!("Found build manifest script: {}", manifest_url);
-        let manifest_js = fetch_html(manifest_url.as_str()).await?;
-
-        // Extract asset paths (JS & CSS) from manifest
-        let paths = extract_paths_from_build_manifest(&manifest_js);
-        println!("Found {} asset paths in build manifest", paths.len());
-
-        // Derive base using runtime url (static prefix) - using core function
-        let base_chunks = derive_base_from_runtime(&runtime_url, "static/");
-
-        let mut urls = Vec::new();
-        let mut seen: HashSet<String> = HashSet::new();
-        for p in paths {
-            // We only care about .js files for now
-            if !p.ends_with(".js") { continue; }
-            if let Ok(u) = base_chunks.join(&p) {
-                if seen.insert(u.as_str().to_string()) {
-                    urls.push(u);
-                }
-            }
-        }
-
-        urls
-    } else {
-        // If manifest not found, fallback to runtime-based inference as before
-
-        // Determine public path base URL
-        let public_path = extract_public_path(&runtime_js);
-        let base_for_chunks: Option<url::Url> = match public_path.as_ref() {
-            Some(pp) => {
-                // __webpack_require__.p provided (absolute or relative)
-                if let Ok(abs) = url::Url::parse(pp) {
-                    Some(abs)
-                } else if let Ok(joined) = base.join(pp) {
-                    Some(joined)
-                } else {
-                    None
+            };
+            let path_opts = SourcePathOptions { namespace_dirs: !flat_namespaces, query_variants: !strip_queries, project_root };
+            let save_opts = SaveOptions {
+                probe_maps,
+                normalizer: Arc::new(SourceNormalizer::with_builtin(path_opts)),
+                ledger: Arc::new(SourceLedger::new(on_conflict)),
+                fetcher,
+                ..Default::default()
+            };
+            let target = match (url, input, har, warc) {
+                (page, _, _, Some(archive)) => DumpTarget::Warc { path: archive, page },
+                (Some(page_url), _, _, None) => DumpTarget::Url(page_url),
+                (None, Some(local), _, None) => DumpTarget::Local(PathBuf::from(local)),
+                (None, None, Some(capture), None) => DumpTarget::Har(capture),
+                (None, None, None, None) => {
+                    eprintln!("error: one of --url, --input, --har or --warc must be provided");
+                    std::process::exit(1);
                 }
-            }
-            None => {
-                // No explicit public path – default to origin; path trimming handled later
-                Some(base.clone())
-            }
-        };
-
-        let has_public_path = public_path.is_some();
-
-        if let Some(bu) = &base_for_chunks {
-            println!("Public path base: {}", bu);
-        } else {
-            println!("No public path detected; chunk URLs may be relative.");
-        }
-
-        if let Some(template) = template_opt {
-            println!("Chunk filename template: prefix='{}' suffix='{}'", template.prefix, template.suffix);
-            let chunk_ids = extract_chunk_ids(&runtime_js);
-            println!("Found {} chunk IDs", chunk_ids.len());
-
-            let dyn_base = if has_public_path {
-                base_for_chunks.as_ref().cloned()
-            } else {
-                Some(derive_base_from_runtime(&runtime_url, &template.prefix))
             };
-
-            generate_chunk_urls(dyn_base.as_ref(), &template, &chunk_ids)
-        } else if let Some(map_info) = extract_chunk_maps(&runtime_js) {
-            println!("Using map-based filename construction (separator '{}')", map_info.separator);
-            let chunk_ids = extract_chunk_ids(&runtime_js);
-            println!("Found {} chunk IDs", chunk_ids.len());
-
-            let dyn_base = if has_public_path {
-                base_for_chunks.as_ref().cloned().unwrap_or(base.clone())
-            } else {
-                derive_base_from_runtime(&runtime_url, &map_info.prefix)
+            let options = DumpOptions {
+                out_dir: out,
+                dry_run,
+                concurrency,
+                retry: RetryPolicy {
+                    max_attempts: retries.max(1),
+                    base_delay: std::time::Duration::from_millis(retry_delay),
+                    max_delay: std::time::Duration::from_secs(max_retry_delay),
+                    retry_statuses: if retry_on.is_empty() { RetryPolicy::default().retry_statuses } else { retry_on },
+                    ..Default::default()
+                },
+                max_files,
+                network_fallback: allow_network,
+                resume,
+                // No `throttle`: the fetcher already throttles every network request, below the cache.
+                ..Default::default()
             };
+            let mut dumper = Dumper::new(DetectorRegistry::with_builtin()).with_options(options).with_save_options(save_opts);
+            let progress = (!no_progress && !cli.quiet && std::io::stderr().is_terminal())
+                .then(|| Arc::new(ProgressObserver::new(&multi)));
+            if let Some(p) = &progress {
+                dumper = dumper.with_observer(p.clone());
+            }
 
-            generate_urls_from_chunk_maps(&dyn_base, &map_info, &chunk_ids)
-        } else {
-            println!("Could not infer template or maps; falling back to literal path extraction.");
-            let paths = extract_literal_chunk_paths(&runtime_js);
-            println!("Found {} literal chunk paths", paths.len());
-            let mut urls = Vec::new();
-
-            // Use derive_base_from_runtime with "static/chunks/" prefix for literal paths
-            let literal_base = if has_public_path {
-                base_for_chunks.as_ref().cloned().unwrap_or(base.clone())
-            } else {
-                derive_base_from_runtime(&runtime_url, "static/chunks/")
+            let result = dumper.run(&target).await;
+            if let Some(p) = &progress {
+                p.finish();
+            }
+            let report = match &result {
+                Ok(res) => res.report.clone(),
+                Err(e) => {
+                    let mut report = DumpReport::new(target.label());
+                    report.record_error(None, e.kind(), e.to_string());
+                    report
+                }
             };
-
-            for p in paths {
-                if let Ok(u) = literal_base.join(&p) {
-                    urls.push(u);
+            if let Some(path) = report_path {
+                if let Err(e) = write_report(&report, format, &path) {
+                    eprintln!("✖ failed to write report {}: {}", path.display(), e);
+                }
+            }
+            match result {
+                Ok(res) => print_summary(&res, dry_run),
+                Err(e) => {
+                    eprintln!("error: {:?}", e);
+                    std::process::exit(1);
                 }
             }
-            urls
         }
-    };
-
-    println!("Generated {} chunk URLs", chunk_urls.len());
+        Commands::Browser { .. } => {
+            eprintln!("browser command not yet implemented; capture a HAR in devtools and run `dump --har <FILE>` instead");
+            std::process::exit(1);
+        }
+    }
+}
 
+/// Human-readable end-of-run summary.
+fn print_summary(res: &DumpResult, dry_run: bool) {
     if dry_run {
-        for u in &chunk_urls {
+        for u in &res.planned {
             println!("  - {}", u);
         }
-        println!("Dry run complete. No downloads performed.");
-        return Ok(());
+        println!("Dry run complete. No files written.");
+        return;
     }
-
-    println!("Validating chunk URLs...");
-    let live_urls = validate_chunk_urls(chunk_urls).await;
-    println!("{} URLs responded with 2xx", live_urls.len());
-
-    let mut live_urls = live_urls; // make mutable
-    if let Some(max_n) = max_files {
-        if live_urls.len() > max_n {
-            live_urls.truncate(max_n);
-            println!("Truncated to {} URLs due to --max-files", max_n);
-        }
+    let Some(out_dir) = &res.out_dir else {
+        println!("Nothing to download.");
+        return;
+    };
+    for c in res.report.chunks.iter().filter(|c| c.status != ChunkStatus::Saved) {
+        let err = res.report.errors.iter().find(|e| e.url.as_deref() == Some(c.url.as_str()));
+        eprintln!("✖ {}: {}", c.url, err.map_or("failed", |e| e.message.as_str()));
     }
-
-    if live_urls.is_empty() {
-        println!("No downloadable chunk URLs found – exiting.");
-        return Ok(());
+    let s = &res.stats;
+    let summary = res.report.summary();
+    println!("✔ saved {} of {} chunks", summary.chunks_saved, summary.chunks);
+    if summary.chunks_unchanged > 0 {
+        println!("  {} unchanged since the cached copy, {} changed or new", summary.chunks_unchanged, summary.chunks_saved - summary.chunks_unchanged);
     }
-
-    // Prepare output directory
-    let out_root: PathBuf = if _out_dir == "out" {
-        // Use "out/<host>" when user did not specify --out
-        let host = base.host_str().unwrap_or("site");
-        PathBuf::from("out").join(host)
-    } else {
-        PathBuf::from(_out_dir)
-    };
-
-    ensure_output_dir(&out_root)?;
-
-    println!("Downloading {} chunk files to {:?} ...", live_urls.len(), out_root);
-
-    if concurrency <= 1 {
-        // Sequential download
-        for (idx, u) in live_urls.iter().enumerate() {
-            println!("[{}/{}] downloading {}", idx + 1, live_urls.len(), u);
-            match resourcer_core::fetch::fetch_with_retries(u.as_str(), 3).await {
-                Ok(body) => {
-                    if let Err(e) = save_js_and_sources(&body, u.as_str(), &out_root).await {
-                        eprintln!("✖ error processing {}: {:?}", u, e);
-                    } else {
-                        println!("✔ saved {}", u);
-                    }
-                }
-                Err(e) => eprintln!("✖ failed to download {}: {:?}", u, e),
-            }
-        }
-    } else {
-        // Parallel via download_many
-        let url_strings: Vec<String> = live_urls.iter().map(|u| u.to_string()).collect();
-        let cfg = DownloadManagerConfig { concurrency, ..Default::default() };
-        let results = download_many(url_strings, cfg).await;
-
-        for (idx, res) in results.into_iter().enumerate() {
-            match res.content {
-                Some(body) => {
-                    if let Err(e) = save_js_and_sources(&body, &res.url, &out_root).await {
-                        eprintln!("✖ error processing {}: {:?}", res.url, e);
-                    } else {
-                        println!("[{}] ✔ saved {}", idx + 1, res.url);
-                    }
-                }
-                None => eprintln!("✖ failed {}: {:?}", res.url, res.error),
-            }
-        }
+    if !res.skipped.is_empty() {
+        println!("  {} chunks already saved by the resumed run were skipped", res.skipped.len());
     }
+    println!(
+        "Sourcemaps: {} declared, {} found by probing; {} webpack modules split from map-less chunks",
+        s.declared_maps, s.probed_maps, s.split_modules
+    );
+    println!(
+        "Sources: {} written, {} duplicates skipped, {} conflicting versions",
+        s.written_sources, s.duplicate_sources, s.conflicting_sources
+    );
+    if let Some(path) = &res.ledger_path {
+        println!("Source ledger written to {:?}", path);
+    }
+    println!("Output at {:?}", out_dir);
+}
 
-    println!("All downloads and source reconstruction complete. Output at {:?}", out_root);
-
-    Ok(())
+/// Write `report` to `path` in `format`.
+fn write_report(report: &DumpReport, format: ReportFormat, path: &Path) -> std::io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    report.write(format, std::io::BufWriter::new(file))
 }
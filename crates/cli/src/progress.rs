@@ -0,0 +1,60 @@
+//! Terminal progress bar driven by the pipeline's [`DumpEvent`]s.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use resourcer_core::{DumpEvent, DumpObserver};
+
+/// Counts discovered chunks into the bar's length and finished downloads into its position;
+/// warnings are printed above the bar.
+pub struct ProgressObserver {
+    bar: ProgressBar,
+    maps: AtomicUsize,
+    files: AtomicUsize,
+}
+
+impl ProgressObserver {
+    pub fn new(multi: &MultiProgress) -> Self {
+        let bar = multi.add(ProgressBar::new(0));
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} [{elapsed_precise}] {bar:30} {pos}/{len} chunks {msg}")
+                .expect("valid progress template"),
+        );
+        Self { bar, maps: AtomicUsize::new(0), files: AtomicUsize::new(0) }
+    }
+
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+
+    fn update_message(&self) {
+        let maps = self.maps.load(Ordering::Relaxed);
+        let files = self.files.load(Ordering::Relaxed);
+        self.bar.set_message(format!("| {maps} maps, {files} files"));
+    }
+}
+
+impl DumpObserver for ProgressObserver {
+    fn on_event(&self, event: &DumpEvent) {
+        match event {
+            DumpEvent::ChunkDiscovered { .. } => self.bar.inc_length(1),
+            DumpEvent::DownloadFinished { .. } => self.bar.inc(1),
+            DumpEvent::SourcemapParsed { .. } => {
+                self.maps.fetch_add(1, Ordering::Relaxed);
+                self.update_message();
+            }
+            DumpEvent::FileWritten { .. } => {
+                self.files.fetch_add(1, Ordering::Relaxed);
+                self.update_message();
+            }
+            DumpEvent::Warning { url, message } => {
+                let line = match url {
+                    Some(u) => format!("⚠ {u}: {message}"),
+                    None => format!("⚠ {message}"),
+                };
+                self.bar.println(line);
+            }
+            DumpEvent::RuntimeDetected { .. } | DumpEvent::DownloadStarted { .. } => {}
+        }
+    }
+}
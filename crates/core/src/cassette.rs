@@ -0,0 +1,214 @@
+//! Recording HTTP traffic to disk and replaying it offline.
+//!
+//! A cassette is a directory holding `index.ndjson`, one [`CassetteEntry`] per line, and a
+//! `bodies/` folder with each distinct response body stored under its SHA-256. A
+//! [`RecordingFetcher`] wraps another [`Fetcher`] and appends every request it serves; a
+//! [`ReplayFetcher`] answers the same requests from the cassette without touching the network, so
+//! a dump can be reproduced exactly. When a URL was recorded more than once, the last entry wins.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::source_ledger::content_hash;
+use crate::{FetchError, FetchResponse, Fetcher, Validators};
+
+/// File listing the recorded requests, inside the cassette directory.
+pub const CASSETTE_INDEX: &str = "index.ndjson";
+const BODIES_DIR: &str = "bodies";
+
+/// One recorded request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    /// `GET`, or `HEAD` for existence checks ([`Fetcher::exists`]), which only record 200 or 404.
+    pub method: String,
+    pub url: String,
+    /// URL after redirects.
+    pub final_url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Body path relative to the cassette directory; `None` for error statuses and `HEAD`.
+    pub body: Option<String>,
+}
+
+/// Records every request served by `inner` into a cassette directory; see the module docs.
+pub struct RecordingFetcher {
+    inner: Arc<dyn Fetcher>,
+    dir: PathBuf,
+    index: Mutex<File>,
+}
+
+impl RecordingFetcher {
+    /// Record into `dir`, creating it if needed. Entries are appended to an existing cassette.
+    pub fn new(inner: Arc<dyn Fetcher>, dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(dir.join(BODIES_DIR))?;
+        let index = OpenOptions::new().create(true).append(true).open(dir.join(CASSETTE_INDEX))?;
+        Ok(Self { inner, dir, index: Mutex::new(index) })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn record(&self, entry: &CassetteEntry) {
+        let line = match serde_json::to_string(entry) {
+            Ok(l) => l,
+            Err(e) => return log::warn!("could not record {}: {}", entry.url, e),
+        };
+        let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(index, "{line}") {
+            log::warn!("could not record {}: {}", entry.url, e);
+        }
+    }
+
+    fn store_body(&self, body: &str) -> io::Result<String> {
+        let rel = format!("{BODIES_DIR}/{}", content_hash(body.as_bytes()));
+        let path = self.dir.join(&rel);
+        if !path.exists() {
+            fs::write(&path, body)?;
+        }
+        Ok(rel)
+    }
+
+    /// Record the outcome of a GET of `url`.
+    fn record_get(&self, url: &Url, res: &Result<FetchResponse, FetchError>) {
+        let entry = match res {
+            Ok(resp) => {
+                let body = match self.store_body(&resp.body) {
+                    Ok(rel) => Some(rel),
+                    Err(e) => return log::warn!("could not record body of {}: {}", url, e),
+                };
+                let headers = resp
+                    .headers
+                    .iter()
+                    .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+                    .collect();
+                CassetteEntry { method: "GET".into(), url: url.to_string(), final_url: resp.url.to_string(), status: resp.status, headers, body }
+            }
+            Err(e) => match e.status() {
+                Some(status) => error_entry("GET", url, status),
+                // Network failures are not part of the site's state; replaying reports them as unrecorded.
+                None => return,
+            },
+        };
+        self.record(&entry);
+    }
+}
+
+#[async_trait]
+impl Fetcher for RecordingFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        let res = self.inner.fetch(url).await;
+        self.record_get(url, &res);
+        res
+    }
+
+    async fn check(&self, url: &Url) -> Result<(), FetchError> {
+        let res = self.inner.check(url).await;
+        let status = match &res {
+            Ok(()) => 200,
+            Err(e) => match e.status() {
+                Some(status) => status,
+                // As for GET, a network failure says nothing about the URL.
+                None => return res,
+            },
+        };
+        self.record(&error_entry("HEAD", url, status));
+        res
+    }
+
+    async fn fetch_conditional(&self, url: &Url, validators: &Validators) -> Result<FetchResponse, FetchError> {
+        let res = self.inner.fetch_conditional(url, validators).await;
+        // A 304 has no body and only holds for these validators; replaying it would lose the resource.
+        if !res.as_ref().is_ok_and(FetchResponse::is_not_modified) {
+            self.record_get(url, &res);
+        }
+        res
+    }
+}
+
+fn error_entry(method: &str, url: &Url, status: u16) -> CassetteEntry {
+    CassetteEntry {
+        method: method.into(),
+        url: url.to_string(),
+        final_url: url.to_string(),
+        status,
+        headers: Vec::new(),
+        body: None,
+    }
+}
+
+/// Serves requests from a recorded cassette; unrecorded URLs fail with
+/// [`FetchError::NotRecorded`].
+#[derive(Debug)]
+pub struct ReplayFetcher {
+    dir: PathBuf,
+    get: HashMap<String, CassetteEntry>,
+    head: HashMap<String, CassetteEntry>,
+}
+
+impl ReplayFetcher {
+    /// Load the cassette in `dir`.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        let index = File::open(dir.join(CASSETTE_INDEX))?;
+        let (mut get, mut head) = (HashMap::new(), HashMap::new());
+        for line in BufReader::new(index).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: CassetteEntry =
+                serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let map = if entry.method == "HEAD" { &mut head } else { &mut get };
+            map.insert(entry.url.clone(), entry);
+        }
+        Ok(Self { dir, get, head })
+    }
+
+    /// Number of distinct recorded `GET` URLs.
+    pub fn len(&self) -> usize {
+        self.get.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.get.is_empty()
+    }
+}
+
+#[async_trait]
+impl Fetcher for ReplayFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        let entry = self.get.get(url.as_str()).ok_or_else(|| FetchError::NotRecorded(url.to_string()))?;
+        if !(200..300).contains(&entry.status) {
+            return Err(FetchError::HttpStatus(entry.status));
+        }
+        let body = match &entry.body {
+            Some(rel) => tokio::fs::read_to_string(self.dir.join(rel)).await?,
+            None => String::new(),
+        };
+        let mut headers = HeaderMap::new();
+        for (k, v) in &entry.headers {
+            if let (Ok(k), Ok(v)) = (HeaderName::from_bytes(k.as_bytes()), HeaderValue::from_str(v)) {
+                headers.append(k, v);
+            }
+        }
+        Ok(FetchResponse { url: Url::parse(&entry.final_url)?, status: entry.status, headers, body })
+    }
+
+    async fn check(&self, url: &Url) -> Result<(), FetchError> {
+        match self.head.get(url.as_str()).or_else(|| self.get.get(url.as_str())) {
+            Some(e) if (200..300).contains(&e.status) => Ok(()),
+            Some(e) => Err(FetchError::HttpStatus(e.status)),
+            None => Err(FetchError::NotRecorded(url.to_string())),
+        }
+    }
+}
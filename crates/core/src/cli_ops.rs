@@ -1,5 +1,7 @@
-use crate::{find_sourcemap_urls, parse_sourcemap, sources_list, reconstruct_paths, fetch};
-use std::path::Path;
+use crate::{find_sourcemap_urls, find_sourcemap_urls_with_headers, sourcemap_file_matches, sourcemap_probe_candidates, load_sourcemap_with, load_sourcemap_with_policy, sources_list, reconstruct_paths_with, safe_join, DumpEvent, Observers, SourceLedger, SourceNormalizer, WriteAction, write_in_tree, write_webpack_modules, may_reference, url_label, default_fetcher, FetchResponse, Fetcher, RetryPolicy};
+use reqwest::header::HeaderMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use url::Url;
 
 #[derive(thiserror::Error, Debug)]
@@ -10,49 +12,294 @@ pub enum CLIError {
     UrlParse(#[from] url::ParseError),
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Analysis(#[from] crate::AnalysisError),
+    #[error(transparent)]
+    Har(#[from] crate::HarError),
+    #[error(transparent)]
+    Warc(#[from] crate::WarcError),
     #[error("{0}")]
     Other(String),
 }
 
-/// Async helper to write JS, download sourcemaps, and reconstruct sources.
-pub async fn save_js_and_sources(body: &str, url_str: &str, out_root: &Path) -> Result<(), CLIError> {
+impl CLIError {
+    /// Stable machine-readable name of the error variant, e.g. `"fetch.http_status"` or `"io"`.
+    pub fn kind(&self) -> String {
+        match self {
+            CLIError::Fetch(e) => format!("fetch.{}", e.kind()),
+            CLIError::UrlParse(_) => "url_parse".into(),
+            CLIError::Io(_) => "io".into(),
+            CLIError::Analysis(_) => "analysis".into(),
+            CLIError::Har(_) => "har".into(),
+            CLIError::Warc(_) => "warc".into(),
+            CLIError::Other(_) => "other".into(),
+        }
+    }
+}
+
+/// Options controlling how downloaded assets are saved.
+#[derive(Debug, Clone)]
+pub struct SaveOptions {
+    /// When an asset declares no (loadable) sourcemap, probe conventional locations next to it
+    /// (see [`sourcemap_probe_candidates`]).
+    pub probe_maps: bool,
+    /// How sourcemap `sources` URLs are mapped to paths under the output directory.
+    pub normalizer: Arc<SourceNormalizer>,
+    /// Run-wide record of written sources; deduplicates sources shared between chunks and
+    /// applies its [`crate::ConflictPolicy`] when their contents differ.
+    pub ledger: Arc<SourceLedger>,
+    /// Receive [`DumpEvent`]s for maps parsed, files written and maps that failed to load.
+    pub observers: Observers,
+    /// Loads sourcemaps (remote, `file://` or `data:`); defaults to [`default_fetcher`].
+    pub fetcher: Arc<dyn Fetcher>,
+    /// When and how often sourcemaps and index-map sections are tried again. A [`crate::Dumper`]
+    /// sets it from [`crate::DumpOptions::retry`].
+    pub retry: RetryPolicy,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self {
+            probe_maps: false,
+            normalizer: Arc::default(),
+            ledger: Arc::default(),
+            observers: Observers::default(),
+            fetcher: default_fetcher(),
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+/// What was recovered for a saved asset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveOutcome {
+    /// Sourcemaps declared by comment or response header that loaded and parsed.
+    pub declared_maps: Vec<Url>,
+    /// Sourcemaps found by probing.
+    pub probed_maps: Vec<Url>,
+    /// Webpack modules split out because no sourcemap was found.
+    pub split_modules: usize,
+    /// Original sources written (including conflict variants).
+    pub written_sources: usize,
+    /// Original sources skipped because identical contents were already written.
+    pub duplicate_sources: usize,
+    /// Original sources whose contents conflicted with an earlier version of the same path.
+    pub conflicting_sources: usize,
+    /// Declared sourcemaps that could not be fetched or read.
+    pub missing_maps: Vec<Url>,
+    /// Declared sourcemaps that were fetched but did not parse.
+    pub unparseable_maps: Vec<Url>,
+    /// Every original source found in the loaded maps, with what the ledger did with it.
+    pub sources: Vec<SavedSource>,
+}
+
+/// An original source reconstructed from a sourcemap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedSource {
+    /// Path relative to the output root.
+    pub path: PathBuf,
+    /// The sourcemap it came from, as [`crate::url_label`] names it.
+    pub map: String,
+    pub action: WriteAction,
+}
+
+/// Async helper to write a JS (or CSS) asset, download its sourcemaps, and reconstruct sources.
+/// Pass the same `opts` to every save of a run so its ledger deduplicates sources across assets.
+pub async fn save_js_and_sources(body: &str, url_str: &str, out_root: &Path, opts: &SaveOptions) -> Result<SaveOutcome, CLIError> {
+    save_js_and_sources_with_headers(body, url_str, &HeaderMap::new(), out_root, opts).await
+}
+
+/// [`save_js_and_sources`] for a fetched asset whose response headers may advertise a sourcemap
+/// (`SourceMap` / `X-SourceMap`) in addition to the `sourceMappingURL` comment.
+pub async fn save_js_and_sources_with_headers(
+    body: &str,
+    url_str: &str,
+    headers: &HeaderMap,
+    out_root: &Path,
+    opts: &SaveOptions,
+) -> Result<SaveOutcome, CLIError> {
     let parsed = Url::parse(url_str)?;
     let rel_path = parsed.path().trim_start_matches('/');
-    let dest_path = out_root.join(rel_path);
-    if let Some(parent) = dest_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-    std::fs::write(&dest_path, body)?;
+    let map_urls = find_sourcemap_urls_with_headers(&parsed, body, headers);
+    save_asset_with_options(body, Path::new(rel_path), Some(&parsed), map_urls, out_root, opts).await
+}
+
+/// [`save_js_and_sources_with_headers`] for `resp`, fetched from `url_str`. Sourcemaps are resolved
+/// and probed against `resp.url`, the URL after redirects; the asset is still written under and
+/// attributed to `url_str`.
+pub async fn save_fetched_asset(url_str: &str, resp: &FetchResponse, out_root: &Path, opts: &SaveOptions) -> Result<SaveOutcome, CLIError> {
+    let parsed = Url::parse(url_str)?;
+    let rel_path = parsed.path().trim_start_matches('/');
+    let map_urls = find_sourcemap_urls_with_headers(&resp.url, &resp.body, &resp.headers);
+    save_asset(&resp.body, Path::new(rel_path), parsed.to_string(), Some(&resp.url), map_urls, out_root, opts).await
+}
+
+/// Write `body` to `out_root/dest_rel`, then load each sourcemap in `map_urls` (remote or
+/// `file://`, or inline `data:`), store it next to the asset and reconstruct its sources under
+/// `out_root`.
+///
+/// When no sourcemap could be loaded for a `.js` asset, its webpack modules (if any) are split
+/// into `out_root/webpack_modules/` instead. As with [`save_js_and_sources`], saves sharing `opts`
+/// share its ledger.
+pub async fn save_asset_with_maps(
+    body: &str,
+    dest_rel: &Path,
+    map_urls: Vec<Url>,
+    out_root: &Path,
+    opts: &SaveOptions,
+) -> Result<SaveOutcome, CLIError> {
+    save_asset_with_options(body, dest_rel, None, map_urls, out_root, opts).await
+}
 
-    // Detect sourcemap URLs
-    let base_url = &parsed;
-    let map_urls = find_sourcemap_urls(base_url, body);
+/// [`save_asset_with_maps`] with [`SaveOptions`]. `asset_url` is where the asset was loaded from;
+/// probing is skipped without it.
+pub async fn save_asset_with_options(
+    body: &str,
+    dest_rel: &Path,
+    asset_url: Option<&Url>,
+    map_urls: Vec<Url>,
+    out_root: &Path,
+    opts: &SaveOptions,
+) -> Result<SaveOutcome, CLIError> {
+    let contributor = asset_url.map_or_else(|| dest_rel.to_string_lossy().into_owned(), |u| u.to_string());
+    save_asset(body, dest_rel, contributor, asset_url, map_urls, out_root, opts).await
+}
+
+/// [`save_asset_with_options`], attributing written files to `contributor` and probing next to
+/// `asset_url`.
+async fn save_asset(
+    body: &str,
+    dest_rel: &Path,
+    contributor: String,
+    asset_url: Option<&Url>,
+    map_urls: Vec<Url>,
+    out_root: &Path,
+    opts: &SaveOptions,
+) -> Result<SaveOutcome, CLIError> {
+    let dest_path = safe_join(out_root, &dest_rel.to_string_lossy());
+    write_in_tree(out_root, &dest_path, body)?;
+    emit_written(opts, &contributor, out_root, &dest_path);
+
+    let mut outcome = SaveOutcome::default();
     for mu in map_urls {
-        // Fetch sourcemap (allow local join)
-        let map_text_opt = if mu.scheme() == "file" {
-            mu.to_file_path().ok().and_then(|p| std::fs::read_to_string(&p).ok())
-        } else {
-            fetch::fetch_with_retries(mu.as_str(), 3).await.ok()
+        if asset_url.is_some_and(|a| !may_reference(a, &mu)) {
+            let message = format!("ignoring local sourcemap {} of a non-local asset", mu);
+            opts.observers.emit(DumpEvent::Warning { url: Some(contributor.clone()), message });
+            continue;
+        }
+        let map_str = match opts.fetcher.fetch_with_policy(&mu, &opts.retry).await {
+            Ok(resp) => resp.body,
+            Err(e) => {
+                let message = format!("sourcemap {} could not be loaded: {}", url_label(&mu), e);
+                opts.observers.emit(DumpEvent::Warning { url: Some(contributor.clone()), message });
+                outcome.missing_maps.push(mu);
+                continue;
+            }
         };
+        if write_map_and_sources(&map_str, &mu, &dest_path, out_root, opts, &contributor, &mut outcome).await? {
+            outcome.declared_maps.push(mu);
+        } else {
+            let message = format!("sourcemap {} could not be parsed", url_label(&mu));
+            opts.observers.emit(DumpEvent::Warning { url: Some(contributor.clone()), message });
+            outcome.unparseable_maps.push(mu);
+        }
+    }
 
-        let map_str = match map_text_opt { Some(s) => s, None => continue };
+    if outcome.declared_maps.is_empty() && opts.probe_maps {
+        if let Some(asset_url) = asset_url {
+            if let Some((mu, map_str)) = probe_sourcemap(opts, asset_url).await {
+                write_map_and_sources(&map_str, &mu, &dest_path, out_root, opts, &contributor, &mut outcome).await?;
+                outcome.probed_maps.push(mu);
+            }
+        }
+    }
 
-        // Write sourcemap file alongside js
-        let map_dest = dest_path.with_extension("js.map");
-        if let Some(parent) = map_dest.parent() { std::fs::create_dir_all(parent)?; }
-        std::fs::write(&map_dest, &map_str)?;
+    let have_map = !outcome.declared_maps.is_empty() || !outcome.probed_maps.is_empty();
+    if !have_map && dest_rel.extension().is_some_and(|e| e == "js") {
+        let modules = write_webpack_modules(body, out_root, &opts.ledger, &contributor)?;
+        for (path, action) in &modules {
+            match action {
+                WriteAction::Written | WriteAction::Replaced => emit_written(opts, &contributor, out_root, path),
+                WriteAction::Variant(v) => emit_written(opts, &contributor, out_root, v),
+                WriteAction::Duplicate | WriteAction::Discarded => {}
+            }
+        }
+        outcome.split_modules = modules.len();
+    }
+    Ok(outcome)
+}
+
+/// Try each conventional candidate location; the first that parses as a sourcemap whose `file`
+/// field matches the asset wins.
+async fn probe_sourcemap(opts: &SaveOptions, asset_url: &Url) -> Option<(Url, String)> {
+    let fetcher = opts.fetcher.as_ref();
+    for candidate in sourcemap_probe_candidates(asset_url) {
+        let Ok(map_str) = fetcher.fetch_text(&candidate, 1).await else { continue };
+        match load_sourcemap_with_policy(fetcher, &map_str, &candidate, &opts.retry).await {
+            Ok(sm) if sourcemap_file_matches(sm.get_file(), asset_url) => return Some((candidate, map_str)),
+            _ => continue,
+        }
+    }
+    None
+}
+
+/// Store a sourcemap loaded from `map_url` next to the asset and reconstruct its sources (across
+/// all sections, for index maps) through the run's ledger, counting into `outcome`. Returns
+/// whether the map parsed.
+async fn write_map_and_sources(
+    map_str: &str,
+    map_url: &Url,
+    dest_path: &Path,
+    out_root: &Path,
+    opts: &SaveOptions,
+    contributor: &str,
+    outcome: &mut SaveOutcome,
+) -> Result<bool, CLIError> {
+    // Write sourcemap file alongside the asset (`app.js` -> `app.js.map`, `app.css` -> `app.css.map`)
+    let mut map_name = dest_path.to_path_buf().into_os_string();
+    map_name.push(".map");
+    let map_dest = PathBuf::from(map_name);
+    write_in_tree(out_root, &map_dest, map_str)?;
+    emit_written(opts, contributor, out_root, &map_dest);
 
-        if let Ok(sm) = parse_sourcemap(&map_str) {
-            let paths = reconstruct_paths(out_root, &sm);
-            for (idx, p) in paths.iter().enumerate() {
-                if let Some(content) = sm.get_source_contents(idx as u32) {
-                    if let Some(parent) = p.parent() { std::fs::create_dir_all(parent)?; }
-                    std::fs::write(p, content)?;
+    let Ok(sm) = load_sourcemap_with_policy(opts.fetcher.as_ref(), map_str, map_url, &opts.retry).await else { return Ok(false) };
+    let map_label = url_label(map_url);
+    opts.observers.emit(DumpEvent::SourcemapParsed {
+        chunk: contributor.to_string(),
+        map: map_label.clone(),
+        sources: sm.get_source_count() as usize,
+    });
+    let paths = reconstruct_paths_with(out_root, &sm, &opts.normalizer);
+    for (idx, p) in paths.iter().enumerate() {
+        if let Some(content) = sm.get_source_contents(idx as u32) {
+            let action = opts.ledger.write_source(out_root, p, content, contributor)?;
+            match action {
+                WriteAction::Written => outcome.written_sources += 1,
+                WriteAction::Duplicate => outcome.duplicate_sources += 1,
+                WriteAction::Discarded => outcome.conflicting_sources += 1,
+                WriteAction::Replaced | WriteAction::Variant(_) => {
+                    outcome.written_sources += 1;
+                    outcome.conflicting_sources += 1;
                 }
             }
+            match &action {
+                WriteAction::Written | WriteAction::Replaced => emit_written(opts, contributor, out_root, p),
+                WriteAction::Variant(v) => emit_written(opts, contributor, out_root, &out_root.join(v)),
+                WriteAction::Duplicate | WriteAction::Discarded => {}
+            }
+            let path = p.strip_prefix(out_root).unwrap_or(p).to_path_buf();
+            outcome.sources.push(SavedSource { path, map: map_label.clone(), action });
         }
     }
-    Ok(())
+    Ok(true)
+}
+
+fn emit_written(opts: &SaveOptions, chunk: &str, out_root: &Path, path: &Path) {
+    if opts.observers.is_empty() {
+        return;
+    }
+    let path = path.strip_prefix(out_root).unwrap_or(path).to_path_buf();
+    opts.observers.emit(DumpEvent::FileWritten { chunk: chunk.to_string(), path });
 }
 
 /// Handle the list-urls command logic
@@ -83,23 +330,28 @@ pub async fn handle_list_urls(input: &str, json: bool, show_sources: bool) -> Re
     }
 
     if show_sources {
+        let fetcher = default_fetcher();
         for u in &urls {
-            if u.scheme() == "file" {
-                if let Ok(path) = u.to_file_path() {
-                    if let Ok(map_contents) = std::fs::read_to_string(&path) {
-                        match parse_sourcemap(&map_contents) {
-                            Ok(sm) => {
-                                println!("\n# sources in {}", path.display());
-                                for s in sources_list(&sm) {
-                                    println!("  {s}");
-                                }
-                            }
-                            Err(e) => eprintln!("failed to parse sourcemap {}: {e}", path.display()),
-                        }
-                    } else {
-                        eprintln!("could not read map file {}", path.display());
+            let label = match u.scheme() {
+                "file" => u.to_file_path().map_or_else(|_| u.to_string(), |p| p.display().to_string()),
+                "data" => "inline data: sourcemap".to_string(),
+                _ => u.to_string(),
+            };
+            let map_contents = match fetcher.fetch_text(u, 1).await {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("could not load sourcemap {label}: {e}");
+                    continue;
+                }
+            };
+            match load_sourcemap_with(fetcher.as_ref(), &map_contents, u).await {
+                Ok(sm) => {
+                    println!("\n# sources in {label}");
+                    for s in sources_list(&sm) {
+                        println!("  {s}");
                     }
                 }
+                Err(e) => eprintln!("failed to parse sourcemap {label}: {e}"),
             }
         }
     }
@@ -0,0 +1,102 @@
+//! Decoding of `data:` URLs (RFC 2397), as used for inline sourcemaps
+//! (`//# sourceMappingURL=data:application/json;charset=utf-8;base64,...`).
+
+use base64::engine::{general_purpose::GeneralPurpose, DecodePaddingMode, GeneralPurposeConfig};
+use base64::{alphabet, Engine};
+use percent_encoding::percent_decode_str;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DataUrlError {
+    #[error("not a data: url")]
+    NotDataUrl,
+    #[error("data: url is missing the ',' separator")]
+    MissingComma,
+    #[error("invalid base64 in data: url: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("unsupported charset: {0}")]
+    UnsupportedCharset(String),
+}
+
+/// Base64 as browsers decode it: padding is optional.
+const FORGIVING: GeneralPurpose =
+    GeneralPurpose::new(&alphabet::STANDARD, GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent));
+
+/// A decoded `data:` URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUrl {
+    /// Lower-cased media type, `text/plain` when omitted.
+    pub mime_type: String,
+    /// Lower-cased `charset` parameter, if given.
+    pub charset: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+impl DataUrl {
+    /// Decode the payload as text according to `charset` (UTF-8 when absent).
+    pub fn text(&self) -> Result<String, DataUrlError> {
+        let bytes = self.bytes.as_slice();
+        match self.charset.as_deref().unwrap_or("utf-8") {
+            "utf-8" | "utf8" => {
+                let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+                Ok(String::from_utf8_lossy(bytes).into_owned())
+            }
+            "us-ascii" | "ascii" | "iso-8859-1" | "latin1" | "l1" => Ok(bytes.iter().map(|&b| b as char).collect()),
+            "utf-16le" | "utf-16be" | "utf-16" => {
+                let be = self.charset.as_deref() == Some("utf-16be") || bytes.starts_with(b"\xFE\xFF");
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|c| if be { u16::from_be_bytes([c[0], c[1]]) } else { u16::from_le_bytes([c[0], c[1]]) })
+                    .collect();
+                let text = String::from_utf16_lossy(&units);
+                Ok(text.strip_prefix('\u{FEFF}').map(str::to_owned).unwrap_or(text))
+            }
+            other => Err(DataUrlError::UnsupportedCharset(other.to_string())),
+        }
+    }
+}
+
+/// Parse and decode a `data:` URL. Both base64 and percent-encoded payloads are supported.
+pub fn decode_data_url(input: &str) -> Result<DataUrl, DataUrlError> {
+    let rest = input
+        .get(..5)
+        .filter(|s| s.eq_ignore_ascii_case("data:"))
+        .map(|_| &input[5..])
+        .ok_or(DataUrlError::NotDataUrl)?;
+    let (meta, payload) = rest.split_once(',').ok_or(DataUrlError::MissingComma)?;
+
+    let mut params = meta.split(';').map(str::trim);
+    let mime_type = params.next().filter(|m| !m.is_empty()).unwrap_or("text/plain").to_ascii_lowercase();
+    let mut charset = None;
+    let mut base64 = false;
+    for p in params {
+        if p.eq_ignore_ascii_case("base64") {
+            base64 = true;
+        } else if let Some((k, v)) = p.split_once('=') {
+            if k.trim().eq_ignore_ascii_case("charset") {
+                charset = Some(percent_decode_str(v.trim().trim_matches('"')).decode_utf8_lossy().to_ascii_lowercase());
+            }
+        }
+    }
+
+    let raw: Vec<u8> = percent_decode_str(payload).collect();
+    let bytes = if base64 {
+        let cleaned: Vec<u8> = raw.into_iter().filter(|b| !b.is_ascii_whitespace()).collect();
+        FORGIVING.decode(cleaned)?
+    } else {
+        raw
+    };
+    Ok(DataUrl { mime_type, charset, bytes })
+}
+
+/// `url` as reports and events name it: `data:` URLs, which carry a whole inline sourcemap, are
+/// shortened to `data:<mime type>;<n> bytes`; every other URL is kept as is.
+pub fn url_label(url: &url::Url) -> String {
+    if url.scheme() != "data" {
+        return url.to_string();
+    }
+    match decode_data_url(url.as_str()) {
+        Ok(data) => format!("data:{};{} bytes", data.mime_type, data.bytes.len()),
+        Err(_) => format!("data:;{} bytes", url.as_str().len()),
+    }
+}
@@ -0,0 +1,153 @@
+//! Runtime detector plugin interface.
+//!
+//! A [`RuntimeDetector`] inspects a page (HTML plus the scripts it references), reports how
+//! confident it is that a particular bundler runtime powers the page, and enumerates every chunk
+//! and asset URL that runtime can load. The dump pipeline ([`crate::Dumper`]) asks a
+//! [`DetectorRegistry`] for all matching detectors and enumerates them in order of confidence.
+//!
+//! The built-in webpack and Vite detectors live in the `detectors` crate.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use url::Url;
+
+use crate::{default_fetcher, extract_script_urls, CLIError, FetchError, Fetcher, LocalBundle};
+
+#[derive(thiserror::Error, Debug)]
+pub enum DetectorError {
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+    #[error("invalid url: {0}")]
+    UrlParse(#[from] url::ParseError),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<DetectorError> for CLIError {
+    fn from(e: DetectorError) -> Self {
+        match e {
+            DetectorError::Fetch(f) => CLIError::Fetch(f),
+            DetectorError::UrlParse(p) => CLIError::UrlParse(p),
+            DetectorError::Other(s) => CLIError::Other(s),
+        }
+    }
+}
+
+/// Everything a detector may look at when deciding whether it applies to a page.
+#[derive(Debug, Clone)]
+pub struct PageContext {
+    pub page_url: Url,
+    pub html: String,
+    /// Absolute URLs of every `<script src>` on the page, in document order.
+    pub scripts: Vec<Url>,
+    /// What detectors fetch runtime scripts and manifests with.
+    pub fetcher: Arc<dyn Fetcher>,
+}
+
+impl PageContext {
+    /// Build a context from fetched HTML, extracting script URLs relative to `page_url`.
+    pub fn from_html(page_url: Url, html: String) -> Self {
+        let scripts = extract_script_urls(&html, &page_url);
+        Self { page_url, html, scripts, fetcher: default_fetcher() }
+    }
+
+    /// Fetch through `fetcher` instead of the [`default_fetcher`].
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn Fetcher>) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
+    /// Build a context for a build directory or bundle on disk.
+    ///
+    /// Root-relative references in the build's `index.html` (`src="/assets/..."`) point at the
+    /// deploy root, which locally is the bundle root rather than the filesystem root, so they are
+    /// rewritten to be relative before resolution. Every script found on disk is appended to
+    /// `scripts` so runtimes are detected even without an HTML entry point.
+    pub fn for_local_bundle(bundle: &LocalBundle) -> Self {
+        static ROOT_RELATIVE_RE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r#"(?i)\b(src|href)\s*=\s*(["'])/([^/])"#).unwrap());
+
+        let html = bundle
+            .index_html
+            .as_deref()
+            .map(|h| ROOT_RELATIVE_RE.replace_all(h, "$1=$2./$3").into_owned())
+            .unwrap_or_default();
+        let page_url = bundle.root_url.join("index.html").unwrap_or_else(|_| bundle.root_url.clone());
+        let mut page = Self::from_html(page_url, html);
+        for u in bundle.script_urls() {
+            if !page.scripts.contains(&u) {
+                page.scripts.push(u);
+            }
+        }
+        page
+    }
+}
+
+/// A positive detection result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    /// How sure the detector is, in `0.0..=1.0`.
+    pub confidence: f32,
+    /// The script that hosts the runtime (e.g. `webpack-<hash>.js`), when there is one.
+    pub entry: Option<Url>,
+}
+
+/// Chunk and asset URLs enumerated by a detector.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkPlan {
+    /// Short name of the strategy that produced the URLs (e.g. `build-manifest`).
+    pub strategy: String,
+    /// Candidate URLs, deduplicated and unvalidated.
+    pub urls: Vec<Url>,
+}
+
+/// A bundler runtime that the dump pipeline knows how to enumerate.
+#[async_trait]
+pub trait RuntimeDetector: Send + Sync {
+    /// Stable identifier, e.g. `"webpack"`.
+    fn name(&self) -> &'static str;
+
+    /// Inspect the page and return `Some` if this runtime appears to be present.
+    fn detect(&self, page: &PageContext) -> Option<Detection>;
+
+    /// Enumerate the chunk/asset URLs the runtime can load. Implementations may fetch the runtime
+    /// script or manifests referenced by the page.
+    async fn enumerate(&self, page: &PageContext, detection: &Detection) -> Result<ChunkPlan, DetectorError>;
+}
+
+/// Ordered collection of detectors consulted by the dump pipeline.
+pub struct DetectorRegistry {
+    detectors: Vec<Box<dyn RuntimeDetector>>,
+}
+
+impl DetectorRegistry {
+    /// An empty registry with no detectors.
+    pub fn empty() -> Self {
+        Self { detectors: Vec::new() }
+    }
+
+    /// Add a detector. Detectors registered earlier win ties on confidence.
+    pub fn register<D: RuntimeDetector + 'static>(&mut self, detector: D) -> &mut Self {
+        self.detectors.push(Box::new(detector));
+        self
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.detectors.iter().map(|d| d.name()).collect()
+    }
+
+    /// Run every detector against the page and return the matches, most confident first.
+    pub fn detect(&self, page: &PageContext) -> Vec<(&dyn RuntimeDetector, Detection)> {
+        let mut hits: Vec<(&dyn RuntimeDetector, Detection)> = self
+            .detectors
+            .iter()
+            .filter_map(|d| d.detect(page).map(|det| (d.as_ref(), det)))
+            .collect();
+        // Stable sort keeps registration order for equal confidence.
+        hits.sort_by(|a, b| b.1.confidence.total_cmp(&a.1.confidence));
+        hits
+    }
+}
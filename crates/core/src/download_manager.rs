@@ -1,47 +1,85 @@
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 use tokio::task;
-use reqwest::Client;
 
-use crate::fetch::{fetch_with_retries, FetchError};
+use url::Url;
+
+use crate::events::{DumpEvent, Observers};
+use crate::fetch::{FetchError, FetchResponse};
+use crate::fetcher::{default_fetcher, Fetcher};
+use crate::retry::RetryPolicy;
+use crate::throttle::{Throttle, ThrottledFetcher};
 
 /// Result of a download attempt
 pub struct DownloadResult {
     pub url: String,
-    pub content: Option<String>,
-    pub error: Option<FetchError>,
+    /// The response (final URL, status, headers, body), or why there is none.
+    pub response: Result<FetchResponse, FetchError>,
+}
+
+impl DownloadResult {
+    /// The downloaded body, if the download succeeded.
+    pub fn content(&self) -> Option<&str> {
+        self.response.as_ref().ok().map(|r| r.body.as_str())
+    }
 }
 
 /// Download manager configuration
 pub struct DownloadManagerConfig {
     pub concurrency: usize,
-    pub retry_attempts: usize,
+    /// When and how often failed downloads are tried again.
+    pub retry: RetryPolicy,
+    /// Receive `DownloadStarted` / `DownloadFinished` events.
+    pub observers: Observers,
+    /// Where the downloads come from; defaults to [`default_fetcher`].
+    pub fetcher: Arc<dyn Fetcher>,
+    /// Per-host rate limit, concurrency cap and slow-down for every download; `None` to only
+    /// honor `concurrency`.
+    pub throttle: Option<Arc<Throttle>>,
 }
 
 impl Default for DownloadManagerConfig {
     fn default() -> Self {
         Self {
             concurrency: 8,
-            retry_attempts: 3,
+            retry: RetryPolicy::default(),
+            observers: Observers::default(),
+            fetcher: default_fetcher(),
+            throttle: None,
         }
     }
 }
 
 /// Concurrently download a list of URLs using a worker pool.
 pub async fn download_many(urls: Vec<String>, config: DownloadManagerConfig) -> Vec<DownloadResult> {
-    let (tx, rx) = mpsc::channel::<String>(config.concurrency * 2);
-    let (result_tx, mut result_rx) = mpsc::channel::<DownloadResult>(config.concurrency * 2);
-    let client = Arc::new(Client::builder().build().unwrap());
+    let mut result_rx = download_stream(urls, config);
+    let mut results = Vec::new();
+    while let Some(res) = result_rx.recv().await {
+        results.push(res);
+    }
+    results
+}
+
+/// Like [`download_many`], but yields each result as soon as it completes. The channel closes
+/// once every URL has been downloaded.
+pub fn download_stream(urls: Vec<String>, config: DownloadManagerConfig) -> mpsc::Receiver<DownloadResult> {
+    let concurrency = config.concurrency.max(1);
+    let (tx, rx) = mpsc::channel::<String>(concurrency * 2);
+    let (result_tx, result_rx) = mpsc::channel::<DownloadResult>(concurrency * 2);
     let rx = Arc::new(Mutex::new(rx));
+    let fetcher: Arc<dyn Fetcher> = match config.throttle {
+        Some(throttle) => Arc::new(ThrottledFetcher::new(config.fetcher, throttle)),
+        None => config.fetcher,
+    };
 
     // Spawn workers
-    let mut handles = Vec::new();
-    for _ in 0..config.concurrency {
+    for _ in 0..concurrency {
         let rx = Arc::clone(&rx);
         let result_tx = result_tx.clone();
-        let client = client.clone();
-        let attempts = config.retry_attempts;
-        let handle = task::spawn(async move {
+        let retry = config.retry.clone();
+        let observers = config.observers.clone();
+        let fetcher = Arc::clone(&fetcher);
+        task::spawn(async move {
             loop {
                 let url = {
                     let mut rx = rx.lock().await;
@@ -51,33 +89,29 @@ pub async fn download_many(urls: Vec<String>, config: DownloadManagerConfig) ->
                     Some(u) => u,
                     None => break,
                 };
-                let res = fetch_with_retries(&url, attempts).await;
-                let (content, error) = match res {
-                    Ok(body) => (Some(body), None),
-                    Err(e) => (None, Some(e)),
+                observers.emit(DumpEvent::DownloadStarted { url: url.clone() });
+                let response = match Url::parse(&url) {
+                    Ok(u) => fetcher.fetch_with_policy(&u, &retry).await,
+                    Err(e) => Err(e.into()),
                 };
-                let _ = result_tx.send(DownloadResult { url: url.clone(), content, error }).await;
+                observers.emit(DumpEvent::DownloadFinished {
+                    url: url.clone(),
+                    status: response.as_ref().ok().map(|r| r.status),
+                    bytes: response.as_ref().map_or(0, |r| r.body.len()),
+                    error: response.as_ref().err().map(ToString::to_string),
+                });
+                let _ = result_tx.send(DownloadResult { url: url.clone(), response }).await;
             }
         });
-        handles.push(handle);
-    }
-
-    // Feed URLs to workers
-    for url in urls {
-        let _ = tx.send(url).await;
     }
-    drop(tx); // Close channel
     drop(result_tx);
 
-    // Wait for all workers to finish
-    for handle in handles {
-        let _ = handle.await;
-    }
+    // Feed URLs to workers; the channel closes when the feeder finishes
+    task::spawn(async move {
+        for url in urls {
+            let _ = tx.send(url).await;
+        }
+    });
 
-    // Collect results
-    let mut results = Vec::new();
-    while let Some(res) = result_rx.recv().await {
-        results.push(res);
-    }
-    results
+    result_rx
 } 
\ No newline at end of file
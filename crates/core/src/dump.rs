@@ -0,0 +1,623 @@
+//! The dump pipeline: from a page URL or a local build to a reconstructed source tree.
+//!
+//! ```no_run
+//! # async fn run(registry: resourcer_core::DetectorRegistry) -> Result<(), resourcer_core::CLIError> {
+//! use resourcer_core::{DumpOptions, DumpTarget, Dumper};
+//!
+//! let dumper = Dumper::new(registry).with_options(DumpOptions { concurrency: 4, ..Default::default() });
+//! let result = dumper.run(&DumpTarget::Url("https://example.com/".into())).await?;
+//! println!("{} chunks saved to {:?}", result.report.summary().chunks_saved, result.out_dir);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Remote dumps fetch the page, pick the most confident detector that yields URLs, validate and
+//! download the chunks and reconstruct their sources. Local dumps process every script and
+//! stylesheet of a bundle on disk, plus anything the detected runtime references. HAR dumps do the
+//! same for every script and stylesheet in a browser capture, serving sourcemaps from it too. WARC
+//! dumps run the remote pipeline against a crawl archive instead of the network.
+//!
+//! Remote and WARC dumps keep a [`RunJournal`] in the output directory, so an interrupted run can
+//! be resumed with [`DumpOptions::resume`]. They also rewrite the source ledger after every saved
+//! chunk, and a resumed run starts from it, so conflict policies and the ledger span both runs.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use url::Url;
+
+use crate::detect::{ChunkPlan, DetectorRegistry, PageContext};
+use crate::{
+    collect_local_bundle, download_stream, ensure_output_dir, find_sourcemap_urls, may_reference, read_js_file,
+    save_asset_with_options, save_fetched_asset, validate_chunk_urls_with_policy, AnalysisError, CLIError,
+    DownloadManagerConfig, DumpEvent, DumpObserver, DumpReport, FetchError, Fetcher, HarArchive, JournalState, RetryPolicy,
+    RunJournal, SaveOptions, SaveOutcome, Throttle, ThrottledFetcher, WarcArchive,
+};
+
+/// What to dump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DumpTarget {
+    /// A page served over HTTP(S).
+    Url(String),
+    /// A bundle file or build directory (`dist/`, `.next/`) on disk.
+    Local(PathBuf),
+    /// A HAR capture exported from browser devtools.
+    Har(PathBuf),
+    /// A WARC crawl archive (`.warc` or `.warc.gz`), dumped as if it were the live site starting
+    /// from `page` (default: the first archived HTML page).
+    Warc { path: PathBuf, page: Option<String> },
+}
+
+impl DumpTarget {
+    /// The URL or path, as recorded in [`DumpReport::target`].
+    pub fn label(&self) -> String {
+        match self {
+            DumpTarget::Url(u) => u.clone(),
+            DumpTarget::Local(p) | DumpTarget::Har(p) | DumpTarget::Warc { path: p, .. } => p.display().to_string(),
+        }
+    }
+}
+
+/// Options for a [`Dumper`] run.
+#[derive(Debug, Clone)]
+pub struct DumpOptions {
+    /// Output directory. Defaults to `out/<host>` for URLs and `out/<bundle dir name>` for local
+    /// bundles.
+    pub out_dir: Option<PathBuf>,
+    /// Detect and enumerate only; do not download or write anything.
+    pub dry_run: bool,
+    /// Maximum concurrent downloads (1 = sequential).
+    pub concurrency: usize,
+    /// When and how often the page fetch, URL validation and chunk downloads are tried again.
+    pub retry: RetryPolicy,
+    /// Process at most this many chunks/files.
+    pub max_files: Option<usize>,
+    /// Check enumerated URLs with HEAD/GET before downloading (remote only).
+    pub validate_urls: bool,
+    /// HAR dumps: fetch chunks and sourcemaps missing from the capture over the network.
+    pub network_fallback: bool,
+    /// Remote and WARC dumps: continue the run journaled in the output directory, skipping the
+    /// page fetch and every chunk whose files are already written with matching hashes.
+    pub resume: bool,
+    /// Per-host politeness for chunk downloads, sequential or concurrent. To throttle every
+    /// request of the run (page, validation, sourcemaps), wrap the fetcher in a
+    /// [`crate::ThrottledFetcher`] instead.
+    pub throttle: Option<Arc<Throttle>>,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self {
+            out_dir: None,
+            dry_run: false,
+            concurrency: 8,
+            retry: RetryPolicy::default(),
+            max_files: None,
+            validate_urls: true,
+            network_fallback: false,
+            resume: false,
+            throttle: None,
+        }
+    }
+}
+
+/// Running totals of how each saved asset's sources were recovered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DumpStats {
+    pub declared_maps: usize,
+    pub probed_maps: usize,
+    pub split_modules: usize,
+    pub written_sources: usize,
+    pub duplicate_sources: usize,
+    pub conflicting_sources: usize,
+}
+
+impl DumpStats {
+    pub fn add(&mut self, outcome: &SaveOutcome) {
+        self.declared_maps += outcome.declared_maps.len();
+        self.probed_maps += outcome.probed_maps.len();
+        self.split_modules += outcome.split_modules;
+        self.written_sources += outcome.written_sources;
+        self.duplicate_sources += outcome.duplicate_sources;
+        self.conflicting_sources += outcome.conflicting_sources;
+    }
+}
+
+/// Result of a [`Dumper`] run.
+#[derive(Debug, Clone, Default)]
+pub struct DumpResult {
+    /// Where files were written; `None` for dry runs and runs that found nothing to download.
+    pub out_dir: Option<PathBuf>,
+    /// Chunk URLs (or `file://` URLs of local files) that were selected for processing, after
+    /// validation and `max_files`.
+    pub planned: Vec<Url>,
+    /// Planned chunks left alone because a resumed run had already saved them.
+    pub skipped: Vec<Url>,
+    pub stats: DumpStats,
+    pub report: DumpReport,
+    /// Path of the source ledger, if one was written (see [`crate::SourceLedger::write_json`]).
+    pub ledger_path: Option<PathBuf>,
+}
+
+/// Reusable dump pipeline; see the module docs.
+pub struct Dumper {
+    registry: DetectorRegistry,
+    options: DumpOptions,
+    save: SaveOptions,
+}
+
+impl Dumper {
+    /// A dumper that detects runtimes with `registry` and uses default options.
+    pub fn new(registry: DetectorRegistry) -> Self {
+        Self { registry, options: DumpOptions::default(), save: SaveOptions::default() }
+    }
+
+    pub fn with_options(mut self, options: DumpOptions) -> Self {
+        self.save.retry = options.retry.clone();
+        self.options = options;
+        self
+    }
+
+    /// How assets are saved and sources reconstructed (probing, path mapping, conflict policy),
+    /// and the [`Fetcher`] every request of the run goes through. Replaces observers and the
+    /// fetcher set earlier with those in `save`. Its retry policy is always
+    /// [`DumpOptions::retry`].
+    pub fn with_save_options(mut self, save: SaveOptions) -> Self {
+        self.save = SaveOptions { retry: self.options.retry.clone(), ..save };
+        self
+    }
+
+    /// Load pages, chunks and sourcemaps through `fetcher` (e.g. a [`crate::MemoryFetcher`] in
+    /// tests) instead of the [`crate::default_fetcher`].
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn Fetcher>) -> Self {
+        self.save.fetcher = fetcher;
+        self
+    }
+
+    /// Receive [`DumpEvent`]s while the pipeline runs.
+    pub fn with_observer(mut self, observer: Arc<dyn DumpObserver>) -> Self {
+        self.save.observers.add(observer);
+        self
+    }
+
+    pub fn options(&self) -> &DumpOptions {
+        &self.options
+    }
+
+    pub fn registry(&self) -> &DetectorRegistry {
+        &self.registry
+    }
+
+    /// Run the pipeline. Per-chunk failures are recorded in the report; only failures that stop
+    /// the whole run (page fetch, output directory, unreadable input) are returned as errors.
+    pub async fn run(&self, target: &DumpTarget) -> Result<DumpResult, CLIError> {
+        let mut result = DumpResult { report: DumpReport::new(target.label()), ..Default::default() };
+        match target {
+            DumpTarget::Url(page_url) => self.run_remote(page_url, &self.save, &mut result).await?,
+            DumpTarget::Local(input) => self.run_local(input, &mut result).await?,
+            DumpTarget::Har(path) => self.run_har(path, &mut result).await?,
+            DumpTarget::Warc { path, page } => self.run_warc(path, page.as_deref(), &mut result).await?,
+        }
+        Ok(result)
+    }
+
+    /// Run every matching detector in order of confidence; the first one yielding URLs wins.
+    /// Returns `None` when no detector matched the page or none produced any URLs.
+    async fn enumerate(&self, page: &PageContext, report: &mut DumpReport) -> Option<ChunkPlan> {
+        let candidates = self.registry.detect(page);
+        if candidates.is_empty() {
+            log::info!("No supported bundler runtime detected (tried: {})", self.registry.names().join(", "));
+            return None;
+        }
+
+        for (detector, detection) in candidates {
+            log::info!("Detected {} runtime (confidence {:.2})", detector.name(), detection.confidence);
+            match detector.enumerate(page, &detection).await {
+                Ok(plan) if !plan.urls.is_empty() => {
+                    log::info!("Enumerated chunks via {} strategy", plan.strategy);
+                    report.set_runtime(detector.name(), detection.confidence, &plan.strategy);
+                    self.emit(DumpEvent::RuntimeDetected {
+                        detector: detector.name().to_string(),
+                        confidence: detection.confidence,
+                        strategy: plan.strategy.clone(),
+                    });
+                    return Some(plan);
+                }
+                Ok(_) => log::info!("{} detector found no chunk URLs", detector.name()),
+                Err(e) => {
+                    log::warn!("{} detector failed: {}", detector.name(), e);
+                    report.record_error(None, format!("detector.{}", detector.name()), e.to_string());
+                    let message = format!("{} detector failed: {}", detector.name(), e);
+                    self.emit(DumpEvent::Warning { url: None, message });
+                }
+            }
+        }
+        None
+    }
+
+    fn emit(&self, event: DumpEvent) {
+        self.save.observers.emit(event);
+    }
+
+    fn discovered<'a>(&self, urls: impl IntoIterator<Item = &'a Url>) {
+        for url in urls {
+            self.emit(DumpEvent::ChunkDiscovered { url: url.to_string() });
+        }
+    }
+
+    /// Drop the `file:` URLs among `urls` unless they come from a `file:` page (`from`; `None` for
+    /// captures), so a remote page cannot make the dump copy local files.
+    fn drop_local_urls(&self, urls: &mut Vec<Url>, from: Option<&Url>) {
+        urls.retain(|u| {
+            let keep = from.map_or(u.scheme() != "file", |f| may_reference(f, u));
+            if !keep {
+                log::warn!("Ignoring local URL {} referenced by a non-local page", u);
+                self.emit(DumpEvent::Warning { url: Some(u.to_string()), message: "ignored local URL referenced by a non-local page".into() });
+            }
+            keep
+        });
+    }
+
+    fn truncate<T>(&self, items: &mut Vec<T>) {
+        if let Some(max_n) = self.options.max_files {
+            if items.len() > max_n {
+                items.truncate(max_n);
+                log::info!("Truncated to {} files due to max_files", max_n);
+            }
+        }
+    }
+
+    fn finish(&self, out_root: &Path, result: &mut DumpResult) {
+        if self.save.ledger.records().is_empty() {
+            return;
+        }
+        match self.save.ledger.write_json(out_root) {
+            Ok(path) => result.ledger_path = Some(path),
+            Err(e) => log::warn!("failed to write source ledger: {}", e),
+        }
+    }
+
+    fn record_save(&self, result: &mut DumpResult, url: &str, status: Option<u16>, body: &str, saved: Result<SaveOutcome, CLIError>) {
+        match saved {
+            Ok(outcome) => {
+                log::debug!("saved {}{}", url, describe_outcome(&outcome));
+                result.stats.add(&outcome);
+                result.report.record_saved(url, status, body, &outcome);
+            }
+            Err(e) => {
+                log::debug!("error processing {}: {}", url, e);
+                self.emit(DumpEvent::Warning { url: Some(url.to_string()), message: format!("could not save chunk: {}", e) });
+                result.report.record_processing_failure(url, status, &e);
+            }
+        }
+    }
+
+    fn record_download_failure(&self, result: &mut DumpResult, url: &str, err: FetchError) {
+        log::debug!("failed to download {}: {}", url, err);
+        result.report.record_download_failure(url, &err);
+    }
+
+    /// Download `urls` through `save.fetcher` (concurrently unless `concurrency` is 1) and save
+    /// each chunk as soon as it arrives, recording it in `journal` if given.
+    async fn download_and_save(
+        &self,
+        urls: &[Url],
+        save: &SaveOptions,
+        out_root: &Path,
+        journal: Option<&RunJournal>,
+        result: &mut DumpResult,
+    ) {
+        let journaled = |url: &str, saved: bool, body: Option<&str>| {
+            if let Some(j) = journal {
+                // Keep the ledger on disk at least as current as the journal, for a resumed run.
+                if saved && !save.ledger.records().is_empty() {
+                    if let Err(e) = save.ledger.write_json(out_root) {
+                        log::warn!("failed to write source ledger: {}", e);
+                    }
+                }
+                j.record_chunk(url, if saved { JournalState::Saved } else { JournalState::Failed }, body);
+            }
+        };
+        if self.options.concurrency <= 1 {
+            let fetcher: Arc<dyn Fetcher> = match &self.options.throttle {
+                Some(throttle) => Arc::new(ThrottledFetcher::new(save.fetcher.clone(), throttle.clone())),
+                None => save.fetcher.clone(),
+            };
+            for (idx, u) in urls.iter().enumerate() {
+                log::debug!("[{}/{}] downloading {}", idx + 1, urls.len(), u);
+                self.emit(DumpEvent::DownloadStarted { url: u.to_string() });
+                let fetched = fetcher.fetch_with_policy(u, &self.options.retry).await;
+                self.emit(DumpEvent::DownloadFinished {
+                    url: u.to_string(),
+                    status: fetched.as_ref().ok().map(|r| r.status),
+                    bytes: fetched.as_ref().map_or(0, |r| r.body.len()),
+                    error: fetched.as_ref().err().map(ToString::to_string),
+                });
+                match fetched {
+                    Ok(resp) => {
+                        let saved = save_fetched_asset(u.as_str(), &resp, out_root, save).await;
+                        journaled(u.as_str(), saved.is_ok(), Some(&resp.body));
+                        self.record_save(result, u.as_str(), Some(resp.status), &resp.body, saved);
+                    }
+                    Err(e) => {
+                        journaled(u.as_str(), false, None);
+                        self.record_download_failure(result, u.as_str(), e);
+                    }
+                }
+            }
+            return;
+        }
+
+        let url_strings: Vec<String> = urls.iter().map(|u| u.to_string()).collect();
+        let cfg = DownloadManagerConfig {
+            concurrency: self.options.concurrency,
+            retry: self.options.retry.clone(),
+            observers: save.observers.clone(),
+            fetcher: save.fetcher.clone(),
+            throttle: self.options.throttle.clone(),
+        };
+        let mut results = download_stream(url_strings, cfg);
+        while let Some(res) = results.recv().await {
+            match res.response {
+                Ok(resp) => {
+                    let saved = save_fetched_asset(&res.url, &resp, out_root, save).await;
+                    journaled(&res.url, saved.is_ok(), Some(&resp.body));
+                    self.record_save(result, &res.url, Some(resp.status), &resp.body, saved);
+                }
+                Err(e) => {
+                    journaled(&res.url, false, None);
+                    self.record_download_failure(result, &res.url, e);
+                }
+            }
+        }
+    }
+
+    /// The remote pipeline, loading everything through `save.fetcher`.
+    async fn run_remote(&self, page_url: &str, save: &SaveOptions, result: &mut DumpResult) -> Result<(), CLIError> {
+        let base = Url::parse(page_url)?;
+        let out_root = self.options.out_dir.clone().unwrap_or_else(|| {
+            // "out/<host>" when no output directory was given
+            PathBuf::from("out").join(base.host_str().unwrap_or("site"))
+        });
+
+        let mut resumed = None;
+        if self.options.resume && !self.options.dry_run {
+            match RunJournal::open(&out_root)? {
+                Some(j) if j.plan().is_some_and(|(target, _)| target == base.as_str()) => resumed = Some(j),
+                Some(_) => log::warn!("Journal in {:?} is for another page; starting over", out_root),
+                None => log::info!("No journal in {:?}; starting over", out_root),
+            }
+        }
+        let (journal, urls) = match resumed {
+            Some(j) => {
+                let urls = j.plan().map(|(_, urls)| urls.to_vec()).unwrap_or_default();
+                log::info!("Resuming the run journaled in {:?} ({} planned chunks)", out_root, urls.len());
+                match save.ledger.load_json(&out_root) {
+                    Ok(0) => {}
+                    Ok(n) => log::info!("Restored {} source ledger records", n),
+                    Err(e) => log::warn!("could not restore the source ledger: {}", e),
+                }
+                (j, urls)
+            }
+            None => {
+                let Some(urls) = self.plan_remote(&base, save, result).await? else { return Ok(()) };
+                ensure_output_dir(&out_root)?;
+                let mut j = RunJournal::create(&out_root)?;
+                j.record_plan(base.as_str(), &urls)?;
+                (j, urls)
+            }
+        };
+        result.report.out_dir = Some(out_root.display().to_string());
+
+        let (done, todo): (Vec<Url>, Vec<Url>) = urls.iter().cloned().partition(|u| journal.is_done(u.as_str()));
+        if !done.is_empty() {
+            log::info!("Skipping {} chunks already saved by the previous run", done.len());
+        }
+        result.planned = urls;
+        result.skipped = done;
+        self.discovered(&todo);
+
+        // The journal learns which files each chunk wrote from the pipeline's events.
+        let journal = Arc::new(journal);
+        let mut save = save.clone();
+        save.observers.add(journal.clone());
+
+        log::info!("Downloading {} chunk files to {:?} ...", todo.len(), out_root);
+        self.download_and_save(&todo, &save, &out_root, Some(&journal), result).await;
+
+        self.finish(&out_root, result);
+        result.out_dir = Some(out_root);
+        Ok(())
+    }
+
+    /// Fetch the page, then enumerate, validate and truncate its chunk URLs. `None` when there is
+    /// nothing to download, including dry runs (whose URLs go to `result.planned`).
+    async fn plan_remote(&self, base: &Url, save: &SaveOptions, result: &mut DumpResult) -> Result<Option<Vec<Url>>, CLIError> {
+        log::info!("Fetching HTML from {}", base);
+        let html = save.fetcher.fetch_with_policy(base, &self.options.retry).await?.body;
+
+        let page = PageContext::from_html(base.clone(), html).with_fetcher(save.fetcher.clone());
+        let Some(mut plan) = self.enumerate(&page, &mut result.report).await else { return Ok(None) };
+        self.drop_local_urls(&mut plan.urls, Some(base));
+        log::info!("Generated {} chunk URLs", plan.urls.len());
+
+        if self.options.dry_run {
+            result.planned = plan.urls;
+            return Ok(None);
+        }
+
+        let mut live_urls = if self.options.validate_urls {
+            log::info!("Validating chunk URLs...");
+            let live = validate_chunk_urls_with_policy(save.fetcher.as_ref(), plan.urls, &self.options.retry).await;
+            log::info!("{} URLs responded with 2xx", live.len());
+            live
+        } else {
+            plan.urls
+        };
+        self.truncate(&mut live_urls);
+
+        if live_urls.is_empty() {
+            log::info!("No downloadable chunk URLs found");
+            return Ok(None);
+        }
+        Ok(Some(live_urls))
+    }
+
+    async fn run_har(&self, path: &Path, result: &mut DumpResult) -> Result<(), CLIError> {
+        log::info!("Loading HAR capture {}", path.display());
+        let archive = HarArchive::from_path(path)?;
+        let page_url = archive.page_url();
+        let mut urls = archive.assets();
+        log::info!("Capture contains {} scripts/stylesheets", urls.len());
+
+        // Responses come from the capture; the network is only used for what it lacks, if allowed.
+        let fallback = self.options.network_fallback.then(|| self.save.fetcher.clone());
+        let offline = fallback.is_none();
+        let fetcher: Arc<dyn Fetcher> = Arc::new(archive.into_fetcher(fallback));
+        let save = SaveOptions { fetcher: fetcher.clone(), ..self.save.clone() };
+
+        // The detected runtime may reference chunks the page never requested while captured.
+        if let Some(page_url) = &page_url {
+            if let Ok(html) = fetcher.fetch_text(page_url, 1).await {
+                let page = PageContext::from_html(page_url.clone(), html).with_fetcher(fetcher.clone());
+                if let Some(plan) = self.enumerate(&page, &mut result.report).await {
+                    for u in plan.urls {
+                        if !urls.contains(&u) && (!offline || fetcher.exists(&u).await) {
+                            urls.push(u);
+                        }
+                    }
+                }
+            }
+        }
+        self.drop_local_urls(&mut urls, None);
+        self.truncate(&mut urls);
+
+        if self.options.dry_run {
+            result.planned = urls;
+            return Ok(());
+        }
+        if urls.is_empty() {
+            log::info!("No scripts or stylesheets in the capture");
+            return Ok(());
+        }
+
+        let out_root = self.options.out_dir.clone().unwrap_or_else(|| {
+            // "out/<host>" of the captured page when no output directory was given
+            let host = page_url.as_ref().or(urls.first()).and_then(|u| u.host_str().map(str::to_owned));
+            PathBuf::from("out").join(host.as_deref().unwrap_or("har"))
+        });
+        ensure_output_dir(&out_root)?;
+        result.report.out_dir = Some(out_root.display().to_string());
+        result.planned = urls.clone();
+        self.discovered(&urls);
+
+        log::info!("Reconstructing {} captured files to {:?} ...", urls.len(), out_root);
+        self.download_and_save(&urls, &save, &out_root, None, result).await;
+
+        self.finish(&out_root, result);
+        result.out_dir = Some(out_root);
+        Ok(())
+    }
+
+    async fn run_warc(&self, path: &Path, page: Option<&str>, result: &mut DumpResult) -> Result<(), CLIError> {
+        log::info!("Indexing WARC archive {}", path.display());
+        let archive = WarcArchive::from_path(path)?;
+        log::info!("Archive contains {} HTTP responses", archive.responses().len());
+        let page_url = match page {
+            Some(p) => p.to_string(),
+            None => archive
+                .page_url()
+                .ok_or_else(|| CLIError::Other(format!("no HTML page archived in {}", path.display())))?
+                .to_string(),
+        };
+        let save = SaveOptions { fetcher: Arc::new(archive.into_fetcher()), ..self.save.clone() };
+        self.run_remote(&page_url, &save, result).await
+    }
+
+    async fn run_local(&self, input: &Path, result: &mut DumpResult) -> Result<(), CLIError> {
+        log::info!("Scanning local bundle {}", input.display());
+        let bundle = collect_local_bundle(input).await?;
+        log::info!("Found {} script/stylesheet files under {}", bundle.files.len(), bundle.root.display());
+
+        // Detection only adds files the runtime references that a plain directory walk would miss
+        // (e.g. chunks next to a single input file); everything on disk is processed regardless.
+        let page = PageContext::for_local_bundle(&bundle).with_fetcher(self.save.fetcher.clone());
+        let mut files = bundle.files.clone();
+        if let Some(plan) = self.enumerate(&page, &mut result.report).await {
+            for u in plan.urls.iter().filter(|u| u.scheme() == "file") {
+                if let Ok(p) = u.to_file_path() {
+                    if p.starts_with(&bundle.root) && p.is_file() && !files.contains(&p) {
+                        files.push(p);
+                    }
+                }
+            }
+        }
+        self.truncate(&mut files);
+        result.planned = files.iter().filter_map(|p| Url::from_file_path(p).ok()).collect();
+
+        if self.options.dry_run {
+            return Ok(());
+        }
+        self.discovered(&result.planned);
+
+        let out_root = self.options.out_dir.clone().unwrap_or_else(|| {
+            // "out/<bundle dir name>" when no output directory was given
+            let name = bundle.root.file_name().and_then(|n| n.to_str()).unwrap_or("local");
+            PathBuf::from("out").join(name)
+        });
+        ensure_output_dir(&out_root)?;
+        result.report.out_dir = Some(out_root.display().to_string());
+
+        log::info!("Reconstructing {} files to {:?} ...", files.len(), out_root);
+        for path in &files {
+            let rel = path.strip_prefix(&bundle.root).unwrap_or(path);
+            let Ok(file_url) = Url::from_file_path(path) else {
+                let e = CLIError::Other(format!("cannot build file URL for {}", path.display()));
+                self.record_save(result, &path.display().to_string(), None, "", Err(e));
+                continue;
+            };
+            let label = file_url.to_string();
+
+            // Reading a file stands in for the download of remote dumps. Scripts are read with
+            // `read_js_file`, which validates them like a single `--input` file.
+            self.emit(DumpEvent::DownloadStarted { url: label.clone() });
+            let loaded: Result<String, CLIError> = if path.extension().is_some_and(|e| e == "js") {
+                read_js_file(path).await.map_err(|e| AnalysisError::from(e).into())
+            } else {
+                tokio::fs::read_to_string(path).await.map_err(Into::into)
+            };
+            self.emit(DumpEvent::DownloadFinished {
+                url: label.clone(),
+                status: None,
+                bytes: loaded.as_ref().map_or(0, String::len),
+                error: loaded.as_ref().err().map(ToString::to_string),
+            });
+
+            match loaded {
+                Ok(body) => {
+                    let maps = find_sourcemap_urls(&file_url, &body);
+                    let saved = save_asset_with_options(&body, rel, Some(&file_url), maps, &out_root, &self.save).await;
+                    self.record_save(result, &label, None, &body, saved);
+                }
+                Err(e) => self.record_save(result, &label, None, "", Err(e)),
+            }
+        }
+
+        self.finish(&out_root, result);
+        result.out_dir = Some(out_root);
+        Ok(())
+    }
+}
+
+/// Suffix for a "saved" line noting how the asset's sources were recovered.
+fn describe_outcome(outcome: &SaveOutcome) -> String {
+    if let Some(u) = outcome.probed_maps.first() {
+        format!(" (probed map {})", u)
+    } else if !outcome.declared_maps.is_empty() {
+        format!(" ({} declared map(s))", outcome.declared_maps.len())
+    } else if outcome.split_modules > 0 {
+        format!(" (no map; split {} modules)", outcome.split_modules)
+    } else {
+        String::new()
+    }
+}
@@ -0,0 +1,87 @@
+//! Progress events emitted while a dump runs.
+//!
+//! Register a [`DumpObserver`] with [`crate::Dumper::with_observer`] (or in
+//! [`crate::SaveOptions::observers`] when saving assets directly) to render progress or forward
+//! events into your own telemetry. Closures `Fn(&DumpEvent)` are observers too, and
+//! [`ChannelObserver`] turns the events into a stream.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+/// Something that happened during a dump.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DumpEvent {
+    /// A detector produced the chunk plan.
+    RuntimeDetected { detector: String, confidence: f32, strategy: String },
+    /// A chunk (or local file) was selected for processing. Chunk URLs are the same string in
+    /// every event about the chunk; local files are named by their `file://` URL.
+    ChunkDiscovered { url: String },
+    DownloadStarted { url: String },
+    /// A download completed; `error` is set when it failed.
+    DownloadFinished { url: String, status: Option<u16>, bytes: usize, error: Option<String> },
+    /// A sourcemap of `chunk` loaded and parsed; `map` as [`crate::url_label`] names it.
+    SourcemapParsed { chunk: String, map: String, sources: usize },
+    /// A file was written, relative to the output directory.
+    FileWritten { chunk: String, path: PathBuf },
+    /// A non-fatal problem (missing map, failed detector, unsaved chunk, ...).
+    Warning { url: Option<String>, message: String },
+}
+
+/// Receives [`DumpEvent`]s. Called synchronously from the pipeline, possibly from several tasks,
+/// so implementations should be quick.
+pub trait DumpObserver: Send + Sync {
+    fn on_event(&self, event: &DumpEvent);
+}
+
+impl<F: Fn(&DumpEvent) + Send + Sync> DumpObserver for F {
+    fn on_event(&self, event: &DumpEvent) {
+        self(event)
+    }
+}
+
+/// Forwards every event into an unbounded channel.
+pub struct ChannelObserver(mpsc::UnboundedSender<DumpEvent>);
+
+impl ChannelObserver {
+    /// An observer and the receiving end of its event stream.
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<DumpEvent>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Self(tx), rx)
+    }
+}
+
+impl DumpObserver for ChannelObserver {
+    fn on_event(&self, event: &DumpEvent) {
+        let _ = self.0.send(event.clone());
+    }
+}
+
+/// The set of observers events are fanned out to.
+#[derive(Clone, Default)]
+pub struct Observers(Vec<Arc<dyn DumpObserver>>);
+
+impl Observers {
+    pub fn add(&mut self, observer: Arc<dyn DumpObserver>) -> &mut Self {
+        self.0.push(observer);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn emit(&self, event: DumpEvent) {
+        for o in &self.0 {
+            o.on_event(&event);
+        }
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
@@ -1,7 +1,13 @@
-use reqwest::{Client, Url};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{HeaderMap, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER};
+use reqwest::Url;
 use thiserror::Error;
 
+use crate::fetcher::{default_fetcher, Fetcher, HttpFetcher};
+use crate::http::HttpClient;
+use crate::retry::parse_retry_after;
+
 #[derive(Error, Debug)]
 pub enum FetchError {
     #[error("invalid url: {0}")]
@@ -12,8 +18,120 @@ pub enum FetchError {
     Network(#[from] reqwest::Error),
     #[error("http status {0}")]
     HttpStatus(u16),
+    /// A status sent with `Retry-After`, e.g. `429` or `503`.
+    #[error("http status {status}, retry after {retry_after:?}")]
+    RetryLater { status: u16, retry_after: Duration },
     #[error("request timed out")]
     Timeout,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    DataUrl(#[from] crate::DataUrlError),
+    #[error("invalid http configuration: {0}")]
+    Config(String),
+    #[error("no recorded response for {0}")]
+    NotRecorded(String),
+}
+
+impl FetchError {
+    /// Stable machine-readable name of the error variant, e.g. `"http_status"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FetchError::UrlParse(_) => "url_parse",
+            FetchError::UnsupportedScheme(_) => "unsupported_scheme",
+            FetchError::Network(_) => "network",
+            FetchError::HttpStatus(_) => "http_status",
+            FetchError::RetryLater { .. } => "retry_later",
+            FetchError::Timeout => "timeout",
+            FetchError::Io(_) => "io",
+            FetchError::DataUrl(_) => "data_url",
+            FetchError::Config(_) => "config",
+            FetchError::NotRecorded(_) => "not_recorded",
+        }
+    }
+
+    /// The error for a response with non-2xx `status`, keeping the wait its `Retry-After` header
+    /// asks for.
+    pub fn from_status(status: u16, headers: &HeaderMap) -> Self {
+        let retry_after = headers.get(RETRY_AFTER).and_then(|v| v.to_str().ok()).and_then(|v| parse_retry_after(v, SystemTime::now()));
+        match retry_after {
+            Some(retry_after) => FetchError::RetryLater { status, retry_after },
+            None => FetchError::HttpStatus(status),
+        }
+    }
+
+    /// The HTTP status the server answered with, if it answered.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            FetchError::HttpStatus(s) | FetchError::RetryLater { status: s, .. } => Some(*s),
+            _ => None,
+        }
+    }
+
+    /// How long the server asked to wait before trying again.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            FetchError::RetryLater { retry_after, .. } => Some(*retry_after),
+            _ => None,
+        }
+    }
+}
+
+/// A successful response: final URL after redirects, status, headers and decoded body.
+#[derive(Debug, Clone)]
+pub struct FetchResponse {
+    pub url: Url,
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl FetchResponse {
+    /// Value of header `name` if present and valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    /// A `304 Not Modified` answer to a conditional request.
+    pub fn is_not_modified(&self) -> bool {
+        self.status == 304
+    }
+
+    pub fn validators(&self) -> Validators {
+        Validators::from_headers(&self.headers)
+    }
+}
+
+/// Cache validators of a response, sent back as `If-None-Match` / `If-Modified-Since` to ask
+/// whether it changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    /// The `Last-Modified` date, verbatim.
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name| headers.get(name).and_then(|v: &HeaderValue| v.to_str().ok()).map(str::to_string);
+        Self { etag: get(ETAG), last_modified: get(LAST_MODIFIED) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// The conditional request headers.
+    pub fn to_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let values = [(IF_NONE_MATCH, &self.etag), (IF_MODIFIED_SINCE, &self.last_modified)];
+        for (name, value) in values {
+            if let Some(v) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(name, v);
+            }
+        }
+        headers
+    }
 }
 
 /// Validate and parse a URL string, returning Url or FetchError
@@ -25,47 +143,29 @@ pub fn validate_url(input: &str) -> Result<Url, FetchError> {
     }
 }
 
-/// Fetch with limited retry attempts on network/timeouts using exponential backoff
+/// Fetch with up to `attempts` tries under the default [`crate::RetryPolicy`].
 pub async fn fetch_with_retries(url: &str, attempts: usize) -> Result<String, FetchError> {
-    let mut delay = Duration::from_millis(200);
-    let url_owned = url.to_string();
-    let mut last_err: Option<FetchError> = None;
-    for _ in 0..attempts {
-        match fetch_html(&url_owned).await {
-            Ok(body) => return Ok(body),
-            Err(e @ FetchError::Timeout) | Err(e @ FetchError::Network(_)) => {
-                last_err = Some(e);
-                tokio::time::sleep(delay).await;
-                delay *= 2;
-                continue;
-            }
-            Err(e) => return Err(e),
-        }
-    }
-    Err(last_err.unwrap_or(FetchError::Timeout))
+    Ok(fetch_response_with_retries(url, attempts).await?.body)
 }
 
-/// Fetch HTML content from the given URL and return it as a UTF-8 string.
-/// A desktop-like User-Agent header is added and request times out after 30 seconds.
-pub async fn fetch_html(url: &str) -> Result<String, FetchError> {
+/// Like [`fetch_with_retries`], but keeps the response headers.
+pub async fn fetch_response_with_retries(url: &str, attempts: usize) -> Result<FetchResponse, FetchError> {
     let url = validate_url(url)?;
-    let client = Client::builder()
-        .timeout(Duration::from_secs(30))
-        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/125.0 Safari/537.36")
-        .build()?;
-
-    let resp = client.get(url).send().await.map_err(|e| {
-        if e.is_timeout() {
-            FetchError::Timeout
-        } else {
-            FetchError::Network(e)
-        }
-    })?;
+    HttpFetcher::new(HttpClient::shared()).fetch_with_retries(&url, attempts).await
+}
 
-    let status = resp.status();
-    if !status.is_success() {
-        return Err(FetchError::HttpStatus(status.as_u16()));
-    }
+/// Load text from a URL of any scheme the [`default_fetcher`] handles (`http(s)`, `file`, `data`).
+pub async fn fetch_url(url: &Url, attempts: usize) -> Result<String, FetchError> {
+    default_fetcher().fetch_text(url, attempts).await
+}
+
+/// Fetch HTML content from the given URL and return it as a UTF-8 string, using the shared
+/// default [`HttpClient`] (desktop-like User-Agent, 30 second timeout).
+pub async fn fetch_html(url: &str) -> Result<String, FetchError> {
+    Ok(fetch_response(url).await?.body)
+}
 
-    Ok(resp.text().await?)
-} 
\ No newline at end of file
+/// Single GET request returning the body together with status and headers; see [`fetch_html`].
+pub async fn fetch_response(url: &str) -> Result<FetchResponse, FetchError> {
+    HttpClient::shared().fetch_response(url).await
+}
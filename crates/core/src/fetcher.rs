@@ -0,0 +1,294 @@
+//! Loading resources by URL, whatever the scheme.
+//!
+//! Every stage of the pipeline (page fetch, detector lookups, chunk validation, downloads,
+//! sourcemap loads) goes through a [`Fetcher`]; only the files of a local bundle are read directly.
+//! The default [`CompositeFetcher`] dispatches on the URL scheme to [`HttpFetcher`] (`http`,
+//! `https`), [`FileFetcher`] (`file`) and [`DataUrlFetcher`] (`data`); more schemes can be
+//! registered, and [`MemoryFetcher`] serves canned responses in tests.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use url::Url;
+
+use crate::{decode_data_url, FetchError, FetchResponse, HttpClient, RetryPolicy, Validators};
+
+/// Loads the resource behind a URL.
+#[async_trait]
+pub trait Fetcher: Send + Sync {
+    /// One attempt at loading `url`. Responses other than 2xx are errors.
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError>;
+
+    /// Whether `url` can be loaded, or why not. Defaults to a full [`Self::fetch`].
+    async fn check(&self, url: &Url) -> Result<(), FetchError> {
+        self.fetch(url).await.map(drop)
+    }
+
+    /// Whether `url` can be loaded; see [`Self::check`].
+    async fn exists(&self, url: &Url) -> bool {
+        self.check(url).await.is_ok()
+    }
+
+    /// Load `url` unless it still matches `validators`, in which case the response is a `304` with
+    /// an empty body. Defaults to [`Self::fetch`], which never answers `304`.
+    async fn fetch_conditional(&self, url: &Url, _validators: &Validators) -> Result<FetchResponse, FetchError> {
+        self.fetch(url).await
+    }
+
+    /// [`Self::fetch`], tried again as long as `policy` allows.
+    async fn fetch_with_policy(&self, url: &Url, policy: &RetryPolicy) -> Result<FetchResponse, FetchError> {
+        policy.retry(|| self.fetch(url)).await
+    }
+
+    /// [`Self::fetch_with_policy`] with the default [`RetryPolicy`] and up to `attempts` tries.
+    async fn fetch_with_retries(&self, url: &Url, attempts: usize) -> Result<FetchResponse, FetchError> {
+        self.fetch_with_policy(url, &RetryPolicy::with_attempts(attempts)).await
+    }
+
+    /// The body of [`Self::fetch_with_retries`].
+    async fn fetch_text(&self, url: &Url, attempts: usize) -> Result<String, FetchError> {
+        Ok(self.fetch_with_retries(url, attempts).await?.body)
+    }
+}
+
+impl fmt::Debug for dyn Fetcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("dyn Fetcher")
+    }
+}
+
+/// `http` and `https` through an [`HttpClient`].
+#[derive(Debug, Clone, Default)]
+pub struct HttpFetcher {
+    client: HttpClient,
+}
+
+impl HttpFetcher {
+    pub fn new(client: HttpClient) -> Self {
+        Self { client }
+    }
+
+    pub fn client(&self) -> &HttpClient {
+        &self.client
+    }
+}
+
+#[async_trait]
+impl Fetcher for HttpFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        self.client.fetch_response(url.as_str()).await
+    }
+
+    /// HEAD (or GET on 405) instead of downloading the body.
+    async fn check(&self, url: &Url) -> Result<(), FetchError> {
+        self.client.check(url).await
+    }
+
+    async fn fetch_conditional(&self, url: &Url, validators: &Validators) -> Result<FetchResponse, FetchError> {
+        self.client.fetch_conditional(url, validators).await
+    }
+}
+
+/// `file://` URLs, read from disk. Responses have status 200 and no headers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileFetcher;
+
+#[async_trait]
+impl Fetcher for FileFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        let path = url.to_file_path().map_err(|_| FetchError::UnsupportedScheme(url.to_string()))?;
+        let body = tokio::fs::read_to_string(path).await?;
+        Ok(FetchResponse { url: url.clone(), status: 200, headers: HeaderMap::new(), body })
+    }
+
+    async fn check(&self, url: &Url) -> Result<(), FetchError> {
+        let path = url.to_file_path().map_err(|_| FetchError::UnsupportedScheme(url.to_string()))?;
+        if !path.is_file() {
+            return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into());
+        }
+        Ok(())
+    }
+}
+
+/// `data:` URLs, decoded in place. The media type is reported as `Content-Type`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DataUrlFetcher;
+
+#[async_trait]
+impl Fetcher for DataUrlFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        let data = decode_data_url(url.as_str())?;
+        let mut headers = HeaderMap::new();
+        if let Ok(v) = HeaderValue::from_str(&data.mime_type) {
+            headers.insert(CONTENT_TYPE, v);
+        }
+        Ok(FetchResponse { url: url.clone(), status: 200, headers, body: data.text()? })
+    }
+}
+
+/// Dispatches each URL to the fetcher registered for its scheme; unknown schemes fail with
+/// [`FetchError::UnsupportedScheme`].
+#[derive(Default)]
+pub struct CompositeFetcher {
+    schemes: HashMap<String, Arc<dyn Fetcher>>,
+}
+
+impl CompositeFetcher {
+    /// A dispatcher with no schemes.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// `http`/`https` through `client`, plus `file` and `data`.
+    pub fn with_builtin(client: HttpClient) -> Self {
+        let mut c = Self::with_remote(client);
+        c.register("file", Arc::new(FileFetcher));
+        c
+    }
+
+    /// [`Self::with_builtin`] without `file`, for dumps of remote pages, captures and archives,
+    /// whose content must not be able to read the local disk.
+    pub fn with_remote(client: HttpClient) -> Self {
+        let http: Arc<dyn Fetcher> = Arc::new(HttpFetcher::new(client));
+        let mut c = Self::empty();
+        c.register("http", http.clone()).register("https", http).register("data", Arc::new(DataUrlFetcher));
+        c
+    }
+
+    /// Handle `scheme` with `fetcher`, replacing any fetcher registered for it before.
+    pub fn register(&mut self, scheme: &str, fetcher: Arc<dyn Fetcher>) -> &mut Self {
+        self.schemes.insert(scheme.to_ascii_lowercase(), fetcher);
+        self
+    }
+
+    pub fn schemes(&self) -> Vec<&str> {
+        let mut s: Vec<&str> = self.schemes.keys().map(String::as_str).collect();
+        s.sort_unstable();
+        s
+    }
+
+    fn route(&self, url: &Url) -> Result<&Arc<dyn Fetcher>, FetchError> {
+        self.schemes.get(url.scheme()).ok_or_else(|| FetchError::UnsupportedScheme(url.scheme().to_string()))
+    }
+}
+
+#[async_trait]
+impl Fetcher for CompositeFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        self.route(url)?.fetch(url).await
+    }
+
+    async fn check(&self, url: &Url) -> Result<(), FetchError> {
+        self.route(url)?.check(url).await
+    }
+
+    async fn fetch_conditional(&self, url: &Url, validators: &Validators) -> Result<FetchResponse, FetchError> {
+        self.route(url)?.fetch_conditional(url, validators).await
+    }
+}
+
+impl fmt::Debug for CompositeFetcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompositeFetcher").field("schemes", &self.schemes()).finish()
+    }
+}
+
+/// Serves responses registered up front; every other URL is a 404.
+#[derive(Debug, Default)]
+pub struct MemoryFetcher {
+    responses: RwLock<HashMap<String, FetchResponse>>,
+}
+
+impl MemoryFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `body` with status 200 at `url`.
+    pub fn insert(&self, url: &Url, body: impl Into<String>) -> &Self {
+        self.insert_response(FetchResponse { url: url.clone(), status: 200, headers: HeaderMap::new(), body: body.into() })
+    }
+
+    /// Serve `response` at `response.url`.
+    pub fn insert_response(&self, response: FetchResponse) -> &Self {
+        let mut map = self.responses.write().unwrap_or_else(|e| e.into_inner());
+        map.insert(response.url.as_str().to_owned(), response);
+        self
+    }
+}
+
+#[async_trait]
+impl Fetcher for MemoryFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        let map = self.responses.read().unwrap_or_else(|e| e.into_inner());
+        map.get(url.as_str()).cloned().ok_or(FetchError::HttpStatus(404))
+    }
+}
+
+/// Serves responses captured in an archive (HAR, WARC). URLs it has no response for are fetched
+/// through the fallback if there is one, and fail with [`FetchError::NotRecorded`] otherwise.
+pub struct ArchiveFetcher {
+    /// The served response, or the error status the archive recorded.
+    responses: HashMap<String, Result<FetchResponse, u16>>,
+    fallback: Option<Arc<dyn Fetcher>>,
+}
+
+impl ArchiveFetcher {
+    pub fn new(fallback: Option<Arc<dyn Fetcher>>) -> Self {
+        Self { responses: HashMap::new(), fallback }
+    }
+
+    /// Serve `response` at `response.url`, replacing anything recorded for it before.
+    pub fn insert(&mut self, response: FetchResponse) -> &mut Self {
+        self.responses.insert(response.url.as_str().to_owned(), Ok(response));
+        self
+    }
+
+    /// Answer `url` with [`FetchError::HttpStatus`]`(status)`.
+    pub fn insert_error(&mut self, url: &Url, status: u16) -> &mut Self {
+        self.responses.insert(url.as_str().to_owned(), Err(status));
+        self
+    }
+
+    /// Number of URLs with a recorded response or error.
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+}
+
+#[async_trait]
+impl Fetcher for ArchiveFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        match (self.responses.get(url.as_str()), &self.fallback) {
+            (Some(Ok(resp)), _) => Ok(resp.clone()),
+            (Some(Err(status)), _) => Err(FetchError::HttpStatus(*status)),
+            (None, Some(fallback)) => fallback.fetch(url).await,
+            (None, None) => Err(FetchError::NotRecorded(url.to_string())),
+        }
+    }
+
+    async fn check(&self, url: &Url) -> Result<(), FetchError> {
+        match (self.responses.get(url.as_str()), &self.fallback) {
+            (Some(Ok(_)), _) => Ok(()),
+            (Some(Err(status)), _) => Err(FetchError::HttpStatus(*status)),
+            (None, Some(fallback)) => fallback.check(url).await,
+            (None, None) => Err(FetchError::NotRecorded(url.to_string())),
+        }
+    }
+}
+
+static DEFAULT: Lazy<Arc<dyn Fetcher>> = Lazy::new(|| Arc::new(CompositeFetcher::with_builtin(HttpClient::shared())));
+
+/// The process-wide [`CompositeFetcher`] over [`HttpClient::shared`].
+pub fn default_fetcher() -> Arc<dyn Fetcher> {
+    DEFAULT.clone()
+}
+
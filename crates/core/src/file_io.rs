@@ -21,7 +21,7 @@ pub async fn validate_js_path<P: AsRef<Path>>(path: P) -> Result<PathBuf, FileAn
     let path_ref = path.as_ref();
 
     // Check extension
-    if path_ref.extension().and_then(|e| e.to_str()).map_or(true, |ext| ext != "js") {
+    if path_ref.extension().and_then(|e| e.to_str()) != Some("js") {
         return Err(FileAnalysisError::InvalidExtension(path_ref.to_path_buf()));
     }
 
@@ -49,7 +49,7 @@ pub async fn validate_js_path<P: AsRef<Path>>(path: P) -> Result<PathBuf, FileAn
 
     let canonical = tokio::task::spawn_blocking(move || std::fs::canonicalize(path_buf))
         .await
-        .map_err(|e| FileAnalysisError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))??;
+        .map_err(|e| FileAnalysisError::Io(std::io::Error::other(e)))??;
 
     Ok(canonical)
 }
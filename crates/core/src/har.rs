@@ -0,0 +1,183 @@
+//! HAR captures as a dump source.
+//!
+//! Browser devtools export every request a page made, including the chunks an SPA loaded
+//! dynamically, as a HAR file. A [`HarArchive`] keeps the responses of such a capture (decoding
+//! base64 bodies) and serves them through an [`ArchiveFetcher`], which can fall back to the
+//! network for anything the capture lacks.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use base64::Engine;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+use url::Url;
+
+use crate::{ArchiveFetcher, FetchResponse, Fetcher};
+
+#[derive(thiserror::Error, Debug)]
+pub enum HarError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("invalid HAR: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// One captured request/response.
+#[derive(Debug, Clone)]
+pub struct HarEntry {
+    pub url: Url,
+    pub status: u16,
+    pub mime_type: String,
+    pub headers: HeaderMap,
+    /// Decoded response body; `None` when the capture did not include it.
+    pub body: Option<String>,
+}
+
+impl HarEntry {
+    /// Whether the response is a script or stylesheet, by media type or extension.
+    pub fn is_asset(&self) -> bool {
+        let mime = self.mime_type.to_ascii_lowercase();
+        let path = self.url.path();
+        mime.contains("javascript")
+            || mime.contains("ecmascript")
+            || mime.contains("text/css")
+            || [".js", ".mjs", ".cjs", ".css"].iter().any(|ext| path.ends_with(ext))
+    }
+
+    fn is_html(&self) -> bool {
+        self.mime_type.to_ascii_lowercase().contains("text/html")
+    }
+
+    /// 2xx, or a 304 the browser answered from its cache.
+    fn is_ok(&self) -> bool {
+        (200..300).contains(&self.status) || self.status == 304
+    }
+}
+
+#[derive(Deserialize)]
+struct RawHar {
+    log: RawLog,
+}
+
+#[derive(Deserialize)]
+struct RawLog {
+    #[serde(default)]
+    entries: Vec<RawEntry>,
+}
+
+#[derive(Deserialize)]
+struct RawEntry {
+    request: RawRequest,
+    response: RawResponse,
+}
+
+#[derive(Deserialize)]
+struct RawRequest {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct RawResponse {
+    status: i64,
+    #[serde(default)]
+    headers: Vec<RawHeader>,
+    #[serde(default)]
+    content: RawContent,
+}
+
+#[derive(Deserialize)]
+struct RawHeader {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct RawContent {
+    #[serde(default)]
+    mime_type: String,
+    text: Option<String>,
+    encoding: Option<String>,
+}
+
+/// The entries of a HAR capture, in request order.
+#[derive(Debug, Clone, Default)]
+pub struct HarArchive {
+    entries: Vec<HarEntry>,
+}
+
+impl HarArchive {
+    pub fn from_path(path: &Path) -> Result<Self, HarError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse HAR JSON. Entries with unparseable URLs are skipped; bodies that fail to decode are
+    /// treated as missing.
+    pub fn parse(json: &str) -> Result<Self, HarError> {
+        let raw: RawHar = serde_json::from_str(json)?;
+        let entries = raw.log.entries.into_iter().filter_map(convert).collect();
+        Ok(Self { entries })
+    }
+
+    pub fn entries(&self) -> &[HarEntry] {
+        &self.entries
+    }
+
+    /// The first HTML document captured, usually the page itself.
+    pub fn page_url(&self) -> Option<Url> {
+        self.entries.iter().find(|e| e.is_html() && e.is_ok()).map(|e| e.url.clone())
+    }
+
+    /// Distinct URLs of the scripts and stylesheets the page loaded successfully.
+    pub fn assets(&self) -> Vec<Url> {
+        let mut urls: Vec<Url> = Vec::new();
+        for e in self.entries.iter().filter(|e| e.is_asset() && e.is_ok()) {
+            if !urls.contains(&e.url) {
+                urls.push(e.url.clone());
+            }
+        }
+        urls
+    }
+
+    /// Serve the capture, fetching responses it lacks through `fallback` if one is given.
+    pub fn into_fetcher(self, fallback: Option<Arc<dyn Fetcher>>) -> ArchiveFetcher {
+        let mut fetcher = ArchiveFetcher::new(fallback);
+        for e in self.entries {
+            let ok = e.is_ok();
+            match e.body {
+                Some(body) if ok => {
+                    let status = if e.status == 304 { 200 } else { e.status };
+                    fetcher.insert(FetchResponse { url: e.url, status, headers: e.headers, body });
+                }
+                _ if !ok && e.status != 0 => {
+                    fetcher.insert_error(&e.url, e.status);
+                }
+                // No body captured (or a blocked request): keep any earlier response.
+                _ => {}
+            }
+        }
+        fetcher
+    }
+}
+
+fn convert(raw: RawEntry) -> Option<HarEntry> {
+    let url = Url::parse(&raw.request.url).ok()?;
+    let mut headers = HeaderMap::new();
+    for h in &raw.response.headers {
+        // HTTP/2 pseudo-headers (`:status`) and malformed values are dropped.
+        if let (Ok(k), Ok(v)) = (HeaderName::from_bytes(h.name.as_bytes()), HeaderValue::from_str(&h.value)) {
+            headers.append(k, v);
+        }
+    }
+    let content = raw.response.content;
+    let body = match (content.text, content.encoding.as_deref()) {
+        (Some(text), Some("base64")) => base64::engine::general_purpose::STANDARD
+            .decode(text.trim())
+            .ok()
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned()),
+        (text, _) => text,
+    };
+    let status = u16::try_from(raw.response.status).unwrap_or(0);
+    Some(HarEntry { url, status, mime_type: content.mime_type, headers, body })
+}
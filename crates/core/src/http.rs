@@ -0,0 +1,209 @@
+//! The HTTP client shared by every stage of a run.
+//!
+//! An [`HttpClient`] wraps one pooled `reqwest::Client` built from an [`HttpConfig`] (user agent,
+//! timeouts, redirect policy, extra headers, cookies, proxy, CA bundle). Wrapped in an
+//! [`crate::HttpFetcher`], it serves every http(s) request of a run, so connections are reused and
+//! every request carries the same identity. Cloning is cheap.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use reqwest::cookie::Jar;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{redirect, Certificate, Client, Proxy, StatusCode, Url};
+
+use crate::fetch::{validate_url, FetchError, FetchResponse, Validators};
+
+/// User agent sent unless [`HttpConfig::user_agent`] says otherwise.
+pub const DEFAULT_USER_AGENT: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/125.0 Safari/537.36";
+
+/// How the shared client talks to servers.
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub user_agent: String,
+    /// Total time allowed for one request, including reading the body.
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Redirects followed per request; `0` disables following redirects.
+    pub max_redirects: usize,
+    /// Extra headers sent with every request.
+    pub headers: Vec<(String, String)>,
+    /// `Set-Cookie`-style cookie strings, each scoped to the URL it is paired with.
+    pub cookies: Vec<(Url, String)>,
+    /// Proxy for all requests (`http://`, `https://` or `socks5://`); overrides the environment.
+    pub proxy: Option<String>,
+    /// Honour `HTTP_PROXY` / `HTTPS_PROXY` / `NO_PROXY` when no explicit proxy is set.
+    pub use_env_proxy: bool,
+    /// PEM file with extra root certificates to trust.
+    pub ca_bundle: Option<PathBuf>,
+    /// Accept invalid TLS certificates and host names.
+    pub insecure: bool,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            max_redirects: 10,
+            headers: Vec::new(),
+            cookies: Vec::new(),
+            proxy: None,
+            use_env_proxy: true,
+            ca_bundle: None,
+            insecure: false,
+        }
+    }
+}
+
+/// A pooled client built from an [`HttpConfig`]; see the module docs.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    config: Arc<HttpConfig>,
+}
+
+static SHARED: Lazy<HttpClient> =
+    Lazy::new(|| HttpClient::new(HttpConfig::default()).expect("default HTTP client configuration is valid"));
+
+impl HttpClient {
+    /// Build a client; fails on malformed headers, cookies, proxy URLs or CA bundles.
+    pub fn new(config: HttpConfig) -> Result<Self, FetchError> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| FetchError::Config(format!("invalid header name '{name}': {e}")))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| FetchError::Config(format!("invalid value for header '{name}': {e}")))?;
+            headers.append(name, value);
+        }
+
+        let redirects = match config.max_redirects {
+            0 => redirect::Policy::none(),
+            n => redirect::Policy::limited(n),
+        };
+        let mut builder = Client::builder()
+            .user_agent(config.user_agent.as_str())
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .redirect(redirects)
+            .default_headers(headers)
+            .danger_accept_invalid_certs(config.insecure);
+
+        if !config.cookies.is_empty() {
+            let jar = Jar::default();
+            for (url, cookie) in &config.cookies {
+                jar.add_cookie_str(cookie, url);
+            }
+            builder = builder.cookie_provider(Arc::new(jar));
+        }
+
+        if let Some(proxy) = &config.proxy {
+            let proxy = Proxy::all(proxy).map_err(|e| FetchError::Config(format!("invalid proxy '{proxy}': {e}")))?;
+            builder = builder.proxy(proxy);
+        } else if !config.use_env_proxy {
+            builder = builder.no_proxy();
+        }
+
+        if let Some(path) = &config.ca_bundle {
+            let pem = std::fs::read(path)
+                .map_err(|e| FetchError::Config(format!("cannot read CA bundle {}: {e}", path.display())))?;
+            let certs = Certificate::from_pem_bundle(&pem)
+                .map_err(|e| FetchError::Config(format!("invalid CA bundle {}: {e}", path.display())))?;
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        let client = builder.build().map_err(|e| FetchError::Config(e.to_string()))?;
+        Ok(Self { client, config: Arc::new(config) })
+    }
+
+    /// The process-wide client with the default configuration.
+    pub fn shared() -> Self {
+        SHARED.clone()
+    }
+
+    pub fn config(&self) -> &HttpConfig {
+        &self.config
+    }
+
+    /// The underlying `reqwest` client, for requests not covered here.
+    pub fn inner(&self) -> &Client {
+        &self.client
+    }
+
+    /// Single GET request returning the body together with status and headers. Non-2xx
+    /// responses are errors.
+    pub async fn fetch_response(&self, url: &str) -> Result<FetchResponse, FetchError> {
+        let resp = self.get(&validate_url(url)?, HeaderMap::new()).await?;
+        if !(200..300).contains(&resp.status) {
+            return Err(FetchError::from_status(resp.status, &resp.headers));
+        }
+        Ok(resp)
+    }
+
+    /// GET `url` with extra request `headers`. Whatever status the server answers with is returned
+    /// as a response; only failures to get one are errors.
+    pub async fn get(&self, url: &Url, headers: HeaderMap) -> Result<FetchResponse, FetchError> {
+        let resp = self.client.get(url.clone()).headers(headers).send().await.map_err(network_error)?;
+        let status = resp.status().as_u16();
+        let final_url = resp.url().clone();
+        let headers = resp.headers().clone();
+        let body = resp.text().await.map_err(network_error)?;
+        Ok(FetchResponse { url: final_url, status, headers, body })
+    }
+
+    /// GET `url` unless it still matches `validators`: a `304 Not Modified` is returned as a
+    /// response with an empty body, other statuses outside 2xx are errors.
+    pub async fn fetch_conditional(&self, url: &Url, validators: &Validators) -> Result<FetchResponse, FetchError> {
+        let resp = self.get(&validate_url(url.as_str())?, validators.to_headers()).await?;
+        if !(200..300).contains(&resp.status) && !resp.is_not_modified() {
+            return Err(FetchError::from_status(resp.status, &resp.headers));
+        }
+        Ok(resp)
+    }
+
+    /// Whether `url` answers with a 2xx status. Uses HEAD, falling back to GET when the server
+    /// rejects HEAD with 405.
+    pub async fn exists(&self, url: &Url) -> bool {
+        self.check(url).await.is_ok()
+    }
+
+    /// Like [`Self::exists`], but says why `url` cannot be loaded.
+    pub async fn check(&self, url: &Url) -> Result<(), FetchError> {
+        let mut resp = self.client.head(url.clone()).send().await.map_err(network_error)?;
+        if resp.status() == StatusCode::METHOD_NOT_ALLOWED {
+            resp = self.client.get(url.clone()).send().await.map_err(network_error)?;
+        }
+        if !resp.status().is_success() {
+            return Err(FetchError::from_status(resp.status().as_u16(), resp.headers()));
+        }
+        Ok(())
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::shared()
+    }
+}
+
+impl fmt::Debug for HttpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpClient").field("config", &self.config).finish()
+    }
+}
+
+fn network_error(e: reqwest::Error) -> FetchError {
+    if e.is_timeout() {
+        FetchError::Timeout
+    } else {
+        FetchError::Network(e)
+    }
+}
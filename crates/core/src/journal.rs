@@ -0,0 +1,181 @@
+//! Run journal for resumable dumps.
+//!
+//! Every remote dump appends to `resourcer-journal.ndjson` in its output directory: first the
+//! planned chunk URLs, then one line per processed chunk with its state, the SHA-256 of its body
+//! and of every file written for it. A later run with [`crate::DumpOptions::resume`] reuses the
+//! plan instead of fetching the page again and skips chunks whose files are all still on disk with
+//! matching hashes. Lines are flushed one at a time, so a run killed halfway leaves at most one
+//! partial line, which is ignored.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::events::{DumpEvent, DumpObserver};
+use crate::source_ledger::content_hash;
+
+/// Journal file name, inside the output directory.
+pub const JOURNAL_FILE: &str = "resourcer-journal.ndjson";
+
+/// How far a chunk got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalState {
+    /// Downloaded and reconstructed.
+    Saved,
+    /// Download or reconstruction failed; retried on resume.
+    Failed,
+}
+
+/// A file written for a chunk, relative to the output directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalFile {
+    pub path: String,
+    pub sha256: String,
+}
+
+/// The outcome of one chunk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalChunk {
+    pub url: String,
+    pub state: JournalState,
+    /// SHA-256 of the downloaded body.
+    pub sha256: Option<String>,
+    pub files: Vec<JournalFile>,
+}
+
+/// One journal line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum JournalRecord {
+    /// The chunks a run set out to process, for the page `target`.
+    Plan { target: String, urls: Vec<String> },
+    Chunk(JournalChunk),
+}
+
+/// Reads and appends the journal of an output directory; see the module docs. As a
+/// [`DumpObserver`] it collects the files written for each chunk until [`RunJournal::record_chunk`].
+pub struct RunJournal {
+    out_root: PathBuf,
+    file: Mutex<File>,
+    plan: Option<(String, Vec<Url>)>,
+    chunks: HashMap<String, JournalChunk>,
+    written: Mutex<HashMap<String, Vec<PathBuf>>>,
+}
+
+impl RunJournal {
+    /// Start a new journal in `out_root`, discarding any previous one.
+    pub fn create(out_root: &Path) -> io::Result<Self> {
+        let file = File::create(out_root.join(JOURNAL_FILE))?;
+        Ok(Self::with_file(out_root, file))
+    }
+
+    /// Load the journal in `out_root` and keep appending to it; `None` if there is none.
+    pub fn open(out_root: &Path) -> io::Result<Option<Self>> {
+        let path = out_root.join(JOURNAL_FILE);
+        let existing = match File::open(&path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut journal = Self::with_file(out_root, OpenOptions::new().append(true).open(&path)?);
+        for line in BufReader::new(existing).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(JournalRecord::Plan { target, urls }) => {
+                    journal.plan = Some((target, urls.iter().filter_map(|u| Url::parse(u).ok()).collect()));
+                    journal.chunks.clear();
+                }
+                Ok(JournalRecord::Chunk(chunk)) => {
+                    journal.chunks.insert(chunk.url.clone(), chunk);
+                }
+                Err(e) => log::warn!("ignoring malformed line in {}: {}", path.display(), e),
+            }
+        }
+        Ok(Some(journal))
+    }
+
+    fn with_file(out_root: &Path, file: File) -> Self {
+        Self {
+            out_root: out_root.to_path_buf(),
+            file: Mutex::new(file),
+            plan: None,
+            chunks: HashMap::new(),
+            written: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The page and chunk URLs of the last recorded plan.
+    pub fn plan(&self) -> Option<(&str, &[Url])> {
+        self.plan.as_ref().map(|(target, urls)| (target.as_str(), urls.as_slice()))
+    }
+
+    /// The last recorded outcome of `url`.
+    pub fn chunk(&self, url: &str) -> Option<&JournalChunk> {
+        self.chunks.get(url)
+    }
+
+    /// Whether `url` was saved and every file written for it is still on disk unchanged.
+    pub fn is_done(&self, url: &str) -> bool {
+        self.chunk(url).is_some_and(|c| {
+            c.state == JournalState::Saved
+                && c.files.iter().all(|f| {
+                    std::fs::read(self.out_root.join(&f.path)).is_ok_and(|data| content_hash(&data) == f.sha256)
+                })
+        })
+    }
+
+    /// Start a plan for `target`; chunks recorded before it no longer count.
+    pub fn record_plan(&mut self, target: &str, urls: &[Url]) -> io::Result<()> {
+        let record = JournalRecord::Plan { target: target.into(), urls: urls.iter().map(Url::to_string).collect() };
+        self.append(&record)?;
+        self.plan = Some((target.into(), urls.to_vec()));
+        self.chunks.clear();
+        Ok(())
+    }
+
+    /// Record the outcome of `url`, with the files written for it since the last call. Failures
+    /// to write the journal are logged; they only cost the ability to resume.
+    pub fn record_chunk(&self, url: &str, state: JournalState, body: Option<&str>) {
+        let written = self.written.lock().expect("journal poisoned").remove(url).unwrap_or_default();
+        let files = written
+            .into_iter()
+            .filter_map(|p| {
+                let data = std::fs::read(self.out_root.join(&p)).ok()?;
+                Some(JournalFile { path: p.to_string_lossy().replace('\\', "/"), sha256: content_hash(&data) })
+            })
+            .collect();
+        let chunk = JournalChunk { url: url.into(), state, sha256: body.map(|b| content_hash(b.as_bytes())), files };
+        if let Err(e) = self.append(&JournalRecord::Chunk(chunk)) {
+            log::warn!("could not update run journal: {}", e);
+        }
+    }
+
+    fn append(&self, record: &JournalRecord) -> io::Result<()> {
+        let mut line = serde_json::to_string(record).map_err(io::Error::other)?;
+        line.push('\n');
+        let mut file = self.file.lock().expect("journal poisoned");
+        file.write_all(line.as_bytes())?;
+        file.flush()
+    }
+}
+
+impl DumpObserver for RunJournal {
+    fn on_event(&self, event: &DumpEvent) {
+        if let DumpEvent::FileWritten { chunk, path } = event {
+            let mut written = self.written.lock().expect("journal poisoned");
+            let files = written.entry(chunk.clone()).or_default();
+            if !files.contains(path) {
+                files.push(path.clone());
+            }
+        }
+    }
+}
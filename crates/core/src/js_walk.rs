@@ -0,0 +1,366 @@
+//! Minimal recursive walker over the SWC AST.
+//!
+//! Only the node types that can contain expressions or declarations are descended into; types,
+//! JSX and TypeScript-only constructs are skipped since bundler output never contains them.
+
+use swc_common::{sync::Lrc, FileName, SourceFile, SourceMap, Span};
+use swc_ecma_ast::*;
+use swc_ecma_parser::{Parser, StringInput, Syntax};
+
+/// Callbacks invoked while walking. Every expression is visited before its children.
+pub(crate) trait Visit {
+    fn expr(&mut self, _e: &Expr) {}
+    fn fn_decl(&mut self, _d: &FnDecl) {}
+    fn var_declarator(&mut self, _d: &VarDeclarator) {}
+}
+
+/// A parsed program together with the source file its spans point into.
+pub(crate) struct ParsedJs {
+    pub program: Program,
+    file: Lrc<SourceFile>,
+}
+
+impl ParsedJs {
+    /// Source text covered by `span`.
+    pub fn snippet(&self, span: Span) -> &str {
+        &self.file.src[self.offset(span.lo)..self.offset(span.hi)]
+    }
+
+    /// Byte offset of `pos` relative to the start of the source.
+    pub fn offset(&self, pos: swc_common::BytePos) -> usize {
+        (pos.0 - self.file.start_pos.0) as usize
+    }
+}
+
+/// Parse JavaScript as a script or module, whichever the source turns out to be.
+pub(crate) fn parse_js(name: &str, js: &str) -> Option<ParsedJs> {
+    let cm: Lrc<SourceMap> = Default::default();
+    let file = cm.new_source_file(FileName::Custom(name.into()).into(), js.to_owned());
+    let mut parser = Parser::new(Syntax::Es(Default::default()), StringInput::from(&*file), None);
+    let program = parser.parse_program().ok()?;
+    Some(ParsedJs { program, file })
+}
+
+pub(crate) fn walk_program<V: Visit>(program: &Program, v: &mut V) {
+    match program {
+        Program::Script(s) => s.body.iter().for_each(|st| walk_stmt(st, v)),
+        Program::Module(m) => {
+            for item in &m.body {
+                match item {
+                    ModuleItem::Stmt(st) => walk_stmt(st, v),
+                    ModuleItem::ModuleDecl(decl) => walk_module_decl(decl, v),
+                }
+            }
+        }
+    }
+}
+
+fn walk_module_decl<V: Visit>(decl: &ModuleDecl, v: &mut V) {
+    match decl {
+        ModuleDecl::ExportDecl(e) => walk_decl(&e.decl, v),
+        ModuleDecl::ExportDefaultExpr(e) => walk_expr(&e.expr, v),
+        ModuleDecl::ExportDefaultDecl(e) => match &e.decl {
+            DefaultDecl::Fn(f) => walk_function(&f.function, v),
+            DefaultDecl::Class(c) => walk_class(&c.class, v),
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+pub(crate) fn walk_stmts<V: Visit>(stmts: &[Stmt], v: &mut V) {
+    stmts.iter().for_each(|s| walk_stmt(s, v));
+}
+
+fn walk_stmt<V: Visit>(stmt: &Stmt, v: &mut V) {
+    match stmt {
+        Stmt::Block(b) => walk_stmts(&b.stmts, v),
+        Stmt::With(w) => {
+            walk_expr(&w.obj, v);
+            walk_stmt(&w.body, v);
+        }
+        Stmt::Return(r) => {
+            if let Some(a) = &r.arg {
+                walk_expr(a, v);
+            }
+        }
+        Stmt::Labeled(l) => walk_stmt(&l.body, v),
+        Stmt::If(i) => {
+            walk_expr(&i.test, v);
+            walk_stmt(&i.cons, v);
+            if let Some(alt) = &i.alt {
+                walk_stmt(alt, v);
+            }
+        }
+        Stmt::Switch(s) => {
+            walk_expr(&s.discriminant, v);
+            for case in &s.cases {
+                if let Some(t) = &case.test {
+                    walk_expr(t, v);
+                }
+                walk_stmts(&case.cons, v);
+            }
+        }
+        Stmt::Throw(t) => walk_expr(&t.arg, v),
+        Stmt::Try(t) => {
+            walk_stmts(&t.block.stmts, v);
+            if let Some(h) = &t.handler {
+                walk_stmts(&h.body.stmts, v);
+            }
+            if let Some(f) = &t.finalizer {
+                walk_stmts(&f.stmts, v);
+            }
+        }
+        Stmt::While(w) => {
+            walk_expr(&w.test, v);
+            walk_stmt(&w.body, v);
+        }
+        Stmt::DoWhile(d) => {
+            walk_stmt(&d.body, v);
+            walk_expr(&d.test, v);
+        }
+        Stmt::For(f) => {
+            match &f.init {
+                Some(VarDeclOrExpr::VarDecl(d)) => walk_var_decl(d, v),
+                Some(VarDeclOrExpr::Expr(e)) => walk_expr(e, v),
+                None => {}
+            }
+            if let Some(t) = &f.test {
+                walk_expr(t, v);
+            }
+            if let Some(u) = &f.update {
+                walk_expr(u, v);
+            }
+            walk_stmt(&f.body, v);
+        }
+        Stmt::ForIn(f) => {
+            walk_expr(&f.right, v);
+            walk_stmt(&f.body, v);
+        }
+        Stmt::ForOf(f) => {
+            walk_expr(&f.right, v);
+            walk_stmt(&f.body, v);
+        }
+        Stmt::Decl(d) => walk_decl(d, v),
+        Stmt::Expr(e) => walk_expr(&e.expr, v),
+        _ => {}
+    }
+}
+
+fn walk_decl<V: Visit>(decl: &Decl, v: &mut V) {
+    match decl {
+        Decl::Fn(f) => {
+            v.fn_decl(f);
+            walk_function(&f.function, v);
+        }
+        Decl::Var(d) => walk_var_decl(d, v),
+        Decl::Class(c) => walk_class(&c.class, v),
+        _ => {}
+    }
+}
+
+fn walk_var_decl<V: Visit>(decl: &VarDecl, v: &mut V) {
+    for d in &decl.decls {
+        v.var_declarator(d);
+        if let Some(init) = &d.init {
+            walk_expr(init, v);
+        }
+    }
+}
+
+fn walk_function<V: Visit>(f: &Function, v: &mut V) {
+    if let Some(body) = &f.body {
+        walk_stmts(&body.stmts, v);
+    }
+}
+
+fn walk_class<V: Visit>(class: &Class, v: &mut V) {
+    if let Some(s) = &class.super_class {
+        walk_expr(s, v);
+    }
+    for member in &class.body {
+        match member {
+            ClassMember::Constructor(c) => {
+                if let Some(body) = &c.body {
+                    walk_stmts(&body.stmts, v);
+                }
+            }
+            ClassMember::Method(m) => walk_function(&m.function, v),
+            ClassMember::PrivateMethod(m) => walk_function(&m.function, v),
+            ClassMember::ClassProp(p) => {
+                if let Some(val) = &p.value {
+                    walk_expr(val, v);
+                }
+            }
+            ClassMember::PrivateProp(p) => {
+                if let Some(val) = &p.value {
+                    walk_expr(val, v);
+                }
+            }
+            ClassMember::StaticBlock(b) => walk_stmts(&b.body.stmts, v),
+            _ => {}
+        }
+    }
+}
+
+fn walk_args<V: Visit>(args: &[ExprOrSpread], v: &mut V) {
+    args.iter().for_each(|a| walk_expr(&a.expr, v));
+}
+
+fn walk_prop_name<V: Visit>(key: &PropName, v: &mut V) {
+    if let PropName::Computed(c) = key {
+        walk_expr(&c.expr, v);
+    }
+}
+
+pub(crate) fn walk_expr<V: Visit>(expr: &Expr, v: &mut V) {
+    v.expr(expr);
+    match expr {
+        Expr::Array(a) => a.elems.iter().flatten().for_each(|e| walk_expr(&e.expr, v)),
+        Expr::Object(o) => {
+            for prop in &o.props {
+                match prop {
+                    PropOrSpread::Spread(s) => walk_expr(&s.expr, v),
+                    PropOrSpread::Prop(p) => match &**p {
+                        Prop::KeyValue(kv) => {
+                            walk_prop_name(&kv.key, v);
+                            walk_expr(&kv.value, v);
+                        }
+                        Prop::Assign(a) => walk_expr(&a.value, v),
+                        Prop::Getter(g) => {
+                            if let Some(body) = &g.body {
+                                walk_stmts(&body.stmts, v);
+                            }
+                        }
+                        Prop::Setter(s) => {
+                            if let Some(body) = &s.body {
+                                walk_stmts(&body.stmts, v);
+                            }
+                        }
+                        Prop::Method(m) => walk_function(&m.function, v),
+                        Prop::Shorthand(_) => {}
+                    },
+                }
+            }
+        }
+        Expr::Fn(f) => walk_function(&f.function, v),
+        Expr::Unary(u) => walk_expr(&u.arg, v),
+        Expr::Update(u) => walk_expr(&u.arg, v),
+        Expr::Bin(b) => {
+            walk_expr(&b.left, v);
+            walk_expr(&b.right, v);
+        }
+        Expr::Assign(a) => {
+            if let AssignTarget::Simple(SimpleAssignTarget::Member(m)) = &a.left {
+                walk_member(m, v);
+            }
+            walk_expr(&a.right, v);
+        }
+        Expr::Member(m) => walk_member(m, v),
+        Expr::Cond(c) => {
+            walk_expr(&c.test, v);
+            walk_expr(&c.cons, v);
+            walk_expr(&c.alt, v);
+        }
+        Expr::Call(c) => {
+            if let Callee::Expr(callee) = &c.callee {
+                walk_expr(callee, v);
+            }
+            walk_args(&c.args, v);
+        }
+        Expr::New(n) => {
+            walk_expr(&n.callee, v);
+            if let Some(args) = &n.args {
+                walk_args(args, v);
+            }
+        }
+        Expr::Seq(s) => s.exprs.iter().for_each(|e| walk_expr(e, v)),
+        Expr::Tpl(t) => t.exprs.iter().for_each(|e| walk_expr(e, v)),
+        Expr::TaggedTpl(t) => {
+            walk_expr(&t.tag, v);
+            t.tpl.exprs.iter().for_each(|e| walk_expr(e, v));
+        }
+        Expr::Arrow(a) => match &*a.body {
+            BlockStmtOrExpr::BlockStmt(b) => walk_stmts(&b.stmts, v),
+            BlockStmtOrExpr::Expr(e) => walk_expr(e, v),
+        },
+        Expr::Class(c) => walk_class(&c.class, v),
+        Expr::Yield(y) => {
+            if let Some(a) = &y.arg {
+                walk_expr(a, v);
+            }
+        }
+        Expr::Await(a) => walk_expr(&a.arg, v),
+        Expr::Paren(p) => walk_expr(&p.expr, v),
+        Expr::OptChain(o) => match &*o.base {
+            OptChainBase::Member(m) => walk_member(m, v),
+            OptChainBase::Call(c) => {
+                walk_expr(&c.callee, v);
+                walk_args(&c.args, v);
+            }
+        },
+        _ => {}
+    }
+}
+
+fn walk_member<V: Visit>(m: &MemberExpr, v: &mut V) {
+    walk_expr(&m.obj, v);
+    if let MemberProp::Computed(c) = &m.prop {
+        walk_expr(&c.expr, v);
+    }
+}
+
+/// Strip any number of wrapping parentheses.
+pub(crate) fn unparen(mut e: &Expr) -> &Expr {
+    while let Expr::Paren(p) = e {
+        e = &p.expr;
+    }
+    e
+}
+
+/// Value of a string or numeric literal as a string (`"a"` -> `a`, `12` -> `12`).
+pub(crate) fn literal_key(e: &Expr) -> Option<String> {
+    match unparen(e) {
+        Expr::Lit(Lit::Str(s)) => Some(s.value.to_string()),
+        Expr::Lit(Lit::Num(n)) => Some(number_key(n)),
+        _ => None,
+    }
+}
+
+pub(crate) fn number_key(n: &Number) -> String {
+    if n.value.fract() == 0.0 && n.value.abs() < 1e15 {
+        format!("{}", n.value as i64)
+    } else {
+        n.value.to_string()
+    }
+}
+
+/// Key of an object-literal property (`a:`, `"a":`, `12:`).
+pub(crate) fn prop_key(key: &PropName) -> Option<String> {
+    match key {
+        PropName::Ident(i) => Some(i.sym.to_string()),
+        PropName::Str(s) => Some(s.value.to_string()),
+        PropName::Num(n) => Some(number_key(n)),
+        _ => None,
+    }
+}
+
+/// Name of the property accessed by a member expression (`a.b` or `a["b"]`).
+pub(crate) fn member_prop_name(m: &MemberExpr) -> Option<String> {
+    match &m.prop {
+        MemberProp::Ident(i) => Some(i.sym.to_string()),
+        MemberProp::Computed(c) => match unparen(&c.expr) {
+            Expr::Lit(Lit::Str(s)) => Some(s.value.to_string()),
+            _ => None,
+        },
+        MemberProp::PrivateName(_) => None,
+    }
+}
+
+/// Name of a plain identifier parameter.
+pub(crate) fn pat_ident(p: &Pat) -> Option<String> {
+    match p {
+        Pat::Ident(b) => Some(b.id.sym.to_string()),
+        _ => None,
+    }
+}
@@ -1,36 +1,83 @@
 pub mod fetch;
 
-pub use fetch::{fetch_html, FetchError};
+pub use fetch::{fetch_html, fetch_response, fetch_response_with_retries, fetch_url, FetchError, FetchResponse, Validators};
+pub mod http;
+pub use http::{HttpClient, HttpConfig, DEFAULT_USER_AGENT};
+pub mod fetcher;
+pub use fetcher::{default_fetcher, ArchiveFetcher, CompositeFetcher, DataUrlFetcher, Fetcher, FileFetcher, HttpFetcher, MemoryFetcher};
+pub mod cassette;
+pub use cassette::{CassetteEntry, RecordingFetcher, ReplayFetcher, CASSETTE_INDEX};
+pub mod har;
+pub use har::{HarArchive, HarEntry, HarError};
+pub mod journal;
+pub use journal::{JournalChunk, JournalFile, JournalRecord, JournalState, RunJournal, JOURNAL_FILE};
+pub mod warc;
+pub use warc::{WarcArchive, WarcError, WarcResponse};
+pub mod retry;
+pub use retry::{parse_retry_after, RetryPolicy};
+pub mod throttle;
+pub use throttle::{Throttle, ThrottleConfig, ThrottlePermit, ThrottledFetcher};
+pub mod data_url;
+pub use data_url::{decode_data_url, url_label, DataUrl, DataUrlError};
 pub mod storage;
-pub use storage::{HtmlStorage, MemoryStorage, StorageError, HtmlDocument};
+pub use storage::{CachingFetcher, FsStorage, HtmlStorage, MemoryStorage, SqliteStorage, StorageError, HtmlDocument};
 pub mod file_io;
 
 pub use file_io::{validate_js_path, read_js_file, FileAnalysisError};
 
 pub mod local_analysis;
 
-pub use local_analysis::{analyze_local_js, AnalysisError};
+pub use local_analysis::{analyze_local_js, analyze_local_js_with_sourcemaps, collect_local_bundle, AnalysisError, LocalBundle};
 
 pub mod sourcemap;
-pub use sourcemap::{extract_sourcemap_urls, validate_sourcemap_urls, find_sourcemap_urls};
+pub use sourcemap::{extract_sourcemap_urls, validate_sourcemap_urls, find_sourcemap_urls, find_sourcemap_urls_with_headers, sourcemap_urls_from_headers, sourcemap_probe_candidates, sourcemap_file_matches};
+
+mod js_walk;
+
+pub mod webpack_runtime;
+pub use webpack_runtime::{analyze_webpack_runtime, ChunkUrlExpr, WebpackRuntimeInfo};
+
+pub mod webpack_modules;
+pub use webpack_modules::{split_webpack_modules, write_webpack_modules, WebpackModule};
 
 pub mod webpack;
-pub use webpack::{infer_chunk_filename_template, ChunkFilenameTemplate, extract_public_path, build_chunk_url, validate_chunk_urls, swc_print_top_level, extract_chunk_ids, generate_chunk_urls, extract_literal_chunk_paths, extract_chunk_maps, generate_urls_from_chunk_maps, extract_paths_from_build_manifest};
+pub use webpack::{infer_chunk_filename_template, ChunkFilenameTemplate, extract_public_path, build_chunk_url, validate_chunk_urls, validate_chunk_urls_with, validate_chunk_urls_with_policy, swc_print_top_level, extract_chunk_ids, generate_chunk_urls, extract_literal_chunk_paths, extract_chunk_maps, generate_urls_from_chunk_maps, extract_paths_from_build_manifest};
 
 pub mod sm_parse;
-pub use sm_parse::{parse_sourcemap, SourcemapError, sources_list};
+pub use sm_parse::{parse_sourcemap, parse_decoded_sourcemap, load_sourcemap, load_sourcemap_with, load_sourcemap_with_policy, SourcemapError, sources_list};
+
+pub mod sanitize;
+pub use sanitize::{sanitize_relative_path, safe_join, check_no_symlinks, write_in_tree, OUTSIDE_DIR};
+
+pub mod source_paths;
+pub use source_paths::{SourceNormalizer, SourcePathOptions, SourceUrlRule, MappedSource};
+
+pub mod source_ledger;
+pub use source_ledger::{content_hash, ConflictPolicy, SourceLedger, SourceRecord, SourceVersion, WriteAction, LEDGER_FILE};
 
 pub mod path_reconstruct;
-pub use path_reconstruct::reconstruct_paths;
+pub use path_reconstruct::{reconstruct_paths, reconstruct_paths_with};
 
 pub mod download_manager;
-pub use download_manager::{download_many, DownloadManagerConfig, DownloadResult};
+pub use download_manager::{download_many, download_stream, DownloadManagerConfig, DownloadResult};
 
 pub mod output;
 pub use output::{ensure_output_dir, mirror_structure, copy_files, validate_output};
 
 pub mod url_utils;
-pub use url_utils::{find_sourcemap_url_in_js, derive_base_from_runtime, extract_script_urls};
+pub use url_utils::{find_sourcemap_url_in_js, derive_base_from_runtime, extract_script_urls, may_reference};
 
 pub mod cli_ops;
-pub use cli_ops::{save_js_and_sources, handle_list_urls, CLIError};
+pub use cli_ops::{save_js_and_sources, save_js_and_sources_with_headers, save_fetched_asset, save_asset_with_maps, save_asset_with_options, SaveOptions, SaveOutcome, SavedSource, handle_list_urls, CLIError};
+
+pub mod report;
+pub use report::{DumpReport, ReportFormat, ReportSummary, RuntimeReport, ChunkReport, ChunkStatus, MapReport, MapOrigin, MapStatus, FileReport, ErrorReport};
+
+pub mod detect;
+pub use detect::{ChunkPlan, Detection, DetectorError, DetectorRegistry, PageContext, RuntimeDetector};
+
+pub mod events;
+pub use events::{ChannelObserver, DumpEvent, DumpObserver, Observers};
+
+pub mod dump;
+pub use dump::{Dumper, DumpOptions, DumpResult, DumpStats, DumpTarget};
@@ -1,8 +1,8 @@
 use crate::file_io::{read_js_file, validate_js_path, FileAnalysisError};
 use crate::storage::{HtmlStorage, StorageError, HtmlDocument};
-use chrono::Utc;
 use url::Url;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
 #[derive(thiserror::Error, Debug)]
 pub enum AnalysisError {
@@ -17,6 +17,7 @@ pub enum AnalysisError {
 /// Analyze a local JavaScript file:
 /// 1. Validate and read the file.
 /// 2. Store the content via provided HtmlStorage implementation.
+///
 /// Returns the stored HtmlDocument instance.
 pub async fn analyze_local_js<S>(path: &Path, store: &S) -> Result<HtmlDocument, AnalysisError>
 where
@@ -26,13 +27,10 @@ where
     let content = read_js_file(&canonical).await?;
 
     let file_url = Url::from_file_path(&canonical).map_err(|_| url::ParseError::IdnaError)?;
-    store.save_html(file_url.clone(), content.clone()).await?;
+    let doc = HtmlDocument::new(file_url, content);
+    store.save(doc.clone()).await?;
 
-    Ok(HtmlDocument {
-        url: file_url,
-        timestamp: Utc::now(),
-        content,
-    })
+    Ok(doc)
 }
 
 pub async fn analyze_local_js_with_sourcemaps<S>(path: &Path, store: &S) -> Result<(HtmlDocument, Vec<Url>), AnalysisError>
@@ -41,6 +39,66 @@ where
 {
     let doc = analyze_local_js(path, store).await?;
     // Re-use the stored document’s file URL as the base for relative sourcemap resolution.
-    let maps = crate::sourcemap::find_sourcemap_urls(&doc.url, &doc.content);
+    let maps = crate::sourcemap::find_sourcemap_urls(&doc.url, &doc.text());
     Ok((doc, maps))
 }
+
+/// A local build to dump: a single bundle file or every script/stylesheet under a build directory
+/// such as `dist/` or `.next/`.
+#[derive(Debug, Clone)]
+pub struct LocalBundle {
+    /// Canonical directory that output paths are made relative to.
+    pub root: PathBuf,
+    /// `file://` URL of `root`, with a trailing slash so relative joins stay inside it.
+    pub root_url: Url,
+    /// Contents of `root/index.html`, when the build ships one.
+    pub index_html: Option<String>,
+    /// Canonical paths of the `.js` and `.css` files found, sorted.
+    pub files: Vec<PathBuf>,
+}
+
+impl LocalBundle {
+    /// `file://` URLs of the bundle's scripts, in the same order as `files`.
+    pub fn script_urls(&self) -> Vec<Url> {
+        self.files
+            .iter()
+            .filter(|p| p.extension().is_some_and(|e| e == "js"))
+            .filter_map(|p| Url::from_file_path(p).ok())
+            .collect()
+    }
+}
+
+/// Collect the files making up a local bundle. `input` may be a single `.js` file (validated with
+/// [`validate_js_path`]) or a directory, which is walked recursively for `.js` and `.css` files.
+pub async fn collect_local_bundle(input: &Path) -> Result<LocalBundle, AnalysisError> {
+    let is_dir = tokio::fs::metadata(input)
+        .await
+        .map(|m| m.is_dir())
+        .unwrap_or(false);
+
+    let (root, files) = if is_dir {
+        let root = tokio::fs::canonicalize(input).await.map_err(FileAnalysisError::Io)?;
+        let mut files: Vec<PathBuf> = WalkDir::new(&root)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|p| p.extension().is_some_and(|e| e == "js" || e == "css"))
+            .collect();
+        files.sort();
+        (root, files)
+    } else {
+        let file = validate_js_path(input).await?;
+        let root = file.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("/"));
+        (root, vec![file])
+    };
+
+    let root_url = Url::from_directory_path(&root).map_err(|_| url::ParseError::IdnaError)?;
+    let index_html = if is_dir {
+        tokio::fs::read_to_string(root.join("index.html")).await.ok()
+    } else {
+        None
+    };
+
+    Ok(LocalBundle { root, root_url, index_html, files })
+}
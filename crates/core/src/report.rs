@@ -0,0 +1,275 @@
+//! Machine-readable summary of a dump run.
+//!
+//! A [`DumpReport`] collects the detected runtime, every chunk with its status/size/hash, every
+//! sourcemap that was found, missing or unparseable, every reconstructed file with the map it came
+//! from, and errors with their [`CLIError::kind`]/[`FetchError::kind`]. It is written either as one
+//! JSON document or as NDJSON, one record per line with a `"type"` field.
+
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::source_ledger::content_hash;
+use crate::{url_label, CLIError, FetchError, SaveOutcome, WriteAction};
+
+/// Output format of [`DumpReport::write`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Ndjson,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            other => Err(format!("unknown report format '{other}' (expected json or ndjson)")),
+        }
+    }
+}
+
+/// The detector whose plan was used.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuntimeReport {
+    pub detector: String,
+    pub confidence: f32,
+    pub strategy: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkStatus {
+    Saved,
+    DownloadFailed,
+    ProcessingFailed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChunkReport {
+    /// Chunk URL (or local path).
+    pub url: String,
+    pub status: ChunkStatus,
+    pub http_status: Option<u16>,
+    pub size: Option<usize>,
+    /// Hex SHA-256 of the chunk body.
+    pub sha256: Option<String>,
+    /// Webpack modules split out because the chunk had no sourcemap.
+    pub split_modules: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapOrigin {
+    Declared,
+    Probed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapStatus {
+    Loaded,
+    Missing,
+    Unparseable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MapReport {
+    pub url: String,
+    /// The chunk that referenced (or was probed for) the map.
+    pub chunk: String,
+    pub origin: MapOrigin,
+    pub status: MapStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileReport {
+    /// Path relative to the output directory.
+    pub path: String,
+    /// Sourcemap the file was reconstructed from.
+    pub map: String,
+    pub chunk: String,
+    /// `written`, `duplicate`, `replaced`, `discarded` or `variant` (see [`WriteAction`]).
+    pub action: &'static str,
+    /// For `variant`: where the conflicting version was written.
+    pub written_to: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorReport {
+    pub url: Option<String>,
+    /// e.g. `fetch.http_status`, `io`.
+    pub kind: String,
+    pub message: String,
+}
+
+/// Totals over a [`DumpReport`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReportSummary {
+    pub chunks: usize,
+    pub chunks_saved: usize,
+    /// Saved chunks the server reported unchanged (`304 Not Modified`) since they were cached.
+    pub chunks_unchanged: usize,
+    pub chunks_failed: usize,
+    pub maps_loaded: usize,
+    pub maps_missing: usize,
+    pub maps_unparseable: usize,
+    pub files_written: usize,
+    pub errors: usize,
+}
+
+/// See the module docs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DumpReport {
+    /// Page URL or local input path.
+    pub target: String,
+    pub out_dir: Option<String>,
+    pub runtime: Option<RuntimeReport>,
+    pub chunks: Vec<ChunkReport>,
+    pub maps: Vec<MapReport>,
+    pub files: Vec<FileReport>,
+    pub errors: Vec<ErrorReport>,
+}
+
+/// One NDJSON line.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record<'a> {
+    Target { target: &'a str, out_dir: Option<&'a str> },
+    Runtime(&'a RuntimeReport),
+    Chunk(&'a ChunkReport),
+    Map(&'a MapReport),
+    File(&'a FileReport),
+    Error(&'a ErrorReport),
+    Summary(ReportSummary),
+}
+
+#[derive(Serialize)]
+struct WithSummary<'a> {
+    #[serde(flatten)]
+    report: &'a DumpReport,
+    summary: ReportSummary,
+}
+
+impl DumpReport {
+    pub fn new(target: impl Into<String>) -> Self {
+        Self { target: target.into(), ..Default::default() }
+    }
+
+    pub fn set_runtime(&mut self, detector: &str, confidence: f32, strategy: &str) {
+        self.runtime = Some(RuntimeReport { detector: detector.into(), confidence, strategy: strategy.into() });
+    }
+
+    /// Record a saved chunk together with its maps and reconstructed files.
+    pub fn record_saved(&mut self, url: &str, http_status: Option<u16>, body: &str, outcome: &SaveOutcome) {
+        self.chunks.push(ChunkReport {
+            url: url.into(),
+            status: ChunkStatus::Saved,
+            http_status,
+            size: Some(body.len()),
+            sha256: Some(content_hash(body.as_bytes())),
+            split_modules: outcome.split_modules,
+        });
+
+        let maps = [
+            (&outcome.declared_maps, MapOrigin::Declared, MapStatus::Loaded),
+            (&outcome.probed_maps, MapOrigin::Probed, MapStatus::Loaded),
+            (&outcome.missing_maps, MapOrigin::Declared, MapStatus::Missing),
+            (&outcome.unparseable_maps, MapOrigin::Declared, MapStatus::Unparseable),
+        ];
+        for (urls, origin, status) in maps {
+            self.maps
+                .extend(urls.iter().map(|u| MapReport { url: url_label(u), chunk: url.into(), origin, status }));
+        }
+
+        for s in &outcome.sources {
+            let (action, written_to) = match &s.action {
+                WriteAction::Written => ("written", None),
+                WriteAction::Duplicate => ("duplicate", None),
+                WriteAction::Replaced => ("replaced", None),
+                WriteAction::Discarded => ("discarded", None),
+                WriteAction::Variant(p) => ("variant", Some(slash_path(p))),
+            };
+            self.files.push(FileReport {
+                path: slash_path(&s.path),
+                map: s.map.clone(),
+                chunk: url.into(),
+                action,
+                written_to,
+            });
+        }
+    }
+
+    /// Record a chunk that could not be downloaded.
+    pub fn record_download_failure(&mut self, url: &str, err: &FetchError) {
+        self.push_failed(url, ChunkStatus::DownloadFailed, err.status());
+        self.push_error(Some(url), format!("fetch.{}", err.kind()), err.to_string());
+    }
+
+    /// Record a chunk that was downloaded but could not be saved or reconstructed.
+    pub fn record_processing_failure(&mut self, url: &str, http_status: Option<u16>, err: &CLIError) {
+        self.push_failed(url, ChunkStatus::ProcessingFailed, http_status);
+        self.push_error(Some(url), err.kind(), err.to_string());
+    }
+
+    /// Record an error not tied to a single chunk (e.g. a failed detector).
+    pub fn record_error(&mut self, url: Option<&str>, kind: impl Into<String>, message: impl Into<String>) {
+        self.push_error(url, kind.into(), message.into());
+    }
+
+    fn push_failed(&mut self, url: &str, status: ChunkStatus, http_status: Option<u16>) {
+        self.chunks.push(ChunkReport { url: url.into(), status, http_status, size: None, sha256: None, split_modules: 0 });
+    }
+
+    fn push_error(&mut self, url: Option<&str>, kind: String, message: String) {
+        self.errors.push(ErrorReport { url: url.map(Into::into), kind, message });
+    }
+
+    pub fn summary(&self) -> ReportSummary {
+        let map_count = |st: MapStatus| self.maps.iter().filter(|m| m.status == st).count();
+        let saved = self.chunks.iter().filter(|c| c.status == ChunkStatus::Saved).count();
+        ReportSummary {
+            chunks: self.chunks.len(),
+            chunks_saved: saved,
+            chunks_unchanged: self.chunks.iter().filter(|c| c.status == ChunkStatus::Saved && c.http_status == Some(304)).count(),
+            chunks_failed: self.chunks.len() - saved,
+            maps_loaded: map_count(MapStatus::Loaded),
+            maps_missing: map_count(MapStatus::Missing),
+            maps_unparseable: map_count(MapStatus::Unparseable),
+            files_written: self.files.iter().filter(|f| matches!(f.action, "written" | "replaced" | "variant")).count(),
+            errors: self.errors.len(),
+        }
+    }
+
+    /// Write the report (including its [`ReportSummary`]) in `format`.
+    pub fn write<W: Write>(&self, format: ReportFormat, mut w: W) -> io::Result<()> {
+        match format {
+            ReportFormat::Json => {
+                serde_json::to_writer_pretty(&mut w, &WithSummary { report: self, summary: self.summary() })?;
+                writeln!(w)
+            }
+            ReportFormat::Ndjson => {
+                let mut records = vec![Record::Target { target: &self.target, out_dir: self.out_dir.as_deref() }];
+                records.extend(self.runtime.iter().map(Record::Runtime));
+                records.extend(self.chunks.iter().map(Record::Chunk));
+                records.extend(self.maps.iter().map(Record::Map));
+                records.extend(self.files.iter().map(Record::File));
+                records.extend(self.errors.iter().map(Record::Error));
+                records.push(Record::Summary(self.summary()));
+                for r in records {
+                    serde_json::to_writer(&mut w, &r)?;
+                    writeln!(w)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn slash_path(p: &std::path::Path) -> String {
+    p.to_string_lossy().replace('\\', "/")
+}
@@ -0,0 +1,101 @@
+//! When and how long to wait before trying a request again.
+//!
+//! A [`RetryPolicy`] retries timeouts, network errors and a configurable set of statuses
+//! (`429` and the transient `5xx` by default), backing off exponentially with optional jitter.
+//! When the server says how long to wait with `Retry-After` (in seconds or as an HTTP date, see
+//! [`parse_retry_after`]), that wait is used instead; a wait longer than the policy's maximum
+//! delay gives up right away.
+
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+
+use crate::FetchError;
+
+/// Retry settings shared by [`crate::Fetcher::fetch_with_policy`], [`crate::download_many`] and
+/// [`crate::validate_chunk_urls_with_policy`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Tries per request, including the first one.
+    pub max_attempts: usize,
+    /// Wait before the first retry; doubled for each further one.
+    pub base_delay: Duration,
+    /// Longest wait between tries, including waits asked for with `Retry-After`.
+    pub max_delay: Duration,
+    /// Wait a random 50-100% of the backoff delay, so clients do not retry in lockstep.
+    pub jitter: bool,
+    /// Statuses worth trying again.
+    pub retry_statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            retry_statuses: vec![408, 425, 429, 500, 502, 503, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The default policy with `max_attempts` tries.
+    pub fn with_attempts(max_attempts: usize) -> Self {
+        Self { max_attempts, ..Self::default() }
+    }
+
+    /// Whether `err` may go away on another try.
+    pub fn is_retryable(&self, err: &FetchError) -> bool {
+        match err {
+            FetchError::Timeout | FetchError::Network(_) => true,
+            _ => err.status().is_some_and(|s| self.retry_statuses.contains(&s)),
+        }
+    }
+
+    /// How long to wait before retry number `retry` (0 for the first) after `err`; `None` when
+    /// `err` is not worth retrying or the server asked for a longer wait than `max_delay`.
+    pub fn delay(&self, retry: u32, err: &FetchError) -> Option<Duration> {
+        if !self.is_retryable(err) {
+            return None;
+        }
+        if let Some(wait) = err.retry_after() {
+            return (wait <= self.max_delay).then_some(wait);
+        }
+        let backoff = self.base_delay.saturating_mul(2u32.saturating_pow(retry)).min(self.max_delay);
+        Some(if self.jitter { backoff.mul_f64(0.5 + fastrand::f64() / 2.0) } else { backoff })
+    }
+
+    /// Run `attempt` until it succeeds, fails for good or `max_attempts` tries are used up.
+    pub async fn retry<T, F, Fut>(&self, mut attempt: F) -> Result<T, FetchError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, FetchError>>,
+    {
+        let mut tries = 1;
+        loop {
+            let err = match attempt().await {
+                Ok(v) => return Ok(v),
+                Err(e) => e,
+            };
+            if tries >= self.max_attempts {
+                return Err(err);
+            }
+            let Some(wait) = self.delay(tries as u32 - 1, &err) else { return Err(err) };
+            log::debug!("retrying in {:?} after: {}", wait, err);
+            tokio::time::sleep(wait).await;
+            tries += 1;
+        }
+    }
+}
+
+/// The wait asked for by a `Retry-After` header: a number of seconds or an HTTP date, which is
+/// taken relative to `now` (dates in the past mean no wait).
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
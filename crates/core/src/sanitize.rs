@@ -0,0 +1,91 @@
+//! Keeping reconstructed files inside the output directory.
+//!
+//! Source paths come from sourcemaps and URLs of sites we do not control, so every path written
+//! under `--out` goes through [`sanitize_relative_path`] and [`write_in_tree`]:
+//!
+//! * `\` separators are treated as `/`, `.` and empty components are dropped and `..` is resolved
+//! * absolute roots (`/`, `//server/share`) and drive letters (`C:`) are stripped
+//! * a `..` that would climb above the root quarantines the rest of the path under
+//!   [`OUTSIDE_DIR`] instead of escaping (`../../etc/passwd` -> `_outside/etc/passwd`)
+//! * writes refuse to pass through symlinks that already exist inside the output tree
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// Top-level folder for sources whose paths tried to climb out of the output directory.
+pub const OUTSIDE_DIR: &str = "_outside";
+
+/// Turn an untrusted path into a relative path that cannot leave the directory it is joined to.
+/// Never returns an empty path; a path with no usable components becomes `_`.
+pub fn sanitize_relative_path(raw: &str) -> PathBuf {
+    let normalized = raw.replace('\\', "/");
+    let mut rest = normalized.as_str();
+    let bytes = rest.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        rest = &rest[2..];
+    }
+
+    let mut parts: Vec<String> = Vec::new();
+    let mut escaped = false;
+    for comp in rest.split('/') {
+        match comp {
+            "" | "." => {}
+            ".." => {
+                if parts.pop().is_none() {
+                    escaped = true;
+                }
+            }
+            c => parts.push(c.chars().map(|ch| if ch.is_control() { '_' } else { ch }).collect()),
+        }
+    }
+
+    let mut out = PathBuf::new();
+    if escaped {
+        out.push(OUTSIDE_DIR);
+    }
+    if parts.is_empty() {
+        out.push("_");
+    }
+    out.extend(parts);
+    out
+}
+
+/// `root.join(sanitize_relative_path(raw))`.
+pub fn safe_join(root: &Path, raw: &str) -> PathBuf {
+    root.join(sanitize_relative_path(raw))
+}
+
+/// Fail if `path` is not under `root` or if any existing component between them (including
+/// `path` itself) is a symlink.
+pub fn check_no_symlinks(root: &Path, path: &Path) -> io::Result<()> {
+    let rel = path.strip_prefix(root).map_err(|_| {
+        io::Error::new(ErrorKind::PermissionDenied, format!("{} is outside {}", path.display(), root.display()))
+    })?;
+    let mut cur = root.to_path_buf();
+    for c in rel.components() {
+        cur.push(c);
+        match fs::symlink_metadata(&cur) {
+            Ok(m) if m.file_type().is_symlink() => {
+                return Err(io::Error::new(
+                    ErrorKind::PermissionDenied,
+                    format!("refusing to follow symlink {}", cur.display()),
+                ))
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Write `contents` to `path` (which must lie under `root`), creating parent directories and
+/// refusing to write through symlinks inside the tree.
+pub fn write_in_tree(root: &Path, path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    check_no_symlinks(root, path)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
@@ -1,4 +1,5 @@
-use sourcemap::SourceMap;
+use sourcemap::{DecodedMap, SourceMap};
+use url::Url;
 use thiserror::Error;
 use swc_ecma_parser::{Parser, StringInput, Syntax};
 use swc_common::{sync::Lrc, SourceMap as SwcSourceMap, FileName, Span};
@@ -7,15 +8,98 @@ use std::collections::HashMap;
 use swc_ecma_ast::Module;
 use swc_common::Spanned;
 
+use crate::fetcher::{default_fetcher, Fetcher};
+use crate::retry::RetryPolicy;
+use crate::url_utils::may_reference;
+
 #[derive(Error, Debug)]
 pub enum SourcemapError {
     #[error("invalid JSON or sourcemap: {0}")]
     Parse(String),
 }
 
+/// Maximum nesting of index maps referencing further index maps via `url` sections.
+const MAX_INDEX_DEPTH: usize = 4;
+
 /// Parse a sourcemap JSON string and return the `sourcemap::SourceMap` object.
+///
+/// Index maps (with a `sections` array) are flattened into a single map; this fails if any
+/// section references its map by `url` instead of embedding it, see [`load_sourcemap`].
 pub fn parse_sourcemap(json: &str) -> Result<SourceMap, SourcemapError> {
-    SourceMap::from_slice(json.as_bytes()).map_err(|e| SourcemapError::Parse(e.to_string()))
+    flatten_decoded(parse_decoded_sourcemap(json)?)
+}
+
+/// Parse any kind of sourcemap (regular, index, or Hermes) without flattening.
+pub fn parse_decoded_sourcemap(json: &str) -> Result<DecodedMap, SourcemapError> {
+    sourcemap::decode_slice(json.as_bytes()).map_err(|e| SourcemapError::Parse(e.to_string()))
+}
+
+fn flatten_decoded(map: DecodedMap) -> Result<SourceMap, SourcemapError> {
+    match map {
+        DecodedMap::Regular(sm) => Ok(sm),
+        DecodedMap::Index(idx) => idx.flatten().map_err(|e| SourcemapError::Parse(e.to_string())),
+        DecodedMap::Hermes(smh) => Ok((*smh).clone()),
+    }
+}
+
+/// Parse a sourcemap loaded from `map_url`, fetching the maps of index-map sections that
+/// reference them by `url` (resolved against `map_url`) before flattening.
+pub async fn load_sourcemap(json: &str, map_url: &Url) -> Result<SourceMap, SourcemapError> {
+    load_sourcemap_with(default_fetcher().as_ref(), json, map_url).await
+}
+
+/// [`load_sourcemap`], fetching section maps through `fetcher`.
+pub async fn load_sourcemap_with(fetcher: &dyn Fetcher, json: &str, map_url: &Url) -> Result<SourceMap, SourcemapError> {
+    load_sourcemap_with_policy(fetcher, json, map_url, &RetryPolicy::default()).await
+}
+
+/// [`load_sourcemap_with`], trying section maps again as `policy` allows.
+pub async fn load_sourcemap_with_policy(
+    fetcher: &dyn Fetcher,
+    json: &str,
+    map_url: &Url,
+    policy: &RetryPolicy,
+) -> Result<SourceMap, SourcemapError> {
+    let decoded = parse_decoded_sourcemap(json)?;
+    flatten_decoded(resolve_sections(fetcher, policy, decoded, map_url, 0).await?)
+}
+
+fn resolve_sections<'a>(
+    fetcher: &'a dyn Fetcher,
+    policy: &'a RetryPolicy,
+    map: DecodedMap,
+    map_url: &'a Url,
+    depth: usize,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<DecodedMap, SourcemapError>> + Send + 'a>> {
+    Box::pin(async move {
+        let DecodedMap::Index(mut idx) = map else { return Ok(map) };
+        if depth >= MAX_INDEX_DEPTH {
+            return Err(SourcemapError::Parse("index sourcemap nested too deeply".into()));
+        }
+        for i in 0..idx.get_section_count() {
+            let section = idx.get_section_mut(i).expect("index within section count");
+            let resolved = match section.get_sourcemap() {
+                Some(nested @ DecodedMap::Index(_)) => resolve_sections(fetcher, policy, nested.clone(), map_url, depth + 1).await?,
+                Some(_) => continue,
+                None => {
+                    let Some(raw) = section.get_url() else { continue };
+                    let url = map_url
+                        .join(raw)
+                        .map_err(|e| SourcemapError::Parse(format!("invalid section url {raw}: {e}")))?;
+                    if !may_reference(map_url, &url) {
+                        return Err(SourcemapError::Parse(format!("refusing to load local section map {url} of a non-local sourcemap")));
+                    }
+                    let text = fetcher.fetch_with_policy(&url, policy)
+                        .await
+                        .map_err(|e| SourcemapError::Parse(format!("failed to load section map {url}: {e}")))?
+                        .body;
+                    resolve_sections(fetcher, policy, parse_decoded_sourcemap(&text)?, &url, depth + 1).await?
+                }
+            };
+            section.set_sourcemap(Some(resolved));
+        }
+        Ok(DecodedMap::Index(idx))
+    })
 }
 
 /// Convenience helper: return the list of original source paths contained in the map.
@@ -31,7 +115,7 @@ pub fn reconstruct_sources_with_swc(sm: &SourceMap, generated_js: &str) -> Resul
     let source_count = sm.get_source_count();
     let mut any_sources_content = false;
     for i in 0..source_count {
-        if let Some(content) = sm.get_source_contents(i as u32) {
+        if let Some(content) = sm.get_source_contents(i) {
             let path = sm.get_source(i).unwrap_or("");
             if !path.is_empty() {
                 fs::write(path, content).map_err(|e| SourcemapError::Parse(format!("Failed to write {}: {}", path, e)))?;
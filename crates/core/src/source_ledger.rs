@@ -0,0 +1,231 @@
+//! Deduplicating writes of reconstructed sources across sourcemaps.
+//!
+//! Chunks of the same build usually share sources (`node_modules/react/...`, `src/utils.ts`), and
+//! maps from different builds or chunks may disagree on their contents. A [`SourceLedger`] is shared
+//! by every save of a run: it writes each distinct content of a path once, resolves conflicting
+//! contents with a [`ConflictPolicy`], and records which chunks contributed every version.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+use crate::sanitize::write_in_tree;
+
+/// File name of the ledger written by [`SourceLedger::write_json`] at the output root.
+pub const LEDGER_FILE: &str = "resourcer-sources.json";
+
+/// What to do when a source path is written again with different contents.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the contents that were written first.
+    KeepFirst,
+    /// Keep the largest contents seen so far.
+    KeepLargest,
+    /// Keep the first contents at the path and write every other version next to it as
+    /// `name.conflict-<hash>.ext`.
+    #[default]
+    WriteVariants,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep-first" | "first" => Ok(Self::KeepFirst),
+            "keep-largest" | "largest" => Ok(Self::KeepLargest),
+            "variants" | "write-variants" => Ok(Self::WriteVariants),
+            other => Err(format!("unknown conflict policy '{other}' (expected keep-first, keep-largest or variants)")),
+        }
+    }
+}
+
+/// One distinct content seen for a source path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceVersion {
+    /// Hex SHA-256 of the contents.
+    pub hash: String,
+    pub size: usize,
+    /// Chunks (asset URLs or paths) whose sourcemaps contained this version.
+    pub contributors: Vec<String>,
+    /// Where this version is on disk, relative to the output root; `None` if it was discarded
+    /// (or replaced) under the conflict policy.
+    #[serde(serialize_with = "serialize_opt_slashed")]
+    pub written_to: Option<PathBuf>,
+}
+
+/// Every version seen for one source path, in the order first seen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceRecord {
+    /// Path relative to the output root.
+    #[serde(serialize_with = "serialize_slashed")]
+    pub path: PathBuf,
+    pub versions: Vec<SourceVersion>,
+}
+
+impl SourceRecord {
+    /// Whether more than one distinct content was seen.
+    pub fn is_conflict(&self) -> bool {
+        self.versions.len() > 1
+    }
+}
+
+/// Result of [`SourceLedger::write_source`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteAction {
+    /// First time this path was written.
+    Written,
+    /// Identical contents were already written; nothing was touched.
+    Duplicate,
+    /// Conflicting contents replaced the file (`KeepLargest`).
+    Replaced,
+    /// Conflicting contents were discarded (`KeepFirst`, or smaller under `KeepLargest`).
+    Discarded,
+    /// Conflicting contents were written to this variant path (`WriteVariants`).
+    Variant(PathBuf),
+}
+
+/// Run-wide record of written sources; see the module docs.
+#[derive(Debug, Default)]
+pub struct SourceLedger {
+    policy: ConflictPolicy,
+    records: Mutex<BTreeMap<PathBuf, SourceRecord>>,
+}
+
+impl SourceLedger {
+    pub fn new(policy: ConflictPolicy) -> Self {
+        Self { policy, records: Mutex::default() }
+    }
+
+    pub fn policy(&self) -> ConflictPolicy {
+        self.policy
+    }
+
+    /// Write `contents` for the source at `path` (under `out_root`), contributed by the chunk
+    /// `contributor`, applying the conflict policy.
+    pub fn write_source(&self, out_root: &Path, path: &Path, contents: &str, contributor: &str) -> io::Result<WriteAction> {
+        let rel = path.strip_prefix(out_root).unwrap_or(path).to_path_buf();
+        let hash = content_hash(contents.as_bytes());
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+
+        let Some(record) = records.get_mut(&rel) else {
+            write_in_tree(out_root, path, contents)?;
+            let version = new_version(hash, contents, contributor, Some(rel.clone()));
+            records.insert(rel.clone(), SourceRecord { path: rel, versions: vec![version] });
+            return Ok(WriteAction::Written);
+        };
+
+        if let Some(v) = record.versions.iter_mut().find(|v| v.hash == hash) {
+            if !v.contributors.iter().any(|c| c == contributor) {
+                v.contributors.push(contributor.to_string());
+            }
+            return Ok(WriteAction::Duplicate);
+        }
+
+        let current = record.versions.iter_mut().find(|v| v.written_to.as_deref() == Some(rel.as_path()));
+        let (action, written_to) = match self.policy {
+            ConflictPolicy::KeepFirst => (WriteAction::Discarded, None),
+            ConflictPolicy::KeepLargest => match current {
+                Some(cur) if cur.size >= contents.len() => (WriteAction::Discarded, None),
+                cur => {
+                    write_in_tree(out_root, path, contents)?;
+                    if let Some(cur) = cur {
+                        cur.written_to = None;
+                    }
+                    (WriteAction::Replaced, Some(rel.clone()))
+                }
+            },
+            ConflictPolicy::WriteVariants => {
+                let variant = variant_path(&rel, &hash);
+                write_in_tree(out_root, &out_root.join(&variant), contents)?;
+                (WriteAction::Variant(variant.clone()), Some(variant))
+            }
+        };
+        record.versions.push(new_version(hash, contents, contributor, written_to));
+        Ok(action)
+    }
+
+    /// Snapshot of all records, ordered by path.
+    pub fn records(&self) -> Vec<SourceRecord> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect()
+    }
+
+    /// Records of paths that were seen with more than one content.
+    pub fn conflicts(&self) -> Vec<SourceRecord> {
+        self.records().into_iter().filter(SourceRecord::is_conflict).collect()
+    }
+
+    /// The records as JSON: `[{ "path", "versions": [{ "hash", "size", "contributors", "written_to" }] }]`.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self.records()).expect("ledger records serialize to JSON")
+    }
+
+    /// Write [`Self::to_json`] to `out_root/`[`LEDGER_FILE`] and return its path.
+    pub fn write_json(&self, out_root: &Path) -> io::Result<PathBuf> {
+        let path = out_root.join(LEDGER_FILE);
+        let json = serde_json::to_string_pretty(&self.records()).map_err(io::Error::other)?;
+        write_in_tree(out_root, &path, json)?;
+        Ok(path)
+    }
+
+    /// Add the records of the ledger an earlier run left at `out_root/`[`LEDGER_FILE`], so a resumed
+    /// run applies the conflict policy against what that run wrote. Records whose files changed on
+    /// disk since are left out. Returns how many records were restored (none without a ledger).
+    pub fn load_json(&self, out_root: &Path) -> io::Result<usize> {
+        let data = match std::fs::read(out_root.join(LEDGER_FILE)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+        let loaded: Vec<SourceRecord> = serde_json::from_slice(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        let mut restored = 0;
+        for SourceRecord { path, versions } in loaded {
+            let intact = versions.iter().all(|v| {
+                v.written_to.as_ref().is_none_or(|w| std::fs::read(out_root.join(w)).is_ok_and(|data| content_hash(&data) == v.hash))
+            });
+            if intact && !records.contains_key(&path) {
+                records.insert(path.clone(), SourceRecord { path, versions });
+                restored += 1;
+            }
+        }
+        Ok(restored)
+    }
+}
+
+/// Paths are written with `/` separators on every platform.
+fn serialize_slashed<S: Serializer>(path: &Path, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&path.to_string_lossy().replace('\\', "/"))
+}
+
+fn serialize_opt_slashed<S: Serializer>(path: &Option<PathBuf>, s: S) -> Result<S::Ok, S::Error> {
+    match path {
+        Some(p) => serialize_slashed(p, s),
+        None => s.serialize_none(),
+    }
+}
+
+fn new_version(hash: String, contents: &str, contributor: &str, written_to: Option<PathBuf>) -> SourceVersion {
+    SourceVersion { hash, size: contents.len(), contributors: vec![contributor.to_string()], written_to }
+}
+
+/// Hex SHA-256 of `data`.
+pub fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// `src/utils.ts` + hash -> `src/utils.conflict-<first 8 hex>.ts`.
+fn variant_path(rel: &Path, hash: &str) -> PathBuf {
+    let tag = format!("conflict-{}", &hash[..8]);
+    let name = match (rel.file_stem(), rel.extension()) {
+        (Some(stem), Some(ext)) => format!("{}.{}.{}", stem.to_string_lossy(), tag, ext.to_string_lossy()),
+        _ => format!("{}.{}", rel.file_name().map(|n| n.to_string_lossy()).unwrap_or_default(), tag),
+    };
+    rel.with_file_name(name)
+}
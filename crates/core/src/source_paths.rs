@@ -0,0 +1,268 @@
+//! Mapping sourcemap `sources` URLs to paths in the reconstructed tree.
+//!
+//! Bundlers name sources very differently: `webpack://app/./src/a.js`, `turbopack://[project]/src/a.tsx`,
+//! `ng://`, `rollup://`, absolute build-machine paths like `file:///Users/alice/proj/src/x.ts`,
+//! Rollup/Vite virtual modules (`\0commonjsHelpers.js`, `vite/preload-helper`) and loader query
+//! strings (`App.vue?3a1b`). A [`SourceNormalizer`] runs its [`SourceUrlRule`]s in order; the first
+//! rule that recognises a source splits it into an optional namespace and a path, and the
+//! normalizer then applies the per-run [`SourcePathOptions`]:
+//!
+//! * the namespace becomes a top-level folder (`webpack://app/./src/a.js` -> `app/src/a.js`)
+//! * absolute paths are collapsed to the project root (`/Users/alice/proj/src/x.ts` -> `src/x.ts`)
+//! * query strings become distinct files (`src/App.vue?3a1b` -> `src/App.3a1b.vue`)
+//!
+//! Further rules can be added with [`SourceNormalizer::register`].
+
+use sha2::{Digest, Sha256};
+
+/// A source URL split by a [`SourceUrlRule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappedSource {
+    /// Top-level folder for the source (e.g. the webpack namespace), if any.
+    pub namespace: Option<String>,
+    /// Path within the namespace; may still carry a query string.
+    pub path: String,
+    /// Whether `path` is an absolute path on the build machine.
+    pub absolute: bool,
+}
+
+/// Recognises one family of source URLs.
+pub trait SourceUrlRule: Send + Sync {
+    /// Stable identifier, e.g. `"scheme"`.
+    fn name(&self) -> &'static str;
+
+    /// Split `source` into namespace and path, or `None` if this rule does not apply.
+    fn apply(&self, source: &str) -> Option<MappedSource>;
+}
+
+/// Per-run options for [`SourceNormalizer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourcePathOptions {
+    /// Put namespaces (`webpack://<ns>/`, HTTP hosts, virtual modules) in top-level folders.
+    pub namespace_dirs: bool,
+    /// Keep sources that differ only by query string apart (`App.3a1b.vue`) instead of stripping
+    /// the query and letting them overwrite each other.
+    pub query_variants: bool,
+    /// Project root on the build machine. Absolute sources under it are made relative to it;
+    /// other absolute sources are cut at the first well-known project folder (`src`,
+    /// `node_modules`, ...).
+    pub project_root: Option<String>,
+}
+
+impl Default for SourcePathOptions {
+    fn default() -> Self {
+        Self { namespace_dirs: true, query_variants: true, project_root: None }
+    }
+}
+
+/// Folders that usually sit directly below a project root.
+const PROJECT_DIRS: &[&str] = &["src", "node_modules", "app", "pages", "packages", "lib", "components"];
+
+/// Folder used for bundler-generated virtual modules.
+const VIRTUAL_DIR: &str = "_virtual";
+
+/// `<scheme>://<namespace>/<path>` for bundler pseudo-protocols (`webpack`, `turbopack`, `ng`,
+/// `rollup`, `webpack-internal`, ...).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SchemeRule;
+
+impl SourceUrlRule for SchemeRule {
+    fn name(&self) -> &'static str {
+        "scheme"
+    }
+
+    fn apply(&self, source: &str) -> Option<MappedSource> {
+        let (scheme, rest) = source.split_once("://")?;
+        let valid_scheme = !scheme.is_empty()
+            && scheme.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'));
+        if !valid_scheme || matches!(scheme, "file" | "http" | "https") {
+            return None;
+        }
+        let (ns, path) = rest.split_once('/').unwrap_or(("", rest));
+        let namespace = match ns.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+            // Turbopack: `[project]` is the project root, `[turbopack]` etc. are internal.
+            Some("project") => None,
+            Some(inner) => Some(inner.to_string()),
+            None if ns.is_empty() || ns == "." => None,
+            None => Some(ns.to_string()),
+        };
+        Some(MappedSource { namespace, path: path.to_string(), absolute: false })
+    }
+}
+
+/// `file:///abs/path` URLs.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileUrlRule;
+
+impl SourceUrlRule for FileUrlRule {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn apply(&self, source: &str) -> Option<MappedSource> {
+        let rest = source.strip_prefix("file://")?;
+        let path = percent_encoding::percent_decode_str(rest).decode_utf8_lossy().into_owned();
+        Some(MappedSource { namespace: None, path, absolute: true })
+    }
+}
+
+/// `http(s)://host/path` sources; the host becomes the namespace.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HttpRule;
+
+impl SourceUrlRule for HttpRule {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    fn apply(&self, source: &str) -> Option<MappedSource> {
+        let rest = source.strip_prefix("https://").or_else(|| source.strip_prefix("http://"))?;
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        Some(MappedSource { namespace: Some(host.to_string()), path: path.to_string(), absolute: false })
+    }
+}
+
+/// Rollup/Vite virtual modules: `\0id` (also `\u0000`-escaped) and Vite's `vite/` helpers.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VirtualModuleRule;
+
+impl SourceUrlRule for VirtualModuleRule {
+    fn name(&self) -> &'static str {
+        "virtual"
+    }
+
+    fn apply(&self, source: &str) -> Option<MappedSource> {
+        let path = source
+            .strip_prefix('\0')
+            .or_else(|| source.strip_prefix("\\0"))
+            .or_else(|| source.strip_prefix("\\u0000"))
+            .map(str::to_string)
+            .or_else(|| source.starts_with("vite/").then(|| source.to_string()))?;
+        Some(MappedSource { namespace: Some(VIRTUAL_DIR.to_string()), path, absolute: false })
+    }
+}
+
+/// Ordered rules plus options; see the module docs.
+pub struct SourceNormalizer {
+    rules: Vec<Box<dyn SourceUrlRule>>,
+    options: SourcePathOptions,
+}
+
+impl SourceNormalizer {
+    /// A normalizer without rules: every source is treated as a plain path.
+    pub fn empty(options: SourcePathOptions) -> Self {
+        Self { rules: Vec::new(), options }
+    }
+
+    /// A normalizer with every built-in rule.
+    pub fn with_builtin(options: SourcePathOptions) -> Self {
+        let mut n = Self::empty(options);
+        n.register(VirtualModuleRule).register(FileUrlRule).register(HttpRule).register(SchemeRule);
+        n
+    }
+
+    /// Add a rule after the existing ones. Rules registered earlier take precedence, so on a
+    /// [`Self::with_builtin`] normalizer use [`Self::register_first`] to override built-in rules.
+    pub fn register<R: SourceUrlRule + 'static>(&mut self, rule: R) -> &mut Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Add a rule ahead of every existing one, including the built-in rules.
+    pub fn register_first<R: SourceUrlRule + 'static>(&mut self, rule: R) -> &mut Self {
+        self.rules.insert(0, Box::new(rule));
+        self
+    }
+
+    pub fn options(&self) -> &SourcePathOptions {
+        &self.options
+    }
+
+    /// Map a source URL to a relative path string (not yet sanitized; see
+    /// [`crate::sanitize_relative_path`]). The second value is `true` when the path was relative
+    /// to begin with, i.e. a `sourceRoot` applies to it.
+    pub fn normalize(&self, source: &str) -> (String, bool) {
+        let mapped = self.rules.iter().find_map(|r| r.apply(source)).unwrap_or_else(|| MappedSource {
+            namespace: None,
+            path: source.to_string(),
+            absolute: is_absolute(source),
+        });
+
+        let (path, query) = split_query(&mapped.path);
+        let mut path = path.trim_start_matches("./").to_string();
+        if mapped.absolute {
+            path = self.collapse_absolute(&path);
+        }
+        if let (Some(q), true) = (query, self.options.query_variants) {
+            path = with_variant(&path, q);
+        }
+        if let (Some(ns), true) = (mapped.namespace, self.options.namespace_dirs) {
+            path = format!("{}/{}", ns, path);
+        }
+        (path, !mapped.absolute)
+    }
+
+    fn collapse_absolute(&self, path: &str) -> String {
+        let path = path.replace('\\', "/");
+        if let Some(root) = self.options.project_root.as_deref() {
+            let root = root.replace('\\', "/");
+            if let Some(rest) = path.strip_prefix(root.trim_end_matches('/')).and_then(|r| r.strip_prefix('/')) {
+                return rest.to_string();
+            }
+        }
+        let comps: Vec<&str> = path.split('/').collect();
+        match comps.iter().position(|c| PROJECT_DIRS.contains(c)) {
+            Some(i) => comps[i..].join("/"),
+            None => path,
+        }
+    }
+}
+
+impl std::fmt::Debug for SourceNormalizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SourceNormalizer")
+            .field("rules", &self.rules.iter().map(|r| r.name()).collect::<Vec<_>>())
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl Default for SourceNormalizer {
+    fn default() -> Self {
+        Self::with_builtin(SourcePathOptions::default())
+    }
+}
+
+fn is_absolute(path: &str) -> bool {
+    let b = path.as_bytes();
+    let drive = b.len() >= 3 && b[0].is_ascii_alphabetic() && b[1] == b':' && matches!(b[2], b'/' | b'\\');
+    path.starts_with('/') || path.starts_with('\\') || drive
+}
+
+/// Split off `?query` / `#fragment`; an empty query counts as none.
+fn split_query(path: &str) -> (&str, Option<&str>) {
+    match path.find(['?', '#']) {
+        Some(i) => {
+            let q = &path[i + 1..];
+            (&path[..i], (!q.is_empty()).then_some(q))
+        }
+        None => (path, None),
+    }
+}
+
+/// `src/App.vue` + `3a1b` -> `src/App.3a1b.vue`. Long or odd queries (`vue&type=style&index=0`)
+/// are replaced by a short hash.
+fn with_variant(path: &str, query: &str) -> String {
+    let tag = if query.len() <= 12 && query.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        query.to_string()
+    } else {
+        let digest = Sha256::digest(query.as_bytes());
+        digest[..4].iter().map(|b| format!("{b:02x}")).collect()
+    };
+    let (dir, file) = path.rsplit_once('/').map_or(("", path), |(d, f)| (d, f));
+    let file = match file.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{stem}.{tag}.{ext}"),
+        _ => format!("{file}.{tag}"),
+    };
+    if dir.is_empty() { file } else { format!("{dir}/{file}") }
+}
@@ -1,5 +1,6 @@
 use regex::Regex;
 use once_cell::sync::Lazy;
+use reqwest::header::HeaderMap;
 use std::collections::HashSet;
 use url::Url;
 
@@ -13,7 +14,12 @@ pub fn extract_sourcemap_urls(js: &str) -> Vec<String> {
     let mut urls = Vec::new();
     for caps in SOURCE_MAP_RE.captures_iter(js) {
         if let Some(m) = caps.get(1).or_else(|| caps.get(2)) {
-            urls.push(m.as_str().trim().to_string());
+            let mut url = m.as_str().trim();
+            // `eval-source-map` puts the comment inside a string literal: `...base64,eyJ2\n//# sourceURL=..")`
+            if url.get(..5).is_some_and(|p| p.eq_ignore_ascii_case("data:")) {
+                url = url.split(['\\', '"', '\'']).next().unwrap_or(url);
+            }
+            urls.push(url.to_string());
         }
     }
     urls
@@ -46,4 +52,81 @@ pub fn validate_sourcemap_urls(base: &Url, raw: impl IntoIterator<Item = String>
 pub fn find_sourcemap_urls(base: &Url, js: &str) -> Vec<Url> {
     let raw = extract_sourcemap_urls(js);
     validate_sourcemap_urls(base, raw)
-} 
\ No newline at end of file
+}
+
+/// Sourcemap URLs advertised by the `SourceMap` or legacy `X-SourceMap` response headers,
+/// resolved against `base` (the asset URL).
+pub fn sourcemap_urls_from_headers(base: &Url, headers: &HeaderMap) -> Vec<Url> {
+    let raw = ["sourcemap", "x-sourcemap"]
+        .iter()
+        .flat_map(|name| headers.get_all(*name))
+        .filter_map(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    validate_sourcemap_urls(base, raw)
+}
+
+/// [`find_sourcemap_urls`] plus the URLs from the response headers. Header URLs come first, as
+/// browsers prefer them over the comment; duplicates are removed.
+pub fn find_sourcemap_urls_with_headers(base: &Url, js: &str, headers: &HeaderMap) -> Vec<Url> {
+    let mut urls = sourcemap_urls_from_headers(base, headers);
+    for u in find_sourcemap_urls(base, js) {
+        if !urls.contains(&u) {
+            urls.push(u);
+        }
+    }
+    urls
+}
+
+/// Trailing `.<hash>` / `-<hash>` segment of a file stem, as produced by `[contenthash]`.
+fn strip_hash(stem: &str) -> Option<&str> {
+    let idx = stem.rfind(['.', '-'])?;
+    let (head, hash) = (&stem[..idx], &stem[idx + 1..]);
+    let is_hex = hash.len() >= 6 && hash.bytes().all(|b| b.is_ascii_hexdigit());
+    let is_b64ish = hash.len() >= 8
+        && hash.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        && hash.bytes().any(|b| b.is_ascii_digit());
+    (!head.is_empty() && (is_hex || is_b64ish)).then_some(head)
+}
+
+/// Conventional sourcemap locations for an asset that does not declare one (webpack's
+/// `hidden-source-map`): `<file>.map`, `<stem>.map`, `<stem>.js.map`, `<stem>.mjs.map`, and the
+/// same names with a trailing content hash removed (`main.3f2a1b.js` -> `main.js.map`).
+pub fn sourcemap_probe_candidates(asset: &Url) -> Vec<Url> {
+    let mut base = asset.clone();
+    base.set_query(None);
+    base.set_fragment(None);
+    let Some(file) = base.path_segments().and_then(|mut s| s.next_back()).filter(|f| !f.is_empty()).map(str::to_string) else {
+        return Vec::new();
+    };
+    let (stem, ext) = file.rsplit_once('.').unwrap_or((&file, ""));
+
+    let mut names = vec![format!("{file}.map"), format!("{stem}.map"), format!("{stem}.js.map"), format!("{stem}.mjs.map")];
+    if let Some(unhashed) = strip_hash(stem) {
+        if !ext.is_empty() {
+            names.push(format!("{unhashed}.{ext}.map"));
+        }
+        names.push(format!("{unhashed}.map"));
+    }
+
+    let mut seen = HashSet::new();
+    names
+        .into_iter()
+        .filter(|n| seen.insert(n.clone()))
+        .filter_map(|n| base.join(&n).ok())
+        .collect()
+}
+
+/// Whether a probed map's `file` field names `asset` (or its unhashed form). Maps without a
+/// `file` field are accepted.
+pub fn sourcemap_file_matches(map_file: Option<&str>, asset: &Url) -> bool {
+    let Some(map_file) = map_file else { return true };
+    let name = |s: &str| s.split(['?', '#']).next().unwrap_or(s).rsplit('/').next().unwrap_or(s).to_string();
+    let declared = name(map_file);
+    let asset_name = asset.path_segments().and_then(|mut s| s.next_back()).unwrap_or("").to_string();
+    if declared == asset_name {
+        return true;
+    }
+    let (stem, ext) = asset_name.rsplit_once('.').unwrap_or((&asset_name, ""));
+    strip_hash(stem).is_some_and(|u| declared == format!("{u}.{ext}"))
+}
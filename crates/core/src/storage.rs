@@ -1,28 +1,110 @@
+//! Stored documents, keyed by URL.
+//!
+//! [`HtmlStorage`] backends keep a fetched document's bytes together with its content type,
+//! response headers and fetch time: [`MemoryStorage`] for tests and small crawls, [`FsStorage`]
+//! (one file pair per URL in a directory) and [`SqliteStorage`] (a single database file) for
+//! caches that survive across runs. A [`CachingFetcher`] puts any of them in front of another
+//! [`Fetcher`], so re-runs and resumed runs do not download the same URL twice, or, when
+//! revalidating, only download what changed according to the stored `ETag` / `Last-Modified`.
+
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use tokio::sync::RwLock;
 use url::Url;
-use thiserror::Error;
+
+use crate::source_ledger::content_hash;
+use crate::{FetchError, FetchResponse, Fetcher, Validators};
 
 #[derive(Error, Debug)]
 pub enum StorageError {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("invalid stored metadata: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
 }
 
 #[derive(Clone, Debug)]
 pub struct HtmlDocument {
     pub url: Url,
+    /// When the document was fetched.
     pub timestamp: DateTime<Utc>,
-    pub content: String,
+    pub content_type: Option<String>,
+    /// Response headers, in received order.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HtmlDocument {
+    /// A document fetched now, without headers.
+    pub fn new(url: Url, body: impl Into<Vec<u8>>) -> Self {
+        Self { url, timestamp: Utc::now(), content_type: None, headers: Vec::new(), body: body.into() }
+    }
+
+    /// `resp` as fetched now, stored under `url` (the requested URL, before redirects).
+    pub fn from_response(url: Url, resp: &FetchResponse) -> Self {
+        let headers = resp
+            .headers
+            .iter()
+            .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+        Self {
+            url,
+            timestamp: Utc::now(),
+            content_type: resp.header(CONTENT_TYPE.as_str()).map(str::to_string),
+            headers,
+            body: resp.body.clone().into_bytes(),
+        }
+    }
+
+    /// The stored `ETag` / `Last-Modified`.
+    pub fn validators(&self) -> Validators {
+        let get = |name: &str| self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.clone());
+        Validators { etag: get("etag"), last_modified: get("last-modified") }
+    }
+
+    /// The body as text, replacing invalid UTF-8.
+    pub fn text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+
+    /// Serve the document as a `200` response.
+    pub fn into_response(self) -> FetchResponse {
+        let mut headers = HeaderMap::new();
+        for (k, v) in &self.headers {
+            if let (Ok(k), Ok(v)) = (HeaderName::from_bytes(k.as_bytes()), HeaderValue::from_str(v)) {
+                headers.append(k, v);
+            }
+        }
+        let body = String::from_utf8(self.body).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+        FetchResponse { url: self.url, status: 200, headers, body }
+    }
 }
 
-#[async_trait::async_trait]
+#[async_trait]
 pub trait HtmlStorage: Send + Sync {
-    async fn save_html(&self, url: Url, html: String) -> Result<(), StorageError>;
+    /// Store `doc`, replacing whatever was stored under its URL.
+    async fn save(&self, doc: HtmlDocument) -> Result<(), StorageError>;
+
+    /// The document stored under `url`. Backends log and report a miss when it cannot be read.
     async fn get(&self, url: &Url) -> Option<HtmlDocument>;
+
+    /// Store an HTML page fetched now.
+    async fn save_html(&self, url: Url, html: String) -> Result<(), StorageError> {
+        let mut doc = HtmlDocument::new(url, html);
+        doc.content_type = Some("text/html".into());
+        self.save(doc).await
+    }
 }
 
 /// In-memory storage – useful for testing and small crawls.
@@ -37,19 +119,225 @@ impl MemoryStorage {
     }
 }
 
-#[async_trait::async_trait]
+#[async_trait]
 impl HtmlStorage for MemoryStorage {
-    async fn save_html(&self, url: Url, html: String) -> Result<(), StorageError> {
-        let doc = HtmlDocument {
+    async fn save(&self, doc: HtmlDocument) -> Result<(), StorageError> {
+        self.inner.write().await.insert(doc.url.as_str().to_owned(), doc);
+        Ok(())
+    }
+
+    async fn get(&self, url: &Url) -> Option<HtmlDocument> {
+        self.inner.read().await.get(url.as_str()).cloned()
+    }
+}
+
+/// Everything but the body, as stored next to it by [`FsStorage`].
+#[derive(Serialize, Deserialize)]
+struct StoredMeta {
+    url: String,
+    timestamp: DateTime<Utc>,
+    content_type: Option<String>,
+    headers: Vec<(String, String)>,
+}
+
+/// Stores each document in `dir` as `<sha256 of url>.body` plus a `<sha256 of url>.json`
+/// metadata file.
+#[derive(Debug, Clone)]
+pub struct FsStorage {
+    dir: PathBuf,
+}
+
+impl FsStorage {
+    /// Use `dir`, creating it if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, StorageError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn paths(&self, url: &Url) -> (PathBuf, PathBuf) {
+        let key = content_hash(url.as_str().as_bytes());
+        (self.dir.join(format!("{key}.json")), self.dir.join(format!("{key}.body")))
+    }
+
+    async fn read(&self, url: &Url) -> Result<Option<HtmlDocument>, StorageError> {
+        let (meta_path, body_path) = self.paths(url);
+        let meta = match tokio::fs::read(&meta_path).await {
+            Ok(m) => m,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let meta: StoredMeta = serde_json::from_slice(&meta)?;
+        let body = tokio::fs::read(&body_path).await?;
+        Ok(Some(HtmlDocument {
             url: url.clone(),
-            timestamp: Utc::now(),
-            content: html,
+            timestamp: meta.timestamp,
+            content_type: meta.content_type,
+            headers: meta.headers,
+            body,
+        }))
+    }
+}
+
+#[async_trait]
+impl HtmlStorage for FsStorage {
+    async fn save(&self, doc: HtmlDocument) -> Result<(), StorageError> {
+        let (meta_path, body_path) = self.paths(&doc.url);
+        let meta = StoredMeta {
+            url: doc.url.to_string(),
+            timestamp: doc.timestamp,
+            content_type: doc.content_type,
+            headers: doc.headers,
         };
-        self.inner.write().await.insert(url.as_str().to_owned(), doc);
+        // The metadata file marks the entry complete, so it is written last.
+        tokio::fs::write(&body_path, &doc.body).await?;
+        let tmp = meta_path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(&meta)?).await?;
+        tokio::fs::rename(&tmp, &meta_path).await?;
         Ok(())
     }
 
     async fn get(&self, url: &Url) -> Option<HtmlDocument> {
-        self.inner.read().await.get(url.as_str()).cloned()
+        self.read(url).await.unwrap_or_else(|e| {
+            log::warn!("could not read cached {} from {}: {}", url, self.dir.display(), e);
+            None
+        })
+    }
+}
+
+/// Stores documents in a single SQLite database file.
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Open (or create) the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// A database that lives only as long as the returned storage.
+    pub fn in_memory() -> Result<Self, StorageError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, StorageError> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS documents (
+                url TEXT PRIMARY KEY,
+                fetched_at TEXT NOT NULL,
+                content_type TEXT,
+                headers TEXT NOT NULL,
+                body BLOB NOT NULL
+            )",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn read(&self, url: &Url) -> Result<Option<HtmlDocument>, StorageError> {
+        let conn = self.conn.lock().expect("sqlite connection poisoned");
+        let row = conn
+            .query_row(
+                "SELECT fetched_at, content_type, headers, body FROM documents WHERE url = ?1",
+                params![url.as_str()],
+                |r| Ok((r.get::<_, String>(0)?, r.get(1)?, r.get::<_, String>(2)?, r.get(3)?)),
+            )
+            .optional()?;
+        let Some((fetched_at, content_type, headers, body)) = row else { return Ok(None) };
+        let timestamp = DateTime::parse_from_rfc3339(&fetched_at)
+            .map(|t| t.with_timezone(&Utc))
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+        Ok(Some(HtmlDocument { url: url.clone(), timestamp, content_type, headers: serde_json::from_str(&headers)?, body }))
+    }
+}
+
+// Statements are short, so they run inline rather than on a blocking thread.
+#[async_trait]
+impl HtmlStorage for SqliteStorage {
+    async fn save(&self, doc: HtmlDocument) -> Result<(), StorageError> {
+        let headers = serde_json::to_string(&doc.headers)?;
+        let conn = self.conn.lock().expect("sqlite connection poisoned");
+        conn.execute(
+            "INSERT OR REPLACE INTO documents (url, fetched_at, content_type, headers, body) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![doc.url.as_str(), doc.timestamp.to_rfc3339(), doc.content_type, headers, doc.body],
+        )?;
+        Ok(())
     }
-} 
\ No newline at end of file
+
+    async fn get(&self, url: &Url) -> Option<HtmlDocument> {
+        self.read(url).unwrap_or_else(|e| {
+            log::warn!("could not read cached {}: {}", url, e);
+            None
+        })
+    }
+}
+
+/// Serves documents stored in an [`HtmlStorage`] and stores every successful response of `inner`
+/// in it, keyed by the requested URL. Errors are never cached.
+///
+/// By default stored documents are served without asking `inner`. With
+/// [`CachingFetcher::revalidating`], each one is requested again with its validators: a `304 Not
+/// Modified` is answered with the stored body and status `304`, so callers can tell unchanged
+/// resources apart, and anything else replaces the stored copy. Documents stored without
+/// validators are downloaded again.
+pub struct CachingFetcher {
+    inner: Arc<dyn Fetcher>,
+    storage: Arc<dyn HtmlStorage>,
+    revalidate: bool,
+}
+
+impl CachingFetcher {
+    pub fn new(inner: Arc<dyn Fetcher>, storage: Arc<dyn HtmlStorage>) -> Self {
+        Self { inner, storage, revalidate: false }
+    }
+
+    /// Revalidate stored documents with conditional requests instead of trusting them.
+    pub fn revalidating(mut self) -> Self {
+        self.revalidate = true;
+        self
+    }
+
+    async fn store(&self, doc: HtmlDocument) {
+        let url = doc.url.clone();
+        if let Err(e) = self.storage.save(doc).await {
+            log::warn!("could not cache {}: {}", url, e);
+        }
+    }
+}
+
+#[async_trait]
+impl Fetcher for CachingFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        let cached = self.storage.get(url).await;
+        let resp = match cached {
+            Some(doc) if !self.revalidate => {
+                log::debug!("cache hit for {}", url);
+                return Ok(doc.into_response());
+            }
+            Some(mut doc) if !doc.validators().is_empty() => {
+                let resp = self.inner.fetch_conditional(url, &doc.validators()).await?;
+                if resp.is_not_modified() {
+                    log::debug!("{} not modified", url);
+                    doc.timestamp = Utc::now();
+                    self.store(doc.clone()).await;
+                    return Ok(FetchResponse { status: 304, ..doc.into_response() });
+                }
+                resp
+            }
+            _ => self.inner.fetch(url).await?,
+        };
+        self.store(HtmlDocument::from_response(url.clone(), &resp)).await;
+        Ok(resp)
+    }
+
+    async fn check(&self, url: &Url) -> Result<(), FetchError> {
+        if self.storage.get(url).await.is_some() {
+            return Ok(());
+        }
+        self.inner.check(url).await
+    }
+}
@@ -0,0 +1,218 @@
+//! Per-host politeness for requests to live sites.
+//!
+//! A [`Throttle`] keeps, for every host, a token bucket capping the request rate, a semaphore
+//! capping concurrent requests and a slow-down gap kept between the starts of its requests, which
+//! grows each time the host answers with `429 Too Many Requests` or `503 Service Unavailable` and
+//! shrinks again as requests succeed. Each request reserves the next free start time, so workers
+//! sharing a host are spaced out instead of waiting in parallel. A random delay can be added
+//! before each request so traffic does not arrive in lockstep.
+//! [`ThrottledFetcher`] applies a throttle to every request of another [`Fetcher`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use url::Url;
+
+use crate::{FetchError, FetchResponse, Fetcher, Validators};
+
+/// Gap kept after the first time a host asks to slow down; doubled on each further refusal.
+const SLOW_DOWN_START: Duration = Duration::from_millis(500);
+
+/// What a [`Throttle`] enforces. The default only slows down hosts that answer `429` or `503`.
+#[derive(Debug, Clone)]
+pub struct ThrottleConfig {
+    /// Requests per second to each host; `None` for no limit.
+    pub requests_per_second: Option<f64>,
+    /// Requests a host may receive back to back before `requests_per_second` applies.
+    pub burst: u32,
+    /// Requests in flight to each host at once; `None` for no limit.
+    pub per_host_concurrency: Option<usize>,
+    /// Upper bound of a random delay before each request; zero for none.
+    pub jitter: Duration,
+    /// Statuses that slow the host down; empty to never slow down.
+    pub slow_down_statuses: Vec<u16>,
+    /// Largest gap kept between requests to a slowed-down host.
+    pub max_slow_down: Duration,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: None,
+            burst: 1,
+            per_host_concurrency: None,
+            jitter: Duration::ZERO,
+            slow_down_statuses: vec![429, 503],
+            max_slow_down: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+#[derive(Debug)]
+struct HostState {
+    permits: Option<Arc<Semaphore>>,
+    bucket: Mutex<Bucket>,
+    slow_down: Mutex<Duration>,
+    /// Earliest start of the next request, reserved by the previous one.
+    next_allowed: Mutex<Instant>,
+}
+
+/// Shared rate, concurrency and slow-down state per host; see the module docs.
+#[derive(Debug)]
+pub struct Throttle {
+    config: ThrottleConfig,
+    hosts: Mutex<HashMap<String, Arc<HostState>>>,
+}
+
+/// Held while a request is in flight; releases the host's concurrency slot when dropped.
+pub struct ThrottlePermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Throttle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self { config, hosts: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> &ThrottleConfig {
+        &self.config
+    }
+
+    fn host(&self, url: &Url) -> Option<Arc<HostState>> {
+        let key = match url.scheme() {
+            "http" | "https" => format!("{}:{}", url.host_str()?, url.port_or_known_default()?),
+            _ => return None,
+        };
+        let mut hosts = self.hosts.lock().expect("throttle poisoned");
+        let state = hosts.entry(key).or_insert_with(|| {
+            Arc::new(HostState {
+                permits: self.config.per_host_concurrency.map(|n| Arc::new(Semaphore::new(n.max(1)))),
+                bucket: Mutex::new(Bucket { tokens: f64::from(self.config.burst.max(1)), refilled: Instant::now() }),
+                slow_down: Mutex::new(Duration::ZERO),
+                next_allowed: Mutex::new(Instant::now()),
+            })
+        });
+        Some(Arc::clone(state))
+    }
+
+    /// Wait until a request to `url` may be sent and hold the permit until its response arrives.
+    /// Only `http` and `https` URLs are throttled.
+    pub async fn acquire(&self, url: &Url) -> ThrottlePermit {
+        let Some(host) = self.host(url) else {
+            return ThrottlePermit { _permit: None };
+        };
+        let permit = match &host.permits {
+            Some(sem) => Some(Arc::clone(sem).acquire_owned().await.expect("throttle semaphore closed")),
+            None => None,
+        };
+        let mut wait = Duration::ZERO;
+        if let Some(rate) = self.config.requests_per_second.filter(|r| *r > 0.0) {
+            // Take a token now, going into debt if there is none, and wait until it is paid off.
+            let mut bucket = host.bucket.lock().expect("throttle poisoned");
+            let now = Instant::now();
+            let burst = f64::from(self.config.burst.max(1));
+            bucket.tokens = (bucket.tokens + now.duration_since(bucket.refilled).as_secs_f64() * rate).min(burst);
+            bucket.refilled = now;
+            bucket.tokens -= 1.0;
+            if bucket.tokens < 0.0 {
+                wait += Duration::from_secs_f64(-bucket.tokens / rate);
+            }
+        }
+        {
+            // Reserve a start time at least the slow-down gap after the previous request's.
+            let gap = *host.slow_down.lock().expect("throttle poisoned");
+            let mut next = host.next_allowed.lock().expect("throttle poisoned");
+            let now = Instant::now();
+            let start = (now + wait).max(*next);
+            *next = start + gap;
+            wait = start - now;
+        }
+        if !self.config.jitter.is_zero() {
+            wait += self.config.jitter.mul_f64(fastrand::f64());
+        }
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        ThrottlePermit { _permit: permit }
+    }
+
+    /// Report the status `url` answered with (`None` when no response arrived). A slow-down status
+    /// doubles the host's gap and holds back its next request by that much; anything else halves
+    /// the gap.
+    pub fn record(&self, url: &Url, status: Option<u16>) {
+        let Some(host) = self.host(url) else { return };
+        let mut delay = host.slow_down.lock().expect("throttle poisoned");
+        match status {
+            Some(s) if self.config.slow_down_statuses.contains(&s) => {
+                *delay = (*delay * 2).max(SLOW_DOWN_START).min(self.config.max_slow_down);
+                let mut next = host.next_allowed.lock().expect("throttle poisoned");
+                *next = (*next).max(Instant::now() + *delay);
+                log::warn!("{} answered {}; waiting {:?} between requests", url.host_str().unwrap_or_default(), s, *delay);
+            }
+            Some(_) if !delay.is_zero() => {
+                *delay /= 2;
+                if *delay < Duration::from_millis(10) {
+                    *delay = Duration::ZERO;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The slow-down gap currently kept between requests to `url`'s host.
+    pub fn slow_down(&self, url: &Url) -> Duration {
+        self.host(url).map_or(Duration::ZERO, |h| *h.slow_down.lock().expect("throttle poisoned"))
+    }
+}
+
+fn status_of(res: &Result<FetchResponse, FetchError>) -> Option<u16> {
+    match res {
+        Ok(resp) => Some(resp.status),
+        Err(e) => e.status(),
+    }
+}
+
+/// Sends every request of `inner` through a [`Throttle`]; several fetchers can share one.
+pub struct ThrottledFetcher {
+    inner: Arc<dyn Fetcher>,
+    throttle: Arc<Throttle>,
+}
+
+impl ThrottledFetcher {
+    pub fn new(inner: Arc<dyn Fetcher>, throttle: Arc<Throttle>) -> Self {
+        Self { inner, throttle }
+    }
+}
+
+#[async_trait]
+impl Fetcher for ThrottledFetcher {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        let _permit = self.throttle.acquire(url).await;
+        let res = self.inner.fetch(url).await;
+        self.throttle.record(url, status_of(&res));
+        res
+    }
+
+    async fn check(&self, url: &Url) -> Result<(), FetchError> {
+        let _permit = self.throttle.acquire(url).await;
+        let res = self.inner.check(url).await;
+        self.throttle.record(url, res.as_ref().err().map_or(Some(200), FetchError::status));
+        res
+    }
+
+    async fn fetch_conditional(&self, url: &Url, validators: &Validators) -> Result<FetchResponse, FetchError> {
+        let _permit = self.throttle.acquire(url).await;
+        let res = self.inner.fetch_conditional(url, validators).await;
+        self.throttle.record(url, status_of(&res));
+        res
+    }
+}
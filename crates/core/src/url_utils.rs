@@ -13,8 +13,10 @@ pub fn find_sourcemap_url_in_js(js: &str) -> Option<String> {
 /// reproduces the runtime's directory structure. Otherwise we fall back to the
 /// page origin.
 pub fn derive_base_from_runtime(runtime_url: &Url, prefix: &str) -> Url {
-    // Build origin (scheme + host) first
-    let mut origin = Url::parse(&format!("{}://{}", runtime_url.scheme(), runtime_url.host_str().unwrap_or(""))).unwrap();
+    // Build origin (scheme + host + port) first
+    let mut origin = runtime_url.clone();
+    origin.set_query(None);
+    origin.set_fragment(None);
     // Try to locate the prefix inside the runtime's path
     let path = runtime_url.path();
     if let Some(idx) = path.rfind(prefix) {
@@ -43,4 +45,9 @@ pub fn extract_script_urls(html: &str, base: &Url) -> Vec<Url> {
         }
     }
     script_urls
-} 
\ No newline at end of file
+} 
+/// Whether a resource loaded from `from` may lead the tool to load `to`. Only `file:` resources
+/// may reference `file:` URLs, so a remote page, capture or archive cannot read the local disk.
+pub fn may_reference(from: &Url, to: &Url) -> bool {
+    to.scheme() != "file" || from.scheme() == "file"
+}
@@ -0,0 +1,285 @@
+//! WARC crawl archives as a dump source.
+//!
+//! A [`WarcArchive`] indexes the `response` records of a WARC file (plain or `.warc.gz`) by target
+//! URL, undoing chunked transfer encoding and gzip/deflate content encoding of the archived HTTP
+//! payloads. `revisit` records of deduplicated crawls are resolved to the payload they refer to
+//! (by `WARC-Payload-Digest`, else `WARC-Refers-To-Target-URI`). Its [`ArchiveFetcher`] lets the
+//! whole dump pipeline run against the crawl as if the site were live, without touching the
+//! network.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use flate2::read::{DeflateDecoder, GzDecoder, MultiGzDecoder, ZlibDecoder};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_ENCODING, CONTENT_TYPE, TRANSFER_ENCODING};
+use url::Url;
+
+use crate::{ArchiveFetcher, FetchResponse};
+
+#[derive(thiserror::Error, Debug)]
+pub enum WarcError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("malformed WARC record: {0}")]
+    Format(String),
+}
+
+/// An archived HTTP response.
+#[derive(Debug, Clone)]
+pub struct WarcResponse {
+    /// `WARC-Target-URI` of the record.
+    pub url: Url,
+    pub status: u16,
+    pub headers: HeaderMap,
+    /// Decoded body; `None` when its content encoding is not supported.
+    pub body: Option<String>,
+}
+
+impl WarcResponse {
+    fn is_html(&self) -> bool {
+        self.headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.to_ascii_lowercase().contains("text/html"))
+    }
+}
+
+/// The `response` records of a WARC file, and resolved `revisit`s, in archive order.
+#[derive(Debug, Clone, Default)]
+pub struct WarcArchive {
+    responses: Vec<WarcResponse>,
+}
+
+impl WarcArchive {
+    /// Read a `.warc` or (multi-member) gzip-compressed `.warc.gz` file.
+    pub fn from_path(path: &Path) -> Result<Self, WarcError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let gzipped = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+        if gzipped {
+            Self::from_reader(BufReader::new(MultiGzDecoder::new(reader)))
+        } else {
+            Self::from_reader(reader)
+        }
+    }
+
+    /// Read uncompressed WARC records from `reader`. Records other than HTTP(S) `response`s and
+    /// `revisit`s are skipped, and so are revisits whose payload is not in the archive.
+    pub fn from_reader<R: BufRead>(mut reader: R) -> Result<Self, WarcError> {
+        let mut archive = Self::default();
+        let mut digests = HashMap::new();
+        let mut pending = Vec::new();
+        while let Some((headers, block)) = read_record(&mut reader)? {
+            let field = |name: &str| {
+                headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+            };
+            let parse_uri = |name: &str| field(name).and_then(|u| Url::parse(u.trim_matches(['<', '>'])).ok());
+            let kind = field("WARC-Type").unwrap_or_default().to_ascii_lowercase();
+            if kind != "response" && kind != "revisit" {
+                continue;
+            }
+            let Some(url) = parse_uri("WARC-Target-URI") else { continue };
+            if !matches!(url.scheme(), "http" | "https") {
+                continue;
+            }
+            let digest = field("WARC-Payload-Digest").map(str::to_string);
+            if kind == "revisit" {
+                // The block holds at most the HTTP head; the payload is the one referred to.
+                let status = parse_http_response(url.clone(), &block).map(|r| r.status);
+                let revisit = Revisit { url, status, digest, refers_to: parse_uri("WARC-Refers-To-Target-URI") };
+                if !archive.push_revisit(&revisit, &digests) {
+                    pending.push(revisit);
+                }
+            } else if let Some(resp) = parse_http_response(url, &block) {
+                if let Some(d) = digest {
+                    digests.insert(d, archive.responses.len());
+                }
+                archive.responses.push(resp);
+            }
+        }
+        // Revisits normally follow their original; resolve the others once everything is read.
+        pending.retain(|r| !archive.push_revisit(r, &digests));
+        if !pending.is_empty() {
+            log::warn!("skipped {} revisit record(s) whose payload is not in the archive", pending.len());
+        }
+        Ok(archive)
+    }
+
+    /// Add the response `revisit` stands for; `false` if its original is not indexed yet.
+    fn push_revisit(&mut self, revisit: &Revisit, digests: &HashMap<String, usize>) -> bool {
+        let by_digest = revisit.digest.as_ref().and_then(|d| digests.get(d)).copied();
+        let original = by_digest.or_else(|| {
+            let target = revisit.refers_to.as_ref()?;
+            self.responses.iter().rposition(|r| &r.url == target)
+        });
+        let Some(original) = original.map(|i| &self.responses[i]) else { return false };
+        let resp = WarcResponse {
+            url: revisit.url.clone(),
+            status: revisit.status.unwrap_or(original.status),
+            headers: original.headers.clone(),
+            body: original.body.clone(),
+        };
+        self.responses.push(resp);
+        true
+    }
+
+    pub fn responses(&self) -> &[WarcResponse] {
+        &self.responses
+    }
+
+    /// The first successfully archived HTML page.
+    pub fn page_url(&self) -> Option<Url> {
+        self.responses
+            .iter()
+            .find(|r| r.is_html() && (200..300).contains(&r.status))
+            .map(|r| r.url.clone())
+    }
+
+    /// Serve the archived responses; URLs the crawl did not capture fail with
+    /// [`crate::FetchError::NotRecorded`]. When a URL was captured more than once, the last
+    /// response wins.
+    pub fn into_fetcher(self) -> ArchiveFetcher {
+        let mut fetcher = ArchiveFetcher::new(None);
+        for r in self.responses {
+            match r.body {
+                Some(body) if (200..300).contains(&r.status) => {
+                    fetcher.insert(FetchResponse { url: r.url, status: r.status, headers: r.headers, body });
+                }
+                // Redirects are not followed inside the archive; report them like any other status.
+                _ if r.status >= 300 && r.status != 304 => {
+                    fetcher.insert_error(&r.url, r.status);
+                }
+                _ => {}
+            }
+        }
+        fetcher
+    }
+}
+
+/// A `revisit` record: `url` answered with the payload of an earlier capture.
+struct Revisit {
+    url: Url,
+    status: Option<u16>,
+    digest: Option<String>,
+    refers_to: Option<Url>,
+}
+
+/// WARC header fields and content block of one record.
+type Record = (Vec<(String, String)>, Vec<u8>);
+
+/// Next record, or `None` at end of input.
+fn read_record<R: BufRead>(reader: &mut R) -> Result<Option<Record>, WarcError> {
+    // Skip the blank lines separating records.
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            break;
+        }
+    }
+    if !line.starts_with("WARC/") {
+        return Err(WarcError::Format(format!("expected WARC version line, got {:?}", line.trim_end())));
+    }
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((k, v)) = line.split_once(':') {
+            headers.push((k.trim().to_string(), v.trim().to_string()));
+        }
+    }
+
+    let length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("Content-Length"))
+        .and_then(|(_, v)| v.parse::<u64>().ok())
+        .ok_or_else(|| WarcError::Format("record without Content-Length".into()))?;
+    let mut block = Vec::new();
+    reader.take(length).read_to_end(&mut block)?;
+    if (block.len() as u64) < length {
+        return Err(WarcError::Format("truncated record".into()));
+    }
+    Ok(Some((headers, block)))
+}
+
+/// Split an archived HTTP response into status, headers and decoded body.
+fn parse_http_response(url: Url, block: &[u8]) -> Option<WarcResponse> {
+    let (head_len, sep_len) = find(block, b"\r\n\r\n").map(|i| (i, 4)).or_else(|| find(block, b"\n\n").map(|i| (i, 2)))?;
+    let head = String::from_utf8_lossy(&block[..head_len]);
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+
+    let mut headers = HeaderMap::new();
+    for line in lines {
+        if let Some((k, v)) = line.split_once(':') {
+            if let (Ok(k), Ok(v)) = (HeaderName::from_bytes(k.trim().as_bytes()), HeaderValue::from_str(v.trim())) {
+                headers.append(k, v);
+            }
+        }
+    }
+
+    let raw = &block[head_len + sep_len..];
+    let chunked = header_has(&headers, TRANSFER_ENCODING, "chunked");
+    let payload = if chunked { dechunk(raw) } else { Some(raw.to_vec()) };
+    let body = payload.and_then(|p| decode_content(&headers, p)).map(|b| String::from_utf8_lossy(&b).into_owned());
+
+    // The body is stored decoded; these headers no longer describe it.
+    headers.remove(TRANSFER_ENCODING);
+    headers.remove(CONTENT_ENCODING);
+    Some(WarcResponse { url, status, headers, body })
+}
+
+fn header_has(headers: &HeaderMap, name: HeaderName, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.split(',').any(|t| t.trim().eq_ignore_ascii_case(token)))
+}
+
+/// Undo `Content-Encoding`; `None` for encodings other than gzip and deflate.
+fn decode_content(headers: &HeaderMap, body: Vec<u8>) -> Option<Vec<u8>> {
+    let encoding = headers.get(CONTENT_ENCODING).and_then(|v| v.to_str().ok()).unwrap_or("").trim().to_ascii_lowercase();
+    let mut out = Vec::new();
+    match encoding.as_str() {
+        "" | "identity" => return Some(body),
+        "gzip" | "x-gzip" => GzDecoder::new(body.as_slice()).read_to_end(&mut out).ok()?,
+        // `deflate` is zlib-wrapped by the spec, but raw deflate is common in the wild.
+        "deflate" => match ZlibDecoder::new(body.as_slice()).read_to_end(&mut out) {
+            Ok(n) => n,
+            Err(_) => {
+                out.clear();
+                DeflateDecoder::new(body.as_slice()).read_to_end(&mut out).ok()?
+            }
+        },
+        _ => return None,
+    };
+    Some(out)
+}
+
+/// Decode a chunked transfer-encoded payload.
+fn dechunk(mut raw: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let eol = find(raw, b"\r\n")?;
+        let size_str = std::str::from_utf8(&raw[..eol]).ok()?;
+        let size = usize::from_str_radix(size_str.split(';').next()?.trim(), 16).ok()?;
+        raw = &raw[eol + 2..];
+        if size == 0 {
+            return Some(out);
+        }
+        out.extend_from_slice(raw.get(..size)?);
+        raw = raw.get(size + 2..)?;
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
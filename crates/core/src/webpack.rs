@@ -1,12 +1,15 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 use url::Url;
-use reqwest::Client;
 use futures::stream::{FuturesUnordered, StreamExt};
 use swc_ecma_parser::{Parser, StringInput, Syntax};
 use swc_common::{sync::Lrc, SourceMap, FileName};
 use std::collections::HashSet;
 
+use crate::fetcher::{default_fetcher, Fetcher};
+use crate::retry::RetryPolicy;
+use crate::webpack_runtime::{analyze_webpack_runtime, ChunkUrlExpr};
+
 /// Represents the discovered pattern that Webpack uses to construct chunk URLs.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ChunkFilenameTemplate {
@@ -14,45 +17,27 @@ pub struct ChunkFilenameTemplate {
     pub suffix: String,
 }
 
-// Regexes for various helper forms; tried in order until one matches.
-static CHUNK_URL_FN_RE: Lazy<Regex> = Lazy::new(|| {
-    // Classic anonymous function form
-    Regex::new(r#"__webpack_require__\.u\s*=\s*function[^\{]*\{[^}]*?return\s+"([^"]*)"\s*\+\s*[^+]+\+\s*"([^"]*)";"#).unwrap()
-});
-
-static CHUNK_URL_ARROW_RE: Lazy<Regex> = Lazy::new(|| {
-    // Arrow function: __webpack_require__.u = (chunkId) => "prefix" + chunkId + "suffix";
-    Regex::new(r#"__webpack_require__\.u\s*=\s*\([^)]*\)\s*=>\s*"([^"]*)"\s*\+\s*[^+]+\+\s*"([^"]*)";"#).unwrap()
-});
-
-static CHUNK_URL_TMPL_RE: Lazy<Regex> = Lazy::new(|| {
-    // Template literal form: return `prefix${chunkId}suffix`;
-    Regex::new(r#"__webpack_require__\.u\s*=\s*function[^\{]*\{[^}]*?return\s+`([^`]*?)\$\{[^}]+}([^`]*?)`;"#).unwrap()
-});
-
-// Pattern for public path assignment.
-static PUBLIC_PATH_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r#"__webpack_require__\.p\s*=\s*"([^"]*)";"#).unwrap()
-});
-
 /// Infer prefix & suffix used to build chunk URLs.
+///
+/// Only succeeds when the runtime's `.u` function is a plain `prefix + chunkId + suffix`; runtimes
+/// with per-chunk lookups are described by [`extract_chunk_maps`] or [`analyze_webpack_runtime`].
 pub fn infer_chunk_filename_template(js: &str) -> Option<ChunkFilenameTemplate> {
-    for re in [&*CHUNK_URL_FN_RE, &*CHUNK_URL_ARROW_RE, &*CHUNK_URL_TMPL_RE] {
-        if let Some(caps) = re.captures(js) {
-            return Some(ChunkFilenameTemplate {
-                prefix: caps.get(1).unwrap().as_str().to_string(),
-                suffix: caps.get(2).unwrap().as_str().to_string(),
-            });
-        }
-    }
-    None
+    let parts = match analyze_webpack_runtime(js)?.js_filename? {
+        ChunkUrlExpr::Concat(parts) => parts,
+        _ => return None,
+    };
+    let (prefix, suffix) = match parts.as_slice() {
+        [ChunkUrlExpr::Lit(p), ChunkUrlExpr::ChunkId, ChunkUrlExpr::Lit(s)] => (p.clone(), s.clone()),
+        [ChunkUrlExpr::ChunkId, ChunkUrlExpr::Lit(s)] => (String::new(), s.clone()),
+        [ChunkUrlExpr::Lit(p), ChunkUrlExpr::ChunkId] => (p.clone(), String::new()),
+        _ => return None,
+    };
+    Some(ChunkFilenameTemplate { prefix, suffix })
 }
 
 /// Extract configured public path, if present.
 pub fn extract_public_path(js: &str) -> Option<String> {
-    let caps = PUBLIC_PATH_RE.captures(js)?;
-    let path = caps.get(1)?.as_str();
-    if path.is_empty() { None } else { Some(path.to_string()) }
+    analyze_webpack_runtime(js)?.public_path.filter(|p| !p.is_empty())
 }
 
 /// Build a full chunk URL from components.
@@ -69,24 +54,21 @@ pub fn build_chunk_url(base: Option<&Url>, template: &ChunkFilenameTemplate, chu
 /// discovered chunk URLs are reachable. Returns the subset of URLs that responded with a 2xx
 /// status code.
 pub async fn validate_chunk_urls(urls: impl IntoIterator<Item = Url>) -> Vec<Url> {
-    let client = Client::builder().redirect(reqwest::redirect::Policy::limited(5)).build().unwrap();
-    let mut futs = FuturesUnordered::new();
-    for url in urls {
-        let c = client.clone();
-        futs.push(async move {
-            match c.head(url.clone()).send().await {
-                Ok(resp) if resp.status().is_success() => Some(url.clone()),
-                Ok(resp) if resp.status() == reqwest::StatusCode::METHOD_NOT_ALLOWED => {
-                    // fallback to GET
-                    match c.get(url.clone()).send().await {
-                        Ok(r) if r.status().is_success() => Some(url.clone()),
-                        _ => None,
-                    }
-                }
-                _ => None,
-            }
-        });
-    }
+    validate_chunk_urls_with(default_fetcher().as_ref(), urls).await
+}
+
+/// [`validate_chunk_urls`] through `fetcher` (see [`Fetcher::check`]), so checks use the run's
+/// client configuration and work for any scheme it handles.
+pub async fn validate_chunk_urls_with(fetcher: &dyn Fetcher, urls: impl IntoIterator<Item = Url>) -> Vec<Url> {
+    validate_chunk_urls_with_policy(fetcher, urls, &RetryPolicy::default()).await
+}
+
+/// [`validate_chunk_urls_with`], checking each URL again as long as `policy` allows.
+pub async fn validate_chunk_urls_with_policy(fetcher: &dyn Fetcher, urls: impl IntoIterator<Item = Url>, policy: &RetryPolicy) -> Vec<Url> {
+    let mut futs: FuturesUnordered<_> = urls
+        .into_iter()
+        .map(|url| async move { policy.retry(|| fetcher.check(&url)).await.is_ok().then_some(url) })
+        .collect();
     let mut good = Vec::new();
     while let Some(res) = futs.next().await {
         if let Some(u) = res { good.push(u) }
@@ -95,76 +77,39 @@ pub async fn validate_chunk_urls(urls: impl IntoIterator<Item = Url>) -> Vec<Url
 }
 
 /// Extract likely chunk IDs from webpack runtime code.
-/// Looks for patterns like `webpackChunk.push(["123", ...])` or array assignments.
-/// Returns unique strings that appear to be chunk identifiers (numbers or hashes).
+/// Collects the IDs pushed onto `webpackChunk*` / `webpackJsonp` arrays and every ID referenced by
+/// the chunk filename functions (lookup maps and `id === N` special cases). Runtimes SWC cannot
+/// parse (truncated or newer syntax) fall back to matching the usual minified patterns.
 pub fn extract_chunk_ids(js: &str) -> Vec<String> {
-    static CHUNK_PUSH_RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r#"webpackChunk(?:_\w+)?\.push\(\[\["?([\w-]+)"?,"#).unwrap()
-    });
+    analyze_webpack_runtime(js).map_or_else(|| extract_chunk_ids_by_pattern(js), |info| info.chunk_ids)
+}
 
-    static CHUNK_ARRAY_RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r#"var installedChunks = \{([\s\S]*?)\};"#).unwrap()
-    });
+/// `webpackChunk*.push([[id, ...`, `N === e ? "static/chunks/N-..."` and the two lookup maps of a
+/// Next.js `"static/chunks/" + ({..}[e] || e) + "." + {..}[e]` filename function.
+fn extract_chunk_ids_by_pattern(js: &str) -> Vec<String> {
+    static PUSH_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"webpackChunk(?:_\w+)?\.push\(\[\["?([\w-]+)"?,"#).unwrap());
+    static CASE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(\d+)\s*===\s*e\s*\?\s*"static/chunks/\d+-[^"]+\.js""#).unwrap());
+    static MAPS_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"static/chunks/"\+\(\{([^}]+)\}\[e\]\|\|e\)\+"\."\+\{([^}]+)\}\[e\]"#).unwrap());
+    static ENTRY_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(\d+):\s*"\w+"#).unwrap());
 
     let mut ids = Vec::new();
     let mut seen = HashSet::new();
-
-    // First pattern: push calls
-    for caps in CHUNK_PUSH_RE.captures_iter(js) {
-        if let Some(id) = caps.get(1) {
-            let s = id.as_str().to_string();
-            if seen.insert(s.clone()) {
-                ids.push(s);
-            }
-        }
-    }
-
-    // Second pattern: hardcoded cases like "7561 === e ? "static/chunks/7561-be856e985935a49b.js""
-    static HARDCODED_CASE_RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r#"(\d+)\s*===\s*e\s*\?\s*"static/chunks/(\d+)-[^"]+\.js""#).unwrap()
-    });
-
-    for caps in HARDCODED_CASE_RE.captures_iter(js) {
-        if let Some(id) = caps.get(1) {
-            let chunk_id = id.as_str().to_string();
-            if seen.insert(chunk_id.clone()) {
-                ids.push(chunk_id);
-            }
+    let mut found = |id: &str| {
+        if seen.insert(id.to_string()) {
+            ids.push(id.to_string());
         }
+    };
+    for caps in PUSH_RE.captures_iter(js).chain(CASE_RE.captures_iter(js)) {
+        found(&caps[1]);
     }
-
-    // Third pattern: object literal maps in complex expressions
-    // Look for both maps in the pattern: {1255: "7d0bf13e", ...}[e] || e) + "." + {43: "7fa619f5d693091a", ...}[e]
-    static COMPLEX_MAP_RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r#"static/chunks/"\+\({([^}]+)}\[e\]\|\|e\)\+"\."\+{([^}]+)}\[e\]"#).unwrap()
-    });
-
-    if let Some(caps) = COMPLEX_MAP_RE.captures(js) {
-        let map1_raw = caps.get(1).map_or("", |m| m.as_str());
-        let map2_raw = caps.get(2).map_or("", |m| m.as_str());
-
-        let entry_re = Regex::new(r#"(\d+):\s*"[\w]+"#).unwrap();
-
-        // Extract chunk IDs from both maps
-        for entry in entry_re.captures_iter(map1_raw) {
-            if let Some(id) = entry.get(1) {
-                let chunk_id = id.as_str().to_string();
-                if seen.insert(chunk_id.clone()) {
-                    ids.push(chunk_id);
-                }
-            }
-        }
-
-        for entry in entry_re.captures_iter(map2_raw) {
-            if let Some(id) = entry.get(1) {
-                let chunk_id = id.as_str().to_string();
-                if seen.insert(chunk_id.clone()) {
-                    ids.push(chunk_id);
-                }
+    if let Some(caps) = MAPS_RE.captures(js) {
+        for map in [&caps[1], &caps[2]] {
+            for entry in ENTRY_RE.captures_iter(map) {
+                found(&entry[1]);
             }
         }
     }
-
     ids
 }
 
@@ -207,41 +152,23 @@ pub struct ChunkMapInfo {
     pub map_second: std::collections::HashMap<String, String>,
 }
 
-/// Try to extract chunk map info (Next.js pattern) from runtime.
+/// Try to extract chunk map info (Next.js pattern) from runtime, i.e. a `.u` function of the form
+/// `prefix + ({..}[id] || id) + sep + {..}[id] + ".js"`. Special-cased chunks in front of it are
+/// ignored.
 pub fn extract_chunk_maps(js: &str) -> Option<ChunkMapInfo> {
-    // Look for the pattern: "static/chunks/" + ({...}[e] || e) + "." + {...}[e] + ".js"
-    static MAP_RE: Lazy<Regex> = Lazy::new(|| {
-        Regex::new(r#""(static/chunks/)"[^+]*\+[^{]*\{([^}]*)\}\[e\][^+]*\+\s*"\."\s*\+[^{]*\{([^}]*)\}\[e\]"#).unwrap()
-    });
-
-    let caps = MAP_RE.captures(js)?;
-    let prefix = caps.get(1)?.as_str().to_string();
-    let map1_raw = caps.get(2)?.as_str();
-    let map2_raw = caps.get(3)?.as_str();
-
-    fn parse_obj(raw: &str) -> std::collections::HashMap<String, String> {
-        let mut hm = std::collections::HashMap::new();
-        // Convert to JSON by wrapping with braces and quoting keys/values properly
-        // naive: split by commas "id: \"hash\""
-        for part in raw.split(',') {
-            let trimmed = part.trim();
-            if trimmed.is_empty() { continue; }
-            let pieces: Vec<&str> = trimmed.split(':').collect();
-            if pieces.len() != 2 { continue; }
-            let key = pieces[0].trim().trim_matches('"').trim();
-            let key = key.to_string();
-            let val = pieces[1].trim().trim_matches('"');
-            hm.insert(key, val.to_string());
+    let mut expr = analyze_webpack_runtime(js)?.js_filename?;
+    while let ChunkUrlExpr::IfEq { otherwise, .. } = expr {
+        expr = *otherwise;
+    }
+    let ChunkUrlExpr::Concat(parts) = expr else { return None };
+    match <[ChunkUrlExpr; 5]>::try_from(parts).ok()? {
+        [ChunkUrlExpr::Lit(prefix), ChunkUrlExpr::Lookup { map: map_first, fallback: Some(fb) }, ChunkUrlExpr::Lit(separator), ChunkUrlExpr::Lookup { map: map_second, fallback: None }, ChunkUrlExpr::Lit(ext)]
+            if *fb == ChunkUrlExpr::ChunkId && ext == ".js" =>
+        {
+            Some(ChunkMapInfo { prefix, separator, map_first, map_second })
         }
-        hm
+        _ => None,
     }
-
-    Some(ChunkMapInfo {
-        prefix,
-        separator: ".".to_string(), // Default separator
-        map_first: parse_obj(map1_raw),
-        map_second: parse_obj(map2_raw),
-    })
 }
 
 /// Generate chunk URLs using map info and chunk ids.
@@ -0,0 +1,315 @@
+//! Split webpack chunks into their individual modules.
+//!
+//! Without a sourcemap the only structure left in a production chunk is webpack's module table:
+//! an object (or array) mapping module IDs to `function(module, exports, __webpack_require__)`
+//! wrappers. This module finds those tables with SWC and extracts each module's body, rewriting
+//! `__webpack_require__(id)` calls with literal IDs into `require("./<id>.js")` so the split files
+//! reference each other.
+//!
+//! Module tables are recognised in three places:
+//!
+//! * `webpackChunk*.push([[chunkIds], {modules}, runtime?])` (webpack 5 chunks)
+//! * `var __webpack_modules__ = {modules}` (unminified webpack 5 entry bundles)
+//! * the webpack 4 bootstrap IIFE, `(function(modules){...})({modules})`; array tables only when the
+//!   IIFE defines `__webpack_require__` or calls `modules[id].call(...)`
+
+use std::path::{Path, PathBuf};
+
+use swc_common::Spanned;
+use swc_ecma_ast::*;
+
+use crate::source_ledger::{content_hash, SourceLedger, WriteAction};
+use crate::js_walk::{self, literal_key, pat_ident, prop_key, unparen, ParsedJs, Visit};
+use crate::webpack_runtime::mentions_chunk_global;
+
+/// Subdirectory of the output root that split modules are written to. Module IDs are unique per
+/// build, so modules from every chunk share it and cross-chunk `require`s resolve.
+pub const MODULES_DIR: &str = "webpack_modules";
+
+/// One module extracted from a module table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebpackModule {
+    /// Module ID as written in the table (`123`, `"./src/index.js"`, `"a1b2"`).
+    pub id: String,
+    /// Module body with literal `__webpack_require__(id)` calls rewritten to relative requires.
+    pub source: String,
+}
+
+impl WebpackModule {
+    /// File name the module is written to, see [`module_file_name`].
+    pub fn file_name(&self) -> String {
+        module_file_name(&self.id)
+    }
+}
+
+/// File name for a module ID: characters outside `[A-Za-z0-9._-]` become `_` and leading dots
+/// are dropped, so named IDs like `./src/index.js` cannot escape the modules directory. Plain IDs
+/// map to `<id>.js`; any other ID gets the start of its hash appended, so IDs that mangle to the
+/// same name (`./src/a/b.js`, `./src/a_b.js`) still get distinct files.
+pub fn module_file_name(id: &str) -> String {
+    let name: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') { c } else { '_' })
+        .collect();
+    let stem = name.trim_start_matches('.');
+    let stem = stem.strip_suffix(".js").unwrap_or(stem);
+    if stem == id && !stem.is_empty() {
+        return format!("{stem}.js");
+    }
+    let stem = if stem.is_empty() { "_" } else { stem };
+    format!("{stem}-{}.js", &content_hash(id.as_bytes())[..8])
+}
+
+/// Extract every module from the module tables in `js`. Returns an empty list if the code does
+/// not parse or contains no module table.
+pub fn split_webpack_modules(js: &str) -> Vec<WebpackModule> {
+    let Some(parsed) = js_walk::parse_js("chunk.js", js) else { return Vec::new() };
+    let mut finder = TableFinder::default();
+    js_walk::walk_program(&parsed.program, &mut finder);
+
+    let mut modules: Vec<WebpackModule> = Vec::new();
+    for (id, func) in finder.modules {
+        if modules.iter().any(|m| m.id == id) {
+            continue;
+        }
+        if let Some(source) = module_source(&parsed, &id, &func) {
+            modules.push(WebpackModule { id, source });
+        }
+    }
+    modules
+}
+
+/// Split `js` and write each module to `out_root/webpack_modules/` through `ledger`, attributed to
+/// the chunk `contributor`, so a module seen again in another chunk follows the conflict policy.
+/// Returns each module's path (relative to `out_root`) with what the ledger did with it.
+pub fn write_webpack_modules(js: &str, out_root: &Path, ledger: &SourceLedger, contributor: &str) -> std::io::Result<Vec<(PathBuf, WriteAction)>> {
+    let dir = out_root.join(MODULES_DIR);
+    let mut written = Vec::new();
+    for m in split_webpack_modules(js) {
+        let path = dir.join(m.file_name());
+        let action = ledger.write_source(out_root, &path, &m.source, contributor)?;
+        written.push((Path::new(MODULES_DIR).join(m.file_name()), action));
+    }
+    Ok(written)
+}
+
+/// A module wrapper function, normalised over the function/arrow/method forms.
+#[derive(Clone)]
+struct ModuleFn {
+    params: Vec<Option<String>>,
+    body: ModuleBody,
+}
+
+#[derive(Clone)]
+enum ModuleBody {
+    Block(BlockStmt),
+    Expr(Box<Expr>),
+}
+
+#[derive(Default)]
+struct TableFinder {
+    modules: Vec<(String, ModuleFn)>,
+}
+
+impl Visit for TableFinder {
+    fn expr(&mut self, e: &Expr) {
+        let Expr::Call(c) = e else { return };
+        let Callee::Expr(callee) = &c.callee else { return };
+        match unparen(callee) {
+            // webpackChunk_app.push([[ids], {modules}])
+            Expr::Member(m) if js_walk::member_prop_name(m).as_deref() == Some("push") && mentions_chunk_global(&m.obj) => {
+                let Some(Expr::Array(outer)) = c.args.first().map(|a| unparen(&a.expr)) else { return };
+                if let Some(Some(table)) = outer.elems.get(1) {
+                    self.table(&table.expr, false);
+                }
+            }
+            // (function(modules){ ...bootstrap... })({modules})
+            bootstrap @ (Expr::Fn(_) | Expr::Arrow(_)) if c.args.len() == 1 => {
+                // Arrays of functions are common IIFE arguments (UMD factories, plugin lists), so
+                // array tables also need the callee to look like the webpack bootstrap.
+                if matches!(unparen(&c.args[0].expr), Expr::Array(_)) && !is_webpack_bootstrap(bootstrap) {
+                    return;
+                }
+                self.table(&c.args[0].expr, true)
+            }
+            _ => {}
+        }
+    }
+
+    fn var_declarator(&mut self, d: &VarDeclarator) {
+        if pat_ident(&d.name).as_deref() == Some("__webpack_modules__") {
+            if let Some(init) = &d.init {
+                self.table(init, false);
+            }
+        }
+    }
+}
+
+impl TableFinder {
+    /// Record the entries of `e` if every entry is a module wrapper function. `strict` also
+    /// requires object keys to look like module IDs (numeric or a path), for tables recognised
+    /// only by shape.
+    fn table(&mut self, e: &Expr, strict: bool) {
+        let mut entries = Vec::new();
+        match unparen(e) {
+            Expr::Object(o) => {
+                for p in &o.props {
+                    let PropOrSpread::Prop(p) = p else { return };
+                    let entry = match &**p {
+                        Prop::KeyValue(kv) => prop_key(&kv.key).zip(module_fn(&kv.value)),
+                        Prop::Method(m) => prop_key(&m.key).zip(function_module(&m.function)),
+                        _ => None,
+                    };
+                    let Some(entry) = entry else { return };
+                    if strict && !(entry.0.bytes().all(|b| b.is_ascii_digit()) || entry.0.contains('/')) {
+                        return;
+                    }
+                    entries.push(entry);
+                }
+            }
+            Expr::Array(a) => {
+                for (i, el) in a.elems.iter().enumerate() {
+                    // Holes are IDs that live in other chunks.
+                    let Some(el) = el else { continue };
+                    let Some(f) = module_fn(&el.expr) else { return };
+                    entries.push((i.to_string(), f));
+                }
+            }
+            _ => return,
+        }
+        self.modules.extend(entries);
+    }
+}
+
+/// Whether the IIFE callee `f` defines `__webpack_require__` or calls modules out of its first
+/// parameter (`modules[moduleId].call(...)`), as the webpack 4 bootstrap does.
+fn is_webpack_bootstrap(f: &Expr) -> bool {
+    let first = match f {
+        Expr::Fn(f) => f.function.params.first().and_then(|p| pat_ident(&p.pat)),
+        Expr::Arrow(a) => a.params.first().and_then(pat_ident),
+        _ => None,
+    };
+    let mut v = BootstrapEvidence { modules: first, found: false };
+    js_walk::walk_expr(f, &mut v);
+    v.found
+}
+
+struct BootstrapEvidence {
+    modules: Option<String>,
+    found: bool,
+}
+
+impl Visit for BootstrapEvidence {
+    fn expr(&mut self, e: &Expr) {
+        let Expr::Call(c) = e else { return };
+        let Callee::Expr(callee) = &c.callee else { return };
+        let Expr::Member(m) = unparen(callee) else { return };
+        if js_walk::member_prop_name(m).as_deref() != Some("call") {
+            return;
+        }
+        if let Expr::Member(table) = unparen(&m.obj) {
+            let computed = matches!(table.prop, MemberProp::Computed(_));
+            if computed && matches!(unparen(&table.obj), Expr::Ident(i) if Some(&*i.sym) == self.modules.as_deref()) {
+                self.found = true;
+            }
+        }
+    }
+
+    fn fn_decl(&mut self, d: &FnDecl) {
+        self.found |= &*d.ident.sym == "__webpack_require__";
+    }
+
+    fn var_declarator(&mut self, d: &VarDeclarator) {
+        self.found |= pat_ident(&d.name).as_deref() == Some("__webpack_require__");
+    }
+}
+
+fn module_fn(e: &Expr) -> Option<ModuleFn> {
+    match unparen(e) {
+        Expr::Fn(f) => function_module(&f.function),
+        Expr::Arrow(a) if a.params.len() <= 3 => Some(ModuleFn {
+            params: a.params.iter().map(pat_ident).collect(),
+            body: match &*a.body {
+                BlockStmtOrExpr::BlockStmt(b) => ModuleBody::Block(b.clone()),
+                BlockStmtOrExpr::Expr(e) => ModuleBody::Expr(e.clone()),
+            },
+        }),
+        _ => None,
+    }
+}
+
+fn function_module(f: &Function) -> Option<ModuleFn> {
+    if f.params.len() > 3 {
+        return None;
+    }
+    Some(ModuleFn {
+        params: f.params.iter().map(|p| pat_ident(&p.pat)).collect(),
+        body: ModuleBody::Block(f.body.clone()?),
+    })
+}
+
+/// Calls of the module's require parameter with a literal ID.
+struct RequireCalls<'a> {
+    require: &'a str,
+    calls: Vec<(swc_common::Span, String)>,
+}
+
+impl Visit for RequireCalls<'_> {
+    fn expr(&mut self, e: &Expr) {
+        let Expr::Call(c) = e else { return };
+        let Callee::Expr(callee) = &c.callee else { return };
+        if !matches!(unparen(callee), Expr::Ident(i) if &*i.sym == self.require) || c.args.len() != 1 {
+            return;
+        }
+        if let Some(id) = literal_key(&c.args[0].expr) {
+            self.calls.push((c.span, id));
+        }
+    }
+}
+
+fn module_source(parsed: &ParsedJs, id: &str, f: &ModuleFn) -> Option<String> {
+    // Block bodies are emitted without their braces.
+    let (span, inner) = match &f.body {
+        ModuleBody::Block(b) => (b.span, 1),
+        ModuleBody::Expr(e) => (e.span(), 0),
+    };
+    let start = parsed.offset(span.lo) + inner;
+    let text = parsed.snippet(span);
+    let text = &text[inner..text.len() - inner];
+
+    let mut calls = Vec::new();
+    if let Some(Some(require)) = f.params.get(2) {
+        let mut v = RequireCalls { require, calls: Vec::new() };
+        match &f.body {
+            ModuleBody::Block(b) => js_walk::walk_stmts(&b.stmts, &mut v),
+            ModuleBody::Expr(e) => js_walk::walk_expr(e, &mut v),
+        }
+        calls = v.calls;
+        calls.sort_by_key(|(s, _)| s.lo);
+    }
+
+    let mut out = String::with_capacity(text.len() + 64);
+    let names = ["module", "exports", "__webpack_require__"];
+    let bindings: Vec<String> = f
+        .params
+        .iter()
+        .zip(names)
+        .filter_map(|(p, n)| p.as_ref().map(|p| format!("{n} = {p}")))
+        .collect();
+    out.push_str(&format!("/* webpack module {id} ({}) */\n", bindings.join(", ")));
+
+    let mut pos = 0;
+    for (span, dep) in calls {
+        let lo = parsed.offset(span.lo).checked_sub(start)?;
+        let hi = parsed.offset(span.hi).checked_sub(start)?;
+        if lo < pos || hi > text.len() {
+            continue;
+        }
+        out.push_str(&text[pos..lo]);
+        out.push_str(&format!("require(\"./{}\")", module_file_name(&dep)));
+        pos = hi;
+    }
+    out.push_str(&text[pos..]);
+    out.push('\n');
+    Some(out)
+}
@@ -0,0 +1,420 @@
+//! SWC-based analysis of webpack runtime code.
+//!
+//! Minifiers rename `__webpack_require__` (to `r`, `n`, `o`, ...), reorder operands and switch
+//! quote styles, so instead of matching source text this module parses the runtime and looks for
+//! assignments to the well-known runtime properties:
+//!
+//! * `.p` – public path
+//! * `.u` – JS chunk filename function (webpack 4's `jsonpScriptSrc` is handled the same way)
+//! * `.miniCssF` – CSS chunk filename function (mini-css-extract-plugin)
+//!
+//! The filename functions are converted into a small [`ChunkUrlExpr`] tree that can be evaluated
+//! for any chunk ID.
+
+use std::collections::{HashMap, HashSet};
+
+use swc_ecma_ast::*;
+use url::Url;
+
+use crate::js_walk::{self, literal_key, member_prop_name, pat_ident, prop_key, unparen, Visit};
+
+/// Properties webpack installs on its require function; used to recognise the (possibly
+/// minified) require binding.
+const RUNTIME_PROPS: &[&str] = &[
+    "m", "c", "d", "o", "r", "n", "e", "f", "u", "p", "l", "g", "h", "t", "O", "miniCssF", "nmd", "hmd",
+];
+
+/// Expression computing a chunk's filename from its ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkUrlExpr {
+    /// A string (or number) literal.
+    Lit(String),
+    /// The chunk ID parameter itself.
+    ChunkId,
+    /// String concatenation (`a + b`, template literals).
+    Concat(Vec<ChunkUrlExpr>),
+    /// `{id: value}[chunkId]`, optionally `|| fallback`.
+    Lookup {
+        map: HashMap<String, String>,
+        fallback: Option<Box<ChunkUrlExpr>>,
+    },
+    /// `chunkId === id ? then : otherwise`.
+    IfEq {
+        ids: Vec<String>,
+        then: Box<ChunkUrlExpr>,
+        otherwise: Box<ChunkUrlExpr>,
+    },
+}
+
+impl ChunkUrlExpr {
+    /// Evaluate the filename for `id`; `None` when a lookup has no entry for it (e.g. a chunk
+    /// without CSS).
+    pub fn eval(&self, id: &str) -> Option<String> {
+        match self {
+            ChunkUrlExpr::Lit(s) => Some(s.clone()),
+            ChunkUrlExpr::ChunkId => Some(id.to_string()),
+            ChunkUrlExpr::Concat(parts) => parts.iter().map(|p| p.eval(id)).collect(),
+            ChunkUrlExpr::Lookup { map, fallback } => match map.get(id) {
+                Some(v) => Some(v.clone()),
+                None => fallback.as_ref()?.eval(id),
+            },
+            ChunkUrlExpr::IfEq { ids, then, otherwise } => {
+                if ids.iter().any(|i| i == id) { then.eval(id) } else { otherwise.eval(id) }
+            }
+        }
+    }
+
+    /// Leading literal of the general case, e.g. `static/chunks/`.
+    pub fn static_prefix(&self) -> &str {
+        match self {
+            ChunkUrlExpr::Lit(s) => s,
+            ChunkUrlExpr::Concat(parts) => match parts.first() {
+                Some(ChunkUrlExpr::Lit(s)) => s,
+                _ => "",
+            },
+            ChunkUrlExpr::IfEq { otherwise, .. } => otherwise.static_prefix(),
+            _ => "",
+        }
+    }
+
+    /// Chunk IDs mentioned by lookups and equality tests, in a stable order.
+    pub fn referenced_ids(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        self.collect_ids(&mut out);
+        out
+    }
+
+    fn collect_ids(&self, out: &mut Vec<String>) {
+        match self {
+            ChunkUrlExpr::Concat(parts) => parts.iter().for_each(|p| p.collect_ids(out)),
+            ChunkUrlExpr::Lookup { map, fallback } => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort_by(|a, b| natural_cmp(a, b));
+                out.extend(keys.into_iter().cloned());
+                if let Some(f) = fallback {
+                    f.collect_ids(out);
+                }
+            }
+            ChunkUrlExpr::IfEq { ids, then, otherwise } => {
+                out.extend(ids.iter().cloned());
+                then.collect_ids(out);
+                otherwise.collect_ids(out);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Numeric IDs sort numerically, everything else lexically after them.
+fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(x), Ok(y)) => x.cmp(&y),
+        (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+        (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+        _ => a.cmp(b),
+    }
+}
+
+/// Everything the analyzer could recover from a webpack runtime.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WebpackRuntimeInfo {
+    /// Local name of `__webpack_require__`, if it could be identified.
+    pub require_name: Option<String>,
+    /// Literal value assigned to `.p`.
+    pub public_path: Option<String>,
+    /// `.u` – filename of JS chunks.
+    pub js_filename: Option<ChunkUrlExpr>,
+    /// `.miniCssF` – filename of CSS chunks.
+    pub css_filename: Option<ChunkUrlExpr>,
+    /// Chunk IDs referenced by the filename functions or `webpackChunk*.push` calls, deduplicated.
+    pub chunk_ids: Vec<String>,
+}
+
+impl WebpackRuntimeInfo {
+    /// Resolve every known chunk's JS and CSS filename against `base`.
+    pub fn chunk_urls(&self, base: &Url) -> Vec<Url> {
+        let mut seen = HashSet::new();
+        let mut urls = Vec::new();
+        for expr in [&self.js_filename, &self.css_filename].into_iter().flatten() {
+            for id in &self.chunk_ids {
+                if let Some(u) = expr.eval(id).and_then(|p| base.join(&p).ok()) {
+                    if seen.insert(u.clone()) {
+                        urls.push(u);
+                    }
+                }
+            }
+        }
+        urls
+    }
+}
+
+/// Assignments and calls of interest, gathered in one pass.
+#[derive(Default)]
+struct Collector {
+    /// `obj.prop = value` for runtime props, keyed by object name.
+    assigns: Vec<(String, String, Expr)>,
+    /// Webpack 4 `function jsonpScriptSrc(chunkId) { ... }`.
+    jsonp_script_src: Option<Function>,
+    push_ids: Vec<String>,
+}
+
+impl Visit for Collector {
+    fn expr(&mut self, e: &Expr) {
+        match e {
+            Expr::Assign(a) if a.op == AssignOp::Assign => {
+                if let AssignTarget::Simple(SimpleAssignTarget::Member(m)) = &a.left {
+                    if let (Expr::Ident(obj), Some(prop)) = (unparen(&m.obj), member_prop_name(m)) {
+                        if RUNTIME_PROPS.contains(&prop.as_str()) {
+                            self.assigns.push((obj.sym.to_string(), prop, (*a.right).clone()));
+                        }
+                    }
+                }
+            }
+            Expr::Call(c) => self.push_call(c),
+            _ => {}
+        }
+    }
+
+    fn fn_decl(&mut self, d: &FnDecl) {
+        if &*d.ident.sym == "jsonpScriptSrc" {
+            self.jsonp_script_src = Some((*d.function).clone());
+        }
+    }
+}
+
+impl Collector {
+    /// `(self.webpackChunkapp = ...).push([[ids], modules])` and `webpackJsonp.push(...)`.
+    fn push_call(&mut self, c: &CallExpr) {
+        let Callee::Expr(callee) = &c.callee else { return };
+        let Expr::Member(m) = unparen(callee) else { return };
+        if member_prop_name(m).as_deref() != Some("push") || !mentions_chunk_global(&m.obj) {
+            return;
+        }
+        let Some(first) = c.args.first() else { return };
+        let Expr::Array(outer) = unparen(&first.expr) else { return };
+        let Some(Some(ids)) = outer.elems.first() else { return };
+        if let Expr::Array(ids) = unparen(&ids.expr) {
+            self.push_ids.extend(ids.elems.iter().flatten().filter_map(|e| literal_key(&e.expr)));
+        }
+    }
+}
+
+/// Whether an expression refers to the `webpackChunk*` / `webpackJsonp*` global.
+pub(crate) fn mentions_chunk_global(e: &Expr) -> bool {
+    let is_chunk_name = |s: &str| s.starts_with("webpackChunk") || s.starts_with("webpackJsonp");
+    match unparen(e) {
+        Expr::Ident(i) => is_chunk_name(&i.sym),
+        Expr::Member(m) => member_prop_name(m).is_some_and(|p| is_chunk_name(&p)) || mentions_chunk_global(&m.obj),
+        Expr::Assign(a) => {
+            matches!(&a.left, AssignTarget::Simple(SimpleAssignTarget::Member(m))
+                if member_prop_name(m).is_some_and(|p| is_chunk_name(&p)))
+                || mentions_chunk_global(&a.right)
+        }
+        Expr::Bin(b) => mentions_chunk_global(&b.left) || mentions_chunk_global(&b.right),
+        _ => false,
+    }
+}
+
+/// Parse a webpack runtime and extract its public path, chunk filename functions and chunk IDs.
+/// Returns `None` if the code does not parse.
+pub fn analyze_webpack_runtime(js: &str) -> Option<WebpackRuntimeInfo> {
+    let parsed = js_walk::parse_js("runtime.js", js)?;
+    let mut col = Collector::default();
+    js_walk::walk_program(&parsed.program, &mut col);
+
+    let require_name = pick_require_name(&col.assigns);
+    let assigned = |prop: &str| {
+        col.assigns
+            .iter()
+            .rev()
+            .find(|(obj, p, _)| Some(obj) == require_name.as_ref() && p == prop)
+            .map(|(_, _, v)| v)
+    };
+
+    let public_path = assigned("p").and_then(|v| match unparen(v) {
+        Expr::Lit(Lit::Str(s)) => Some(s.value.to_string()),
+        _ => None,
+    });
+    let req = require_name.as_deref();
+    let js_filename = assigned("u")
+        .and_then(|v| filename_fn(v, req))
+        .or_else(|| col.jsonp_script_src.as_ref().and_then(|f| function_filename(f, req)));
+    let css_filename = assigned("miniCssF").and_then(|v| filename_fn(v, req));
+
+    let mut chunk_ids = Vec::new();
+    let mut seen = HashSet::new();
+    let from_exprs = [&js_filename, &css_filename].into_iter().flatten().flat_map(|e| e.referenced_ids());
+    for id in col.push_ids.into_iter().chain(from_exprs) {
+        if seen.insert(id.clone()) {
+            chunk_ids.push(id);
+        }
+    }
+
+    Some(WebpackRuntimeInfo { require_name, public_path, js_filename, css_filename, chunk_ids })
+}
+
+/// `__webpack_require__` wins outright; otherwise the object with the most runtime properties
+/// assigned, provided it has `.u` or `.p`.
+fn pick_require_name(assigns: &[(String, String, Expr)]) -> Option<String> {
+    let mut props: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for (obj, prop, _) in assigns {
+        props.entry(obj.as_str()).or_default().insert(prop.as_str());
+    }
+    if props.contains_key("__webpack_require__") {
+        return Some("__webpack_require__".into());
+    }
+    props
+        .into_iter()
+        .filter(|(_, p)| p.contains("u") || p.contains("p"))
+        .max_by(|a, b| a.1.len().cmp(&b.1.len()).then_with(|| b.0.cmp(a.0)))
+        .map(|(name, _)| name.to_string())
+}
+
+/// Convert a `.u`-style function value into a [`ChunkUrlExpr`].
+fn filename_fn(v: &Expr, req: Option<&str>) -> Option<ChunkUrlExpr> {
+    match unparen(v) {
+        Expr::Arrow(a) => {
+            let params: Vec<&Pat> = a.params.iter().collect();
+            match &*a.body {
+                BlockStmtOrExpr::Expr(e) => to_url_expr(e, &pat_ident(params.first()?)?, req),
+                BlockStmtOrExpr::BlockStmt(b) => function_expr(&params, b, req),
+            }
+        }
+        Expr::Fn(f) => function_filename(&f.function, req),
+        _ => None,
+    }
+}
+
+fn function_filename(f: &Function, req: Option<&str>) -> Option<ChunkUrlExpr> {
+    let params: Vec<&Pat> = f.params.iter().map(|p| &p.pat).collect();
+    function_expr(&params, f.body.as_ref()?, req)
+}
+
+fn function_expr(params: &[&Pat], body: &BlockStmt, req: Option<&str>) -> Option<ChunkUrlExpr> {
+    let id = pat_ident(params.first()?)?;
+    stmts_expr(&body.stmts, &id, req)
+}
+
+/// Fold `if (id === N) return a; ... return b;` into nested [`ChunkUrlExpr::IfEq`].
+fn stmts_expr(stmts: &[Stmt], id: &str, req: Option<&str>) -> Option<ChunkUrlExpr> {
+    for (i, stmt) in stmts.iter().enumerate() {
+        match stmt {
+            Stmt::Return(r) => return to_url_expr(r.arg.as_ref()?, id, req),
+            Stmt::If(s) if s.alt.is_none() => {
+                let Some(ids) = eq_ids(&s.test, id) else { continue };
+                let then = match &*s.cons {
+                    Stmt::Return(r) => to_url_expr(r.arg.as_ref()?, id, req)?,
+                    Stmt::Block(b) => stmts_expr(&b.stmts, id, req)?,
+                    _ => continue,
+                };
+                let otherwise = stmts_expr(&stmts[i + 1..], id, req)?;
+                return Some(ChunkUrlExpr::IfEq { ids, then: Box::new(then), otherwise: Box::new(otherwise) });
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// IDs tested by `id === N`, `N == id`, or `||`-chains of those.
+fn eq_ids(test: &Expr, id: &str) -> Option<Vec<String>> {
+    let Expr::Bin(b) = unparen(test) else { return None };
+    match b.op {
+        BinaryOp::EqEqEq | BinaryOp::EqEq => {
+            let is_id = |e: &Expr| matches!(unparen(e), Expr::Ident(i) if &*i.sym == id);
+            if is_id(&b.left) {
+                literal_key(&b.right).map(|k| vec![k])
+            } else if is_id(&b.right) {
+                literal_key(&b.left).map(|k| vec![k])
+            } else {
+                None
+            }
+        }
+        BinaryOp::LogicalOr => {
+            let mut ids = eq_ids(&b.left, id)?;
+            ids.extend(eq_ids(&b.right, id)?);
+            Some(ids)
+        }
+        _ => None,
+    }
+}
+
+fn object_map(o: &ObjectLit) -> Option<HashMap<String, String>> {
+    let mut map = HashMap::new();
+    for p in &o.props {
+        let PropOrSpread::Prop(p) = p else { return None };
+        let Prop::KeyValue(kv) = &**p else { return None };
+        map.insert(prop_key(&kv.key)?, literal_key(&kv.value)?);
+    }
+    Some(map)
+}
+
+fn to_url_expr(e: &Expr, id: &str, req: Option<&str>) -> Option<ChunkUrlExpr> {
+    match unparen(e) {
+        Expr::Lit(_) => literal_key(e).map(ChunkUrlExpr::Lit),
+        Expr::Ident(i) if &*i.sym == id => Some(ChunkUrlExpr::ChunkId),
+        Expr::Tpl(t) => {
+            let mut parts = Vec::new();
+            for (i, q) in t.quasis.iter().enumerate() {
+                parts.push(ChunkUrlExpr::Lit(q.cooked.as_ref().unwrap_or(&q.raw).to_string()));
+                if let Some(ex) = t.exprs.get(i) {
+                    parts.push(to_url_expr(ex, id, req)?);
+                }
+            }
+            Some(concat(parts))
+        }
+        Expr::Bin(b) if b.op == BinaryOp::Add => {
+            Some(concat(vec![to_url_expr(&b.left, id, req)?, to_url_expr(&b.right, id, req)?]))
+        }
+        Expr::Bin(b) if b.op == BinaryOp::LogicalOr => match to_url_expr(&b.left, id, req)? {
+            ChunkUrlExpr::Lookup { map, fallback: None } => Some(ChunkUrlExpr::Lookup {
+                map,
+                fallback: Some(Box::new(to_url_expr(&b.right, id, req)?)),
+            }),
+            _ => None,
+        },
+        Expr::Member(m) => {
+            // `__webpack_require__.p + ...` inside jsonpScriptSrc: the base URL already covers it.
+            if let (Expr::Ident(obj), Some("p")) = (unparen(&m.obj), member_prop_name(m).as_deref()) {
+                if Some(&*obj.sym) == req {
+                    return Some(ChunkUrlExpr::Lit(String::new()));
+                }
+            }
+            let MemberProp::Computed(c) = &m.prop else { return None };
+            if !matches!(unparen(&c.expr), Expr::Ident(i) if &*i.sym == id) {
+                return None;
+            }
+            let Expr::Object(o) = unparen(&m.obj) else { return None };
+            Some(ChunkUrlExpr::Lookup { map: object_map(o)?, fallback: None })
+        }
+        Expr::Cond(c) => Some(ChunkUrlExpr::IfEq {
+            ids: eq_ids(&c.test, id)?,
+            then: Box::new(to_url_expr(&c.cons, id, req)?),
+            otherwise: Box::new(to_url_expr(&c.alt, id, req)?),
+        }),
+        _ => None,
+    }
+}
+
+/// Flatten nested concatenations and merge adjacent literals.
+fn concat(parts: Vec<ChunkUrlExpr>) -> ChunkUrlExpr {
+    let mut out: Vec<ChunkUrlExpr> = Vec::new();
+    for p in parts {
+        let flat = match p {
+            ChunkUrlExpr::Concat(inner) => inner,
+            other => vec![other],
+        };
+        for f in flat {
+            match (out.last_mut(), f) {
+                (_, ChunkUrlExpr::Lit(s)) if s.is_empty() => {}
+                (Some(ChunkUrlExpr::Lit(prev)), ChunkUrlExpr::Lit(s)) => prev.push_str(&s),
+                (_, f) => out.push(f),
+            }
+        }
+    }
+    match out.len() {
+        0 => ChunkUrlExpr::Lit(String::new()),
+        1 => out.pop().unwrap(),
+        _ => ChunkUrlExpr::Concat(out),
+    }
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::Listed;
+use httpmock::prelude::*;
+use resourcer_core::{
+    CompositeFetcher, DumpTarget, FetchError, Fetcher, HttpClient, HttpFetcher, RecordingFetcher, ReplayFetcher,
+    Validators, CASSETTE_INDEX,
+};
+use std::sync::Arc;
+use url::Url;
+
+fn recorder(dir: &std::path::Path) -> RecordingFetcher {
+    RecordingFetcher::new(Arc::new(HttpFetcher::new(HttpClient::shared())), dir).unwrap()
+}
+
+#[tokio::test]
+async fn replays_recorded_responses_offline() {
+    let cassette = tempfile::tempdir().unwrap();
+    let (ok, missing, unrecorded);
+    {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(GET).path("/a.js");
+            then.status(200).header("SourceMap", "a.js.map").body("var a;");
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::HEAD).path("/a.js");
+            then.status(200);
+        });
+        server.mock(|when, then| {
+            when.path("/gone.js");
+            then.status(404);
+        });
+        ok = Url::parse(&server.url("/a.js")).unwrap();
+        missing = Url::parse(&server.url("/gone.js")).unwrap();
+        unrecorded = Url::parse(&server.url("/never.js")).unwrap();
+
+        let rec = recorder(cassette.path());
+        assert_eq!(rec.fetch(&ok).await.unwrap().body, "var a;");
+        assert!(matches!(rec.fetch(&missing).await, Err(FetchError::HttpStatus(404))));
+        assert!(rec.exists(&ok).await);
+    }
+    assert_eq!(std::fs::read_to_string(cassette.path().join(CASSETTE_INDEX)).unwrap().lines().count(), 3);
+
+    let replay = ReplayFetcher::open(cassette.path()).unwrap();
+    let resp = replay.fetch(&ok).await.unwrap();
+    assert_eq!(resp.body, "var a;");
+    assert_eq!(resp.header("sourcemap"), Some("a.js.map"));
+    assert!(replay.exists(&ok).await);
+    assert!(matches!(replay.fetch(&missing).await, Err(FetchError::HttpStatus(404))));
+    assert!(!replay.exists(&missing).await);
+    assert!(matches!(replay.fetch(&unrecorded).await, Err(FetchError::NotRecorded(_))));
+}
+
+#[tokio::test]
+async fn skips_network_failures_and_forwards_conditional_requests() {
+    let cassette = tempfile::tempdir().unwrap();
+    let server = MockServer::start_async().await;
+    let unchanged = server.mock(|when, then| {
+        when.method(GET).path("/a.js").header("if-none-match", "\"v1\"");
+        then.status(304);
+    });
+    let rec = recorder(cassette.path());
+    let a = Url::parse(&server.url("/a.js")).unwrap();
+    let validators = Validators { etag: Some("\"v1\"".into()), last_modified: None };
+    assert!(rec.fetch_conditional(&a, &validators).await.unwrap().is_not_modified());
+    unchanged.assert();
+
+    // Nothing listens on port 1.
+    let down = Url::parse("http://127.0.0.1:1/b.js").unwrap();
+    assert!(rec.check(&down).await.unwrap_err().status().is_none());
+    drop(rec);
+
+    assert_eq!(std::fs::read_to_string(cassette.path().join(CASSETTE_INDEX)).unwrap(), "");
+    assert!(matches!(ReplayFetcher::open(cassette.path()).unwrap().check(&down).await, Err(FetchError::NotRecorded(_))));
+}
+
+/// Dump `/app.js` of `page` into `out`, loading `http` URLs through `fetcher`.
+async fn dump(fetcher: Arc<dyn Fetcher>, page: &str, out: &std::path::Path) -> resourcer_core::DumpResult {
+    let mut composite = CompositeFetcher::with_builtin(HttpClient::shared());
+    composite.register("http", fetcher);
+    common::dump(Listed::new(&["app.js"]), common::options(out), Some(Arc::new(composite)), DumpTarget::Url(page.to_string())).await
+}
+
+#[tokio::test]
+async fn recorded_dump_replays_identically() {
+    let cassette = tempfile::tempdir().unwrap();
+    let first = tempfile::tempdir().unwrap();
+    let page;
+    {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.path("/");
+            then.status(200).body("<html></html>");
+        });
+        server.mock(|when, then| {
+            when.path("/app.js");
+            then.status(200).body("var a;\n//# sourceMappingURL=app.js.map");
+        });
+        server.mock(|when, then| {
+            when.path("/app.js.map");
+            then.status(200).body(r#"{"version":3,"sources":["src/app.ts"],"sourcesContent":["let a"],"mappings":""}"#);
+        });
+        page = server.url("/");
+        let recorded = dump(Arc::new(recorder(cassette.path())), &page, first.path()).await;
+        assert_eq!(recorded.stats.written_sources, 1);
+    }
+
+    let second = tempfile::tempdir().unwrap();
+    let replayed = dump(Arc::new(ReplayFetcher::open(cassette.path()).unwrap()), &page, second.path()).await;
+    assert_eq!(replayed.stats.written_sources, 1);
+    assert_eq!(std::fs::read_to_string(second.path().join("src/app.ts")).unwrap(), "let a");
+}
@@ -0,0 +1,73 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use resourcer_core::{
+    ChunkPlan, Detection, DetectorError, DetectorRegistry, DumpOptions, DumpResult, DumpTarget, Dumper, Fetcher,
+    PageContext, RuntimeDetector,
+};
+use url::Url;
+
+/// Claims every page and enumerates fixed paths relative to it or, built with
+/// [`Listed::from_runtime`], the paths listed one per line in a runtime file loaded through the
+/// page's fetcher.
+pub struct Listed {
+    paths: Vec<&'static str>,
+    runtime: Option<&'static str>,
+}
+
+impl Listed {
+    pub fn new(paths: &[&'static str]) -> Self {
+        Self { paths: paths.to_vec(), runtime: None }
+    }
+
+    pub fn from_runtime(runtime: &'static str) -> Self {
+        Self { paths: Vec::new(), runtime: Some(runtime) }
+    }
+
+    /// A registry holding only this detector.
+    pub fn registry(self) -> DetectorRegistry {
+        let mut registry = DetectorRegistry::empty();
+        registry.register(self);
+        registry
+    }
+}
+
+#[async_trait]
+impl RuntimeDetector for Listed {
+    fn name(&self) -> &'static str {
+        "listed"
+    }
+
+    fn detect(&self, _page: &PageContext) -> Option<Detection> {
+        Some(Detection { confidence: 1.0, entry: None })
+    }
+
+    async fn enumerate(&self, page: &PageContext, _d: &Detection) -> Result<ChunkPlan, DetectorError> {
+        let Some(runtime) = self.runtime else {
+            let urls = self.paths.iter().map(|p| page.page_url.join(p)).collect::<Result<Vec<Url>, _>>()?;
+            return Ok(ChunkPlan { strategy: "fixed".into(), urls });
+        };
+        let listing = page.fetcher.fetch_text(&page.page_url.join(runtime)?, 1).await?;
+        let urls = listing.lines().map(|p| page.page_url.join(p.trim())).collect::<Result<Vec<Url>, _>>()?;
+        Ok(ChunkPlan { strategy: "runtime".into(), urls })
+    }
+}
+
+/// Options for a sequential dump into `out`.
+pub fn options(out: &Path) -> DumpOptions {
+    DumpOptions { out_dir: Some(out.to_path_buf()), concurrency: 1, ..Default::default() }
+}
+
+/// Dump `target` with `detector` only, loading everything through `fetcher` (the default fetcher
+/// when `None`).
+pub async fn dump(detector: Listed, options: DumpOptions, fetcher: Option<Arc<dyn Fetcher>>, target: DumpTarget) -> DumpResult {
+    let mut dumper = Dumper::new(detector.registry()).with_options(options);
+    if let Some(fetcher) = fetcher {
+        dumper = dumper.with_fetcher(fetcher);
+    }
+    dumper.run(&target).await.unwrap()
+}
@@ -0,0 +1,67 @@
+use resourcer_core::{decode_data_url, fetch_url, find_sourcemap_urls, save_asset_with_maps, DataUrlError, SaveOptions};
+use std::path::Path;
+use tempfile::tempdir;
+use url::Url;
+
+const MAP_B64: &str = "eyJ2ZXJzaW9uIjozLCJzb3VyY2VzIjpbInNyYy9hLmpzIl0sInNvdXJjZXNDb250ZW50IjpbImV4cG9ydCBjb25zdCBhID0gMTtcbiJdLCJtYXBwaW5ncyI6IkFBQUEiLCJuYW1lcyI6W119";
+const MAP_PCT: &str = "%7B%22version%22%3A3%2C%22sources%22%3A%5B%22src/a.js%22%5D%2C%22sourcesContent%22%3A%5B%22export%20const%20a%20%3D%201%3B%5Cn%22%5D%2C%22mappings%22%3A%22AAAA%22%2C%22names%22%3A%5B%5D%7D";
+
+#[test]
+fn base64_with_charset() {
+    let d = decode_data_url(&format!("data:application/json;charset=UTF-8;base64,{MAP_B64}")).unwrap();
+    assert_eq!(d.mime_type, "application/json");
+    assert_eq!(d.charset.as_deref(), Some("utf-8"));
+    assert!(d.text().unwrap().starts_with(r#"{"version":3"#));
+}
+
+#[test]
+fn base64_without_padding_or_with_whitespace() {
+    assert_eq!(decode_data_url("data:;base64,aGk").unwrap().bytes, b"hi");
+    assert_eq!(decode_data_url("data:;base64,aG%20k=").unwrap().bytes, b"hi");
+}
+
+#[test]
+fn percent_encoded_and_defaults() {
+    let d = decode_data_url(&format!("data:application/json,{MAP_PCT}")).unwrap();
+    assert!(d.text().unwrap().contains(r#""sources":["src/a.js"]"#));
+    let plain = decode_data_url("data:,a%20b").unwrap();
+    assert_eq!(plain.mime_type, "text/plain");
+    assert_eq!(plain.charset, None);
+    assert_eq!(plain.text().unwrap(), "a b");
+}
+
+#[test]
+fn latin1_charset() {
+    let d = decode_data_url("data:text/plain;charset=ISO-8859-1,caf%E9").unwrap();
+    assert_eq!(d.text().unwrap(), "café");
+}
+
+#[test]
+fn malformed_data_urls() {
+    assert!(matches!(decode_data_url("https://x/"), Err(DataUrlError::NotDataUrl)));
+    assert!(matches!(decode_data_url("data:text/plain"), Err(DataUrlError::MissingComma)));
+    assert!(matches!(decode_data_url("data:;base64,@@@"), Err(DataUrlError::Base64(_))));
+    let d = decode_data_url("data:text/plain;charset=koi8-r,x").unwrap();
+    assert!(matches!(d.text(), Err(DataUrlError::UnsupportedCharset(c)) if c == "koi8-r"));
+}
+
+#[tokio::test]
+async fn fetch_url_decodes_data_scheme() {
+    let u = Url::parse("data:text/plain;base64,aGVsbG8=").unwrap();
+    assert_eq!(fetch_url(&u, 1).await.unwrap(), "hello");
+}
+
+#[tokio::test]
+async fn inline_sourcemap_is_reconstructed() {
+    let dir = tempdir().unwrap();
+    let js = format!("const a=1;\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,{MAP_B64}\n");
+    let base = Url::parse("https://example.com/static/app.js").unwrap();
+    let maps = find_sourcemap_urls(&base, &js);
+    assert_eq!(maps.len(), 1);
+    assert_eq!(maps[0].scheme(), "data");
+
+    save_asset_with_maps(&js, Path::new("static/app.js"), maps, dir.path(), &SaveOptions::default()).await.unwrap();
+    assert!(dir.path().join("static/app.js.map").is_file());
+    let src = std::fs::read_to_string(dir.path().join("src/a.js")).unwrap();
+    assert_eq!(src, "export const a = 1;\n");
+}
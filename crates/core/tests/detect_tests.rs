@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use resourcer_core::detect::{ChunkPlan, Detection, DetectorError, DetectorRegistry, PageContext, RuntimeDetector};
+use resourcer_core::LocalBundle;
+use url::Url;
+
+struct Fixed(&'static str, f32);
+
+#[async_trait]
+impl RuntimeDetector for Fixed {
+    fn name(&self) -> &'static str {
+        self.0
+    }
+
+    fn detect(&self, _page: &PageContext) -> Option<Detection> {
+        Some(Detection { confidence: self.1, entry: None })
+    }
+
+    async fn enumerate(&self, _page: &PageContext, _d: &Detection) -> Result<ChunkPlan, DetectorError> {
+        Ok(ChunkPlan::default())
+    }
+}
+
+fn page(html: &str) -> PageContext {
+    PageContext::from_html(Url::parse("https://example.com/").unwrap(), html.to_string())
+}
+
+#[test]
+fn registry_orders_by_confidence() {
+    let mut reg = DetectorRegistry::empty();
+    reg.register(Fixed("low", 0.2)).register(Fixed("high", 0.9)).register(Fixed("tie", 0.2));
+    let names: Vec<_> = reg.detect(&page("")).into_iter().map(|(d, _)| d.name()).collect();
+    assert_eq!(names, vec!["high", "low", "tie"]);
+}
+
+#[test]
+fn local_bundle_rewrites_root_relative_refs() {
+    let bundle = LocalBundle {
+        root: "/build/dist".into(),
+        root_url: Url::parse("file:///build/dist/").unwrap(),
+        index_html: Some(r#"<script type="module" src="/assets/index-1.js"></script>"#.into()),
+        files: vec!["/build/dist/assets/index-1.js".into(), "/build/dist/assets/other.js".into()],
+    };
+    let page = PageContext::for_local_bundle(&bundle);
+    assert_eq!(page.page_url.as_str(), "file:///build/dist/index.html");
+    let scripts: Vec<_> = page.scripts.iter().map(|u| u.as_str()).collect();
+    assert_eq!(scripts, vec!["file:///build/dist/assets/index-1.js", "file:///build/dist/assets/other.js"]);
+}
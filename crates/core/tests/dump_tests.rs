@@ -0,0 +1,177 @@
+mod common;
+
+use common::Listed;
+use httpmock::prelude::*;
+use resourcer_core::{
+    ChannelObserver, ChunkStatus, DetectorRegistry, DumpEvent, DumpOptions, DumpTarget, Dumper, RetryPolicy,
+};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn remote_dump_downloads_and_reconstructs() {
+    let server = MockServer::start_async().await;
+    server.mock(|when, then| {
+        when.method(GET).path("/");
+        then.status(200).body("<html></html>");
+    });
+    server.mock(|when, then| {
+        when.path("/a.js");
+        then.status(200).body("var a;\n//# sourceMappingURL=a.js.map");
+    });
+    server.mock(|when, then| {
+        when.path("/a.js.map");
+        then.status(200).body(r#"{"version":3,"sources":["src/a.ts"],"sourcesContent":["let a"],"mappings":""}"#);
+    });
+    server.mock(|when, then| {
+        when.path("/gone.js");
+        then.status(404);
+    });
+
+    let out = tempfile::tempdir().unwrap();
+    let options = DumpOptions {
+        out_dir: Some(out.path().to_path_buf()),
+        concurrency: 1,
+        retry: RetryPolicy::with_attempts(1),
+        validate_urls: false,
+        ..Default::default()
+    };
+    let dumper = Dumper::new(Listed::new(&["a.js", "gone.js"]).registry()).with_options(options);
+    let res = dumper.run(&DumpTarget::Url(server.url("/"))).await.unwrap();
+
+    assert_eq!(res.out_dir.as_deref(), Some(out.path()));
+    assert_eq!(res.stats.declared_maps, 1);
+    assert_eq!(std::fs::read_to_string(out.path().join("src/a.ts")).unwrap(), "let a");
+    assert_eq!(res.report.runtime.as_ref().unwrap().strategy, "fixed");
+    let statuses: Vec<ChunkStatus> = res.report.chunks.iter().map(|c| c.status).collect();
+    assert_eq!(statuses, vec![ChunkStatus::Saved, ChunkStatus::DownloadFailed]);
+    assert!(res.ledger_path.is_some());
+}
+
+#[tokio::test]
+async fn dry_run_only_plans() {
+    let server = MockServer::start_async().await;
+    server.mock(|when, then| {
+        when.path("/");
+        then.status(200).body("<html></html>");
+    });
+
+    let options = DumpOptions { dry_run: true, ..Default::default() };
+    let dumper = Dumper::new(Listed::new(&["x.js", "y.js"]).registry()).with_options(options);
+    let res = dumper.run(&DumpTarget::Url(server.url("/"))).await.unwrap();
+
+    assert_eq!(res.planned.len(), 2);
+    assert!(res.out_dir.is_none());
+    assert!(res.report.chunks.is_empty());
+}
+
+#[tokio::test]
+async fn url_dumps_do_not_read_local_files() {
+    let secrets = tempfile::tempdir().unwrap();
+    std::fs::write(secrets.path().join("x.js"), "SECRET").unwrap();
+    std::fs::write(secrets.path().join("x.js.map"), r#"{"version":3,"sources":["id_rsa"],"sourcesContent":["SECRET"],"mappings":""}"#).unwrap();
+    let script: &'static str = url::Url::from_file_path(secrets.path().join("x.js")).unwrap().to_string().leak();
+
+    let server = MockServer::start_async().await;
+    server.mock(|when, then| {
+        when.path("/");
+        then.status(200).body("<html></html>");
+    });
+    server.mock(|when, then| {
+        when.path("/app.js");
+        then.status(200).header("SourceMap", format!("{script}.map")).body("var a;");
+    });
+
+    let out = tempfile::tempdir().unwrap();
+    let options = DumpOptions { validate_urls: false, ..common::options(out.path()) };
+    let res = common::dump(Listed::new(&["app.js", script]), options, None, DumpTarget::Url(server.url("/"))).await;
+    let chunks: Vec<&str> = res.report.chunks.iter().map(|c| c.url.as_str()).collect();
+    assert_eq!(chunks, [server.url("/app.js")]);
+    assert_eq!(res.stats.declared_maps, 0);
+    for entry in walkdir::WalkDir::new(out.path()).into_iter().filter_map(Result::ok).filter(|e| e.file_type().is_file()) {
+        assert!(!std::fs::read_to_string(entry.path()).unwrap().contains("SECRET"), "{}", entry.path().display());
+    }
+}
+
+#[tokio::test]
+async fn local_dump_without_detectors() {
+    let input = tempfile::tempdir().unwrap();
+    std::fs::write(input.path().join("app.js"), "var b;\n//# sourceMappingURL=app.js.map").unwrap();
+    std::fs::write(
+        input.path().join("app.js.map"),
+        r#"{"version":3,"sources":["webpack:///./src/b.js"],"sourcesContent":["let b"],"mappings":""}"#,
+    )
+    .unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    let options = DumpOptions { out_dir: Some(out.path().to_path_buf()), ..Default::default() };
+    let (observer, mut rx) = ChannelObserver::new();
+    let dumper = Dumper::new(DetectorRegistry::empty()).with_options(options).with_observer(Arc::new(observer));
+    let res = dumper.run(&DumpTarget::Local(input.path().to_path_buf())).await.unwrap();
+    drop(dumper);
+
+    assert_eq!(res.report.summary().chunks_saved, 1);
+    assert_eq!(std::fs::read_to_string(out.path().join("src/b.js")).unwrap(), "let b");
+
+    // Every event about the file, and the report, name it by the same file:// URL.
+    let file_url = url::Url::from_file_path(input.path().join("app.js")).unwrap().to_string();
+    let mut urls = Vec::new();
+    while let Some(e) = rx.recv().await {
+        match e {
+            DumpEvent::ChunkDiscovered { url } | DumpEvent::DownloadStarted { url } | DumpEvent::DownloadFinished { url, .. } => urls.push(url),
+            _ => {}
+        }
+    }
+    assert_eq!(urls, vec![file_url.clone(); 3]);
+    assert_eq!(res.report.chunks[0].url, file_url);
+}
+
+#[tokio::test]
+async fn observers_receive_pipeline_events() {
+    let server = MockServer::start_async().await;
+    server.mock(|when, then| {
+        when.path("/");
+        then.status(200).body("<html></html>");
+    });
+    server.mock(|when, then| {
+        when.path("/a.js");
+        then.status(200).body("var a;\n//# sourceMappingURL=a.js.map");
+    });
+    server.mock(|when, then| {
+        when.path("/a.js.map");
+        then.status(200).body(r#"{"version":3,"sources":["src/a.ts"],"sourcesContent":["let a"],"mappings":""}"#);
+    });
+    server.mock(|when, then| {
+        when.path("/b.js");
+        then.status(200).body("var b;\n//# sourceMappingURL=missing.js.map");
+    });
+    server.mock(|when, then| {
+        when.path("/missing.js.map");
+        then.status(404);
+    });
+
+    let out = tempfile::tempdir().unwrap();
+    let options = DumpOptions {
+        out_dir: Some(out.path().to_path_buf()),
+        concurrency: 2,
+        retry: RetryPolicy::with_attempts(1),
+        validate_urls: false,
+        ..Default::default()
+    };
+    let (observer, mut rx) = ChannelObserver::new();
+    let dumper = Dumper::new(Listed::new(&["a.js", "b.js"]).registry()).with_options(options).with_observer(Arc::new(observer));
+    dumper.run(&DumpTarget::Url(server.url("/"))).await.unwrap();
+    drop(dumper);
+
+    let mut events = Vec::new();
+    while let Some(e) = rx.recv().await {
+        events.push(e);
+    }
+    let count = |f: fn(&DumpEvent) -> bool| events.iter().filter(|e| f(e)).count();
+    assert!(matches!(events[0], DumpEvent::RuntimeDetected { .. }));
+    assert_eq!(count(|e| matches!(e, DumpEvent::ChunkDiscovered { .. })), 2);
+    assert_eq!(count(|e| matches!(e, DumpEvent::DownloadStarted { .. })), 2);
+    assert_eq!(count(|e| matches!(e, DumpEvent::DownloadFinished { error: None, .. })), 2);
+    assert_eq!(count(|e| matches!(e, DumpEvent::SourcemapParsed { sources: 1, .. })), 1);
+    assert!(events.iter().any(|e| matches!(e, DumpEvent::FileWritten { path, .. } if path.ends_with("src/a.ts"))));
+    assert_eq!(count(|e| matches!(e, DumpEvent::Warning { .. })), 1);
+}
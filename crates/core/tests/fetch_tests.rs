@@ -1,4 +1,4 @@
-use resourcer_core::{fetch_html, download_many, DownloadManagerConfig};
+use resourcer_core::{fetch_html, download_many, DownloadManagerConfig, RetryPolicy};
 use httpmock::prelude::*;
 
 #[tokio::test]
@@ -11,7 +11,7 @@ async fn fetch_html_success() {
             .body("<html>hello</html>");
     });
 
-    let url = format!("{}", server.base_url());
+    let url = server.base_url();
     let body = fetch_html(&url).await.unwrap();
     m.assert();
     assert!(body.contains("hello"));
@@ -46,7 +46,7 @@ async fn concurrent_download_many() {
         when.method(GET).path("/b");
         then.status(200).body("B");
     });
-    let fail = server.mock(|when, then| {
+    let _fail = server.mock(|when, then| {
         when.method(GET).path("/fail");
         then.status(500);
     });
@@ -56,23 +56,38 @@ async fn concurrent_download_many() {
         format!("{}/fail", server.base_url()),
         "http://invalid-url".to_string(),
     ];
-    let config = DownloadManagerConfig { concurrency: 2, retry_attempts: 2 };
+    let config = DownloadManagerConfig { concurrency: 2, retry: RetryPolicy::with_attempts(2), ..Default::default() };
     let results = download_many(urls, config).await;
     assert_eq!(results.len(), 4);
     let mut ok_count = 0;
     let mut fail_count = 0;
     for r in results {
-        match (r.content, r.error) {
-            (Some(body), None) => {
-                assert!(body == "A" || body == "B");
+        match r.response {
+            Ok(resp) => {
+                assert!(resp.body == "A" || resp.body == "B");
                 ok_count += 1;
             }
-            (None, Some(_)) => {
+            Err(_) => {
                 fail_count += 1;
             }
-            _ => panic!("Unexpected result variant"),
         }
     }
     assert_eq!(ok_count, 2);
     assert_eq!(fail_count, 2);
-} 
\ No newline at end of file
+    ok1.assert();
+    ok2.assert();
+}
+
+#[tokio::test]
+async fn fetch_response_keeps_headers() {
+    let server = MockServer::start_async().await;
+    server.mock(|when, then| {
+        when.method(GET).path("/app.js");
+        then.status(200).header("SourceMap", "app.js.map").body("x");
+    });
+
+    let resp = resourcer_core::fetch_response(&server.url("/app.js")).await.unwrap();
+    assert_eq!(resp.status, 200);
+    assert_eq!(resp.header("sourcemap"), Some("app.js.map"));
+    assert_eq!(resp.body, "x");
+}
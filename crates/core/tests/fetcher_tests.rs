@@ -0,0 +1,62 @@
+mod common;
+
+use common::Listed;
+use resourcer_core::{CompositeFetcher, DataUrlFetcher, DumpTarget, FetchError, Fetcher, FileFetcher, HttpClient, MemoryFetcher};
+use std::sync::Arc;
+use url::Url;
+
+#[tokio::test]
+async fn composite_dispatches_on_scheme() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a.js");
+    std::fs::write(&path, "from disk").unwrap();
+    let memory = Arc::new(MemoryFetcher::new());
+    memory.insert(&Url::parse("mem://host/x.js").unwrap(), "from memory");
+
+    let mut fetcher = CompositeFetcher::with_builtin(HttpClient::shared());
+    fetcher.register("mem", memory);
+
+    let file_url = Url::from_file_path(&path).unwrap();
+    assert_eq!(fetcher.fetch_text(&file_url, 1).await.unwrap(), "from disk");
+    let data = Url::parse("data:application/json;base64,eyJhIjoxfQ==").unwrap();
+    assert_eq!(fetcher.fetch_text(&data, 1).await.unwrap(), r#"{"a":1}"#);
+    assert_eq!(fetcher.fetch_text(&Url::parse("mem://host/x.js").unwrap(), 1).await.unwrap(), "from memory");
+
+    let err = fetcher.fetch(&Url::parse("ftp://example.com/a.js").unwrap()).await.unwrap_err();
+    assert!(matches!(err, FetchError::UnsupportedScheme(s) if s == "ftp"));
+    assert_eq!(fetcher.schemes(), ["data", "file", "http", "https", "mem"]);
+}
+
+#[tokio::test]
+async fn builtin_backends_report_existence_and_media_type() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("a.js");
+    std::fs::write(&path, "x").unwrap();
+    assert!(FileFetcher.exists(&Url::from_file_path(&path).unwrap()).await);
+    assert!(!FileFetcher.exists(&Url::from_file_path(dir.path().join("missing.js")).unwrap()).await);
+
+    let resp = DataUrlFetcher.fetch(&Url::parse("data:text/css,a%7Bb%3Ac%7D").unwrap()).await.unwrap();
+    assert_eq!(resp.body, "a{b:c}");
+    assert_eq!(resp.header("content-type"), Some("text/css"));
+}
+
+#[tokio::test]
+async fn dumper_runs_entirely_from_an_in_memory_fetcher() {
+    let site = Url::parse("https://site.test/").unwrap();
+    let memory = MemoryFetcher::new();
+    memory
+        .insert(&site, "<html></html>")
+        .insert(&site.join("runtime.js").unwrap(), "app.js")
+        .insert(&site.join("app.js").unwrap(), "var a;\n//# sourceMappingURL=app.js.map")
+        .insert(
+            &site.join("app.js.map").unwrap(),
+            r#"{"version":3,"sources":["src/app.ts"],"sourcesContent":["let a"],"mappings":""}"#,
+        );
+
+    let out = tempfile::tempdir().unwrap();
+    let detector = Listed::from_runtime("runtime.js");
+    let res = common::dump(detector, common::options(out.path()), Some(Arc::new(memory)), DumpTarget::Url(site.to_string())).await;
+    assert_eq!(res.planned, vec![site.join("app.js").unwrap()]);
+    assert_eq!(res.stats.declared_maps, 1);
+    assert_eq!(std::fs::read_to_string(out.path().join("src/app.ts")).unwrap(), "let a");
+}
@@ -0,0 +1,95 @@
+use base64::Engine;
+use httpmock::prelude::*;
+use resourcer_core::{DetectorRegistry, DumpOptions, DumpTarget, Dumper, FetchError, Fetcher, HarArchive, MapStatus};
+use serde_json::{json, Value};
+use url::Url;
+
+fn entry(url: &str, status: u16, mime: &str, text: Option<&str>, base64: bool) -> Value {
+    let mut content = json!({ "size": 0, "mimeType": mime });
+    if let Some(t) = text {
+        content["text"] = if base64 { base64::engine::general_purpose::STANDARD.encode(t).into() } else { t.into() };
+        if base64 {
+            content["encoding"] = "base64".into();
+        }
+    }
+    json!({
+        "request": { "method": "GET", "url": url, "headers": [] },
+        "response": {
+            "status": status,
+            "headers": [{ "name": ":status", "value": status.to_string() }, { "name": "content-type", "value": mime }],
+            "content": content,
+        },
+    })
+}
+
+fn har(entries: Vec<Value>) -> String {
+    json!({ "log": { "version": "1.2", "creator": { "name": "test" }, "entries": entries } }).to_string()
+}
+
+const MAP: &str = r#"{"version":3,"sources":["src/app.ts"],"sourcesContent":["let a"],"mappings":""}"#;
+
+#[tokio::test]
+async fn parses_entries_and_serves_decoded_bodies() {
+    let archive = HarArchive::parse(&har(vec![
+        entry("https://site.test/", 200, "text/html", Some("<html></html>"), false),
+        entry("https://site.test/app.js", 200, "application/javascript", Some("var a;"), true),
+        entry("https://site.test/app.js", 304, "application/javascript", None, false),
+        entry("https://site.test/style.css", 200, "text/css", Some("a{}"), false),
+        entry("https://site.test/gone.js", 404, "text/plain", Some("nope"), false),
+        entry("https://site.test/logo.png", 200, "image/png", None, false),
+    ]))
+    .unwrap();
+
+    assert_eq!(archive.page_url().unwrap().as_str(), "https://site.test/");
+    let assets: Vec<String> = archive.assets().iter().map(Url::to_string).collect();
+    assert_eq!(assets, ["https://site.test/app.js", "https://site.test/style.css"]);
+
+    let fetcher = archive.into_fetcher(None);
+    let app = fetcher.fetch(&Url::parse("https://site.test/app.js").unwrap()).await.unwrap();
+    assert_eq!(app.body, "var a;");
+    assert_eq!(app.header("content-type"), Some("application/javascript"));
+    let gone = fetcher.fetch(&Url::parse("https://site.test/gone.js").unwrap()).await;
+    assert!(matches!(gone, Err(FetchError::HttpStatus(404))));
+    let other = fetcher.fetch(&Url::parse("https://site.test/other.js").unwrap()).await;
+    assert!(matches!(other, Err(FetchError::NotRecorded(_))));
+}
+
+#[tokio::test]
+async fn dumps_capture_offline_and_falls_back_to_network_when_allowed() {
+    let server = MockServer::start_async().await;
+    let missing_map = server.mock(|when, then| {
+        when.path("/vendor.js.map");
+        then.status(200).body(r#"{"version":3,"sources":["src/vendor.ts"],"sourcesContent":["let v"],"mappings":""}"#);
+    });
+    let page = server.url("/");
+    let capture = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        capture.path(),
+        har(vec![
+            entry(&page, 200, "text/html", Some("<html></html>"), false),
+            entry(&server.url("/app.js"), 200, "application/javascript", Some("var a;\n//# sourceMappingURL=app.js.map"), true),
+            entry(&server.url("/app.js.map"), 200, "application/json", Some(MAP), false),
+            entry(&server.url("/vendor.js"), 200, "text/javascript", Some("var v;\n//# sourceMappingURL=vendor.js.map"), false),
+        ]),
+    )
+    .unwrap();
+
+    let run = |network_fallback: bool, out: std::path::PathBuf| {
+        let options = DumpOptions { out_dir: Some(out), concurrency: 1, network_fallback, ..Default::default() };
+        let dumper = Dumper::new(DetectorRegistry::empty()).with_options(options);
+        let target = DumpTarget::Har(capture.path().to_path_buf());
+        async move { dumper.run(&target).await.unwrap() }
+    };
+
+    let offline = tempfile::tempdir().unwrap();
+    let res = run(false, offline.path().to_path_buf()).await;
+    assert_eq!(res.planned.len(), 2);
+    assert_eq!(std::fs::read_to_string(offline.path().join("src/app.ts")).unwrap(), "let a");
+    assert!(res.report.maps.iter().any(|m| m.url.ends_with("/vendor.js.map") && m.status == MapStatus::Missing));
+    missing_map.assert_hits(0);
+
+    let online = tempfile::tempdir().unwrap();
+    run(true, online.path().to_path_buf()).await;
+    assert_eq!(std::fs::read_to_string(online.path().join("src/vendor.ts")).unwrap(), "let v");
+    missing_map.assert_hits(1);
+}
@@ -0,0 +1,116 @@
+use httpmock::prelude::*;
+use resourcer_core::{
+    download_many, validate_chunk_urls_with, DownloadManagerConfig, FetchError, Fetcher, HttpClient, HttpConfig,
+    HttpFetcher, Validators,
+};
+use std::sync::Arc;
+use url::Url;
+
+fn client(config: HttpConfig) -> HttpClient {
+    HttpClient::new(config).unwrap()
+}
+
+#[tokio::test]
+async fn sends_user_agent_headers_and_cookies() {
+    let server = MockServer::start_async().await;
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/")
+            .header("user-agent", "resourcer-test")
+            .header("x-token", "abc")
+            .header("cookie", "session=42");
+        then.status(200).body("ok");
+    });
+
+    let base = Url::parse(&server.base_url()).unwrap();
+    let http = client(HttpConfig {
+        user_agent: "resourcer-test".into(),
+        headers: vec![("X-Token".into(), "abc".into())],
+        cookies: vec![(base.clone(), "session=42".into())],
+        ..Default::default()
+    });
+    let resp = http.fetch_response(base.as_str()).await.unwrap();
+    m.assert();
+    assert_eq!(resp.body, "ok");
+}
+
+#[tokio::test]
+async fn redirects_follow_the_configured_limit() {
+    let server = MockServer::start_async().await;
+    server.mock(|when, then| {
+        when.method(GET).path("/old");
+        then.status(302).header("Location", "/new");
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/new");
+        then.status(200).body("moved");
+    });
+
+    let followed = client(HttpConfig::default()).fetch_response(&server.url("/old")).await.unwrap();
+    assert_eq!(followed.body, "moved");
+    assert!(followed.url.path().ends_with("/new"));
+
+    let none = client(HttpConfig { max_redirects: 0, ..Default::default() });
+    let err = none.fetch_response(&server.url("/old")).await.unwrap_err();
+    assert!(matches!(err, FetchError::HttpStatus(302)));
+}
+
+#[tokio::test]
+async fn validation_and_downloads_use_the_client() {
+    let server = MockServer::start_async().await;
+    let head = server.mock(|when, then| {
+        when.method(httpmock::Method::HEAD).path("/a.js").header("user-agent", "resourcer-test");
+        then.status(200);
+    });
+    let get = server.mock(|when, then| {
+        when.method(GET).path("/a.js").header("user-agent", "resourcer-test");
+        then.status(200).body("a");
+    });
+
+    let http = HttpFetcher::new(client(HttpConfig { user_agent: "resourcer-test".into(), ..Default::default() }));
+    let live = validate_chunk_urls_with(&http, [Url::parse(&server.url("/a.js")).unwrap()]).await;
+    head.assert();
+    assert_eq!(live.len(), 1);
+
+    let cfg = DownloadManagerConfig { fetcher: Arc::new(http), ..Default::default() };
+    let results = download_many(vec![server.url("/a.js")], cfg).await;
+    get.assert();
+    assert_eq!(results[0].content(), Some("a"));
+}
+
+#[test]
+fn invalid_configuration_is_rejected() {
+    let bad_header = HttpClient::new(HttpConfig { headers: vec![("bad header".into(), "x".into())], ..Default::default() });
+    assert!(matches!(bad_header, Err(FetchError::Config(_))));
+
+    let missing_ca = HttpClient::new(HttpConfig { ca_bundle: Some("/nonexistent/ca.pem".into()), ..Default::default() });
+    assert!(matches!(missing_ca, Err(FetchError::Config(_))));
+}
+
+#[tokio::test]
+async fn conditional_requests_expose_not_modified() {
+    let server = MockServer::start_async().await;
+    let unchanged = server.mock(|when, then| {
+        when.method(GET).path("/app.js").header("if-none-match", "\"v1\"");
+        then.status(304);
+    });
+    let full = server.mock(|when, then| {
+        when.method(GET).path("/app.js").matches(|req| !req.headers.iter().flatten().any(|(k, _)| k.eq_ignore_ascii_case("if-none-match")));
+        then.status(200).header("etag", "\"v1\"").header("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT").body("var a;");
+    });
+    let url = Url::parse(&server.url("/app.js")).unwrap();
+    let fetcher = HttpFetcher::new(HttpClient::shared());
+
+    let first = fetcher.fetch_conditional(&url, &Validators::default()).await.unwrap();
+    assert_eq!((first.status, first.body.as_str()), (200, "var a;"));
+    let validators = first.validators();
+    assert_eq!(validators.last_modified.as_deref(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+
+    let second = fetcher.fetch_conditional(&url, &validators).await.unwrap();
+    assert!(second.is_not_modified());
+    assert!(second.body.is_empty());
+    // A plain fetch still treats anything but 2xx as an error.
+    assert!(fetcher.fetch(&url).await.is_ok());
+    full.assert_hits(2);
+    unchanged.assert_hits(1);
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use std::io::Write;
+use std::sync::Arc;
+
+use common::Listed;
+use resourcer_core::{
+    ConflictPolicy, DumpOptions, DumpTarget, Dumper, JournalState, MemoryFetcher, RetryPolicy, RunJournal, SaveOptions,
+    SourceLedger, JOURNAL_FILE, LEDGER_FILE,
+};
+use url::Url;
+
+const PAGE: &str = "https://site.test/";
+
+fn url(path: &str) -> Url {
+    Url::parse(PAGE).unwrap().join(path).unwrap()
+}
+
+fn with_a(fetcher: &MemoryFetcher) {
+    fetcher
+        .insert(&url("a.js"), "var a;\n//# sourceMappingURL=a.js.map")
+        .insert(&url("a.js.map"), r#"{"version":3,"sources":["src/a.ts"],"sourcesContent":["let a"],"mappings":""}"#);
+}
+
+/// Dump `/a.js` and `/b.js` of [`PAGE`] from `fetcher` into `out`.
+async fn dump(fetcher: MemoryFetcher, out: &std::path::Path, resume: bool) -> resourcer_core::DumpResult {
+    let options = DumpOptions { retry: RetryPolicy::with_attempts(1), validate_urls: false, resume, ..common::options(out) };
+    common::dump(Listed::new(&["a.js", "b.js"]), options, Some(Arc::new(fetcher)), DumpTarget::Url(PAGE.into())).await
+}
+
+#[tokio::test]
+async fn resume_skips_saved_chunks_and_retries_the_rest() {
+    let out = tempfile::tempdir().unwrap();
+
+    // First run: b.js is unavailable.
+    let first = MemoryFetcher::new();
+    first.insert(&url(""), "<html></html>");
+    with_a(&first);
+    let res = dump(first, out.path(), false).await;
+    assert_eq!(res.report.summary().chunks_saved, 1);
+    let journal = RunJournal::open(out.path()).unwrap().unwrap();
+    assert_eq!(journal.plan().unwrap().1, [url("a.js"), url("b.js")]);
+    assert_eq!(journal.chunk(url("b.js").as_str()).unwrap().state, JournalState::Failed);
+    let a = journal.chunk(url("a.js").as_str()).unwrap();
+    assert!(a.files.iter().any(|f| f.path == "src/a.ts"));
+
+    // Resumed run: neither the page nor a.js are served, so fetching them again would fail.
+    let second = MemoryFetcher::new();
+    second.insert(&url("b.js"), "var b;");
+    let res = dump(second, out.path(), true).await;
+    assert_eq!(res.skipped, [url("a.js")]);
+    assert_eq!(res.report.summary().chunks_saved, 1);
+    assert_eq!(std::fs::read_to_string(out.path().join("b.js")).unwrap(), "var b;");
+
+    // A file that changed on disk sends its chunk back through the pipeline.
+    std::fs::write(out.path().join("src/a.ts"), "edited").unwrap();
+    let third = MemoryFetcher::new();
+    with_a(&third);
+    let res = dump(third, out.path(), true).await;
+    assert_eq!(res.skipped, [url("b.js")]);
+    assert_eq!(std::fs::read_to_string(out.path().join("src/a.ts")).unwrap(), "let a");
+}
+
+/// A chunk at `path` whose sourcemap holds `src/shared.ts` with `contents`.
+fn with_shared(fetcher: &MemoryFetcher, path: &str, contents: &str) {
+    let map = format!(r#"{{"version":3,"sources":["src/shared.ts"],"sourcesContent":["{contents}"],"mappings":""}}"#);
+    fetcher.insert(&url(path), format!("var x;\n//# sourceMappingURL={path}.map")).insert(&url(&format!("{path}.map")), map);
+}
+
+#[tokio::test]
+async fn resumed_runs_keep_the_ledger_of_the_first() {
+    let out = tempfile::tempdir().unwrap();
+    let run = |fetcher: MemoryFetcher, resume: bool| {
+        let options = DumpOptions { retry: RetryPolicy::with_attempts(1), validate_urls: false, resume, ..common::options(out.path()) };
+        let save = SaveOptions { ledger: Arc::new(SourceLedger::new(ConflictPolicy::KeepFirst)), ..Default::default() };
+        Dumper::new(Listed::new(&["a.js", "b.js"]).registry()).with_options(options).with_save_options(save).with_fetcher(Arc::new(fetcher))
+    };
+
+    // First run: b.js is unavailable.
+    let first = MemoryFetcher::new();
+    first.insert(&url(""), "<html></html>");
+    with_shared(&first, "a.js", "from a");
+    run(first, false).run(&DumpTarget::Url(PAGE.into())).await.unwrap();
+
+    // The resumed run brings a different src/shared.ts, which keep-first discards.
+    let second = MemoryFetcher::new();
+    with_shared(&second, "b.js", "from b");
+    let res = run(second, true).run(&DumpTarget::Url(PAGE.into())).await.unwrap();
+    assert_eq!(res.skipped, [url("a.js")]);
+    assert_eq!(res.stats.conflicting_sources, 1);
+    assert_eq!(std::fs::read_to_string(out.path().join("src/shared.ts")).unwrap(), "from a");
+
+    let ledger: serde_json::Value = serde_json::from_slice(&std::fs::read(out.path().join(LEDGER_FILE)).unwrap()).unwrap();
+    let shared = ledger.as_array().unwrap().iter().find(|r| r["path"] == "src/shared.ts").unwrap();
+    assert_eq!(shared["versions"][0]["contributors"], serde_json::json!([url("a.js").as_str()]));
+    assert_eq!(shared["versions"][0]["written_to"], "src/shared.ts");
+    assert_eq!(shared["versions"][1]["contributors"], serde_json::json!([url("b.js").as_str()]));
+    assert!(shared["versions"][1]["written_to"].is_null());
+}
+
+#[tokio::test]
+async fn resume_without_journal_starts_over() {
+    let out = tempfile::tempdir().unwrap();
+    let fetcher = MemoryFetcher::new();
+    fetcher.insert(&url(""), "<html></html>").insert(&url("b.js"), "var b;");
+    with_a(&fetcher);
+    let res = dump(fetcher, out.path(), true).await;
+    assert!(res.skipped.is_empty());
+    assert_eq!(res.report.summary().chunks_saved, 2);
+    assert!(out.path().join(JOURNAL_FILE).is_file());
+}
+
+#[test]
+fn ignores_a_truncated_last_line() {
+    let out = tempfile::tempdir().unwrap();
+    let mut journal = RunJournal::create(out.path()).unwrap();
+    journal.record_plan(PAGE, &[url("a.js")]).unwrap();
+    journal.record_chunk(url("a.js").as_str(), JournalState::Saved, Some("var a;"));
+    drop(journal);
+    let mut file = std::fs::OpenOptions::new().append(true).open(out.path().join(JOURNAL_FILE)).unwrap();
+    file.write_all(br#"{"record":"chunk","url":"https://site.test/b.js","sta"#).unwrap();
+
+    let journal = RunJournal::open(out.path()).unwrap().unwrap();
+    assert_eq!(journal.plan().unwrap().0, PAGE);
+    assert!(journal.is_done(url("a.js").as_str()));
+    assert!(journal.chunk(url("b.js").as_str()).is_none());
+}
@@ -12,7 +12,7 @@ async fn analyze_and_store() {
 
     let doc = analyze_local_js(&path, &store).await.unwrap();
     let retrieved = store.get(&doc.url).await.unwrap();
-    assert_eq!(retrieved.content, "console.log('stored');");
+    assert_eq!(retrieved.text(), "console.log('stored');");
 }
 
 #[tokio::test]
@@ -26,4 +26,34 @@ async fn analyze_and_detect_sourcemaps() {
     let (_doc, maps) = resourcer_core::local_analysis::analyze_local_js_with_sourcemaps(&path, &store).await.unwrap();
     assert_eq!(maps.len(), 1);
     assert!(maps[0].as_str().ends_with("map1.js.map"));
-} 
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn collect_bundle_from_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("assets")).unwrap();
+    std::fs::write(dir.path().join("index.html"), "<html></html>").unwrap();
+    std::fs::write(dir.path().join("assets/app.js"), "console.log(1)").unwrap();
+    std::fs::write(dir.path().join("assets/app.css"), "body{}").unwrap();
+    std::fs::write(dir.path().join("assets/app.js.map"), "{}").unwrap();
+
+    let bundle = resourcer_core::collect_local_bundle(dir.path()).await.unwrap();
+    let root = std::fs::canonicalize(dir.path()).unwrap();
+    assert_eq!(bundle.root, root);
+    assert_eq!(bundle.index_html.as_deref(), Some("<html></html>"));
+    assert_eq!(bundle.files, vec![root.join("assets/app.css"), root.join("assets/app.js")]);
+    assert_eq!(bundle.script_urls().len(), 1);
+    assert!(bundle.root_url.as_str().ends_with('/'));
+}
+
+#[tokio::test]
+async fn collect_bundle_from_single_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("main.js");
+    fs::write(&path, b"console.log('x');").await.unwrap();
+
+    let bundle = resourcer_core::collect_local_bundle(&path).await.unwrap();
+    assert_eq!(bundle.files, vec![std::fs::canonicalize(&path).unwrap()]);
+    assert_eq!(bundle.root, std::fs::canonicalize(dir.path()).unwrap());
+    assert!(bundle.index_html.is_none());
+}
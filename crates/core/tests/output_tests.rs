@@ -1,7 +1,6 @@
 use std::fs;
 use tempfile::tempdir;
 use resourcer_core::{ensure_output_dir, mirror_structure, copy_files, validate_output};
-use walkdir::WalkDir;
 
 #[test]
 fn creates_missing_directory() {
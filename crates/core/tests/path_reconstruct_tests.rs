@@ -1,4 +1,4 @@
-use resourcer_core::{parse_sourcemap, reconstruct_paths};
+use resourcer_core::{parse_sourcemap, reconstruct_paths, reconstruct_paths_with, SourceNormalizer, SourcePathOptions};
 use tempfile::tempdir;
 
 #[test]
@@ -13,7 +13,21 @@ fn webpack_protocol_and_source_root() {
 
     let paths = reconstruct_paths(dir.path(), &sm);
     let expected1 = dir.path().join("src/foo/bar.js");
-    let expected2 = dir.path().join("src/baz.js");
+    let expected2 = dir.path().join("src/namespace/baz.js");
 
     assert_eq!(paths, vec![expected1, expected2]);
-} 
\ No newline at end of file
+}
+
+#[test]
+fn flat_namespaces_option() {
+    let json = r#"{
+        "version":3,
+        "sources":["webpack://namespace/./baz.js","file:///home/ci/build/src/a.ts"]
+    }"#;
+    let sm = parse_sourcemap(json).unwrap();
+    let dir = tempdir().unwrap();
+    let opts = SourcePathOptions { namespace_dirs: false, ..Default::default() };
+
+    let paths = reconstruct_paths_with(dir.path(), &sm, &SourceNormalizer::with_builtin(opts));
+    assert_eq!(paths, vec![dir.path().join("baz.js"), dir.path().join("src/a.ts")]);
+}
@@ -0,0 +1,100 @@
+use resourcer_core::{
+    save_asset_with_maps, save_js_and_sources, url_label, ChunkStatus, CLIError, DumpReport, FetchError, MapStatus, ReportFormat, SaveOptions,
+};
+use std::path::Path;
+use tempfile::tempdir;
+use url::Url;
+
+fn data_map(json: &str) -> Url {
+    let encoded = percent_encoding::utf8_percent_encode(json, percent_encoding::NON_ALPHANUMERIC);
+    Url::parse(&format!("data:application/json,{encoded}")).unwrap()
+}
+
+#[tokio::test]
+async fn saved_chunk_records_maps_and_files() {
+    let dir = tempdir().unwrap();
+    let good = data_map(r#"{"version":3,"sources":["webpack://app/./src/a.ts"],"sourcesContent":["export {}"],"mappings":""}"#);
+    let broken = data_map("not a sourcemap");
+    let missing = Url::from_file_path(dir.path().join("nope.js.map")).unwrap();
+    let maps = vec![good.clone(), broken.clone(), missing.clone()];
+    let outcome = save_asset_with_maps("var a;", Path::new("main.js"), maps, dir.path(), &SaveOptions::default()).await.unwrap();
+    assert_eq!(outcome.unparseable_maps, vec![broken]);
+    assert_eq!(outcome.missing_maps, vec![missing]);
+
+    let mut report = DumpReport::new("https://example.com/");
+    report.set_runtime("webpack", 0.9, "runtime-ast");
+    report.record_saved("https://example.com/main.js", Some(200), "var a;", &outcome);
+
+    assert_eq!(report.chunks[0].status, ChunkStatus::Saved);
+    assert_eq!(report.chunks[0].size, Some(6));
+    assert_eq!(report.chunks[0].sha256.as_deref().map(str::len), Some(64));
+    let statuses: Vec<MapStatus> = report.maps.iter().map(|m| m.status).collect();
+    assert_eq!(statuses, vec![MapStatus::Loaded, MapStatus::Missing, MapStatus::Unparseable]);
+    assert_eq!(report.files[0].path, "app/src/a.ts");
+    assert_eq!(report.files[0].map, url_label(&good));
+    assert_eq!(report.files[0].action, "written");
+
+    let summary = report.summary();
+    assert_eq!((summary.chunks_saved, summary.maps_loaded, summary.files_written), (1, 1, 1));
+
+    report.record_saved("https://example.com/cached.js", Some(304), "var c;", &Default::default());
+    assert_eq!((report.summary().chunks_saved, report.summary().chunks_unchanged), (2, 1));
+}
+
+#[tokio::test]
+async fn inline_maps_are_recorded_by_a_short_label() {
+    use base64::Engine;
+
+    let dir = tempdir().unwrap();
+    let map = r#"{"version":3,"sources":["src/a.ts","src/b.ts"],"sourcesContent":["let a","let b"],"mappings":""}"#;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(map);
+    let body = format!("var a;\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,{encoded}");
+    let outcome = save_js_and_sources(&body, "https://example.com/main.js", dir.path(), &SaveOptions::default()).await.unwrap();
+
+    let mut report = DumpReport::new("https://example.com/");
+    report.record_saved("https://example.com/main.js", Some(200), &body, &outcome);
+    let label = format!("data:application/json;{} bytes", map.len());
+    assert_eq!(report.maps[0].url, label);
+    assert!(report.files.iter().all(|f| f.map == label));
+    assert_eq!(report.files.len(), 2);
+
+    let mut json = Vec::new();
+    report.write(ReportFormat::Json, &mut json).unwrap();
+    assert!(!String::from_utf8(json).unwrap().contains(&encoded));
+}
+
+#[test]
+fn failures_carry_error_kinds() {
+    let mut report = DumpReport::new("https://example.com/");
+    report.record_download_failure("https://example.com/a.js", &FetchError::HttpStatus(404));
+    report.record_processing_failure("https://example.com/b.js", Some(200), &CLIError::Other("boom".into()));
+
+    assert_eq!(report.chunks[0].status, ChunkStatus::DownloadFailed);
+    assert_eq!(report.chunks[0].http_status, Some(404));
+    assert_eq!(report.errors[0].kind, "fetch.http_status");
+    assert_eq!(report.errors[1].kind, "other");
+    assert_eq!(report.summary().chunks_failed, 2);
+}
+
+#[test]
+fn json_and_ndjson_output() {
+    let mut report = DumpReport::new("https://example.com/");
+    report.set_runtime("vite", 0.8, "module-graph");
+    report.record_download_failure("https://example.com/a.js", &FetchError::Timeout);
+
+    let mut json = Vec::new();
+    report.write(ReportFormat::Json, &mut json).unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(v["runtime"]["detector"], "vite");
+    assert_eq!(v["chunks"][0]["status"], "download_failed");
+    assert_eq!(v["summary"]["errors"], 1);
+
+    let mut nd = Vec::new();
+    report.write(ReportFormat::Ndjson, &mut nd).unwrap();
+    let types: Vec<String> = String::from_utf8(nd)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str::<serde_json::Value>(l).unwrap()["type"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(types, vec!["target", "runtime", "chunk", "error", "summary"]);
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use httpmock::prelude::*;
+use common::Listed;
+use resourcer_core::{
+    download_many, parse_retry_after, validate_chunk_urls_with_policy, DownloadManagerConfig, DumpOptions, DumpTarget,
+    FetchError, FetchResponse, Fetcher, HttpClient, HttpFetcher, MemoryFetcher, RetryPolicy,
+};
+use url::Url;
+
+/// Fails the first `failures` requests with `error`, then serves "ok".
+struct Flaky {
+    failures: usize,
+    error: fn() -> FetchError,
+    calls: AtomicUsize,
+}
+
+impl Flaky {
+    fn new(failures: usize, error: fn() -> FetchError) -> Self {
+        Self { failures, error, calls: AtomicUsize::new(0) }
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl Fetcher for Flaky {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            return Err((self.error)());
+        }
+        MemoryFetcher::new().insert(url, "ok").fetch(url).await
+    }
+}
+
+/// Serves `inner`, but answers the first request for `flaky` with `503`.
+struct FlakyMap {
+    inner: MemoryFetcher,
+    flaky: Url,
+    refused: AtomicBool,
+}
+
+#[async_trait]
+impl Fetcher for FlakyMap {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        if *url == self.flaky && !self.refused.swap(true, Ordering::SeqCst) {
+            return Err(FetchError::HttpStatus(503));
+        }
+        self.inner.fetch(url).await
+    }
+}
+
+fn quick(max_attempts: usize) -> RetryPolicy {
+    RetryPolicy { max_attempts, base_delay: Duration::from_millis(1), jitter: false, ..Default::default() }
+}
+
+fn url() -> Url {
+    Url::parse("https://site.test/app.js").unwrap()
+}
+
+#[test]
+fn parses_retry_after_in_both_forms() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_445_412_480); // Wed, 21 Oct 2015 07:28:00 GMT
+    assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+    assert_eq!(parse_retry_after(" 0 ", now), Some(Duration::ZERO));
+    assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now), Some(Duration::from_secs(30)));
+    assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now), Some(Duration::ZERO));
+    assert_eq!(parse_retry_after("soon", now), None);
+}
+
+#[test]
+fn delays_follow_the_policy() {
+    let policy = RetryPolicy { jitter: false, max_delay: Duration::from_secs(1), ..Default::default() };
+    assert_eq!(policy.delay(0, &FetchError::HttpStatus(503)), Some(Duration::from_millis(200)));
+    assert_eq!(policy.delay(1, &FetchError::Timeout), Some(Duration::from_millis(400)));
+    assert_eq!(policy.delay(5, &FetchError::HttpStatus(502)), Some(Duration::from_secs(1)));
+    assert_eq!(policy.delay(0, &FetchError::HttpStatus(404)), None);
+
+    let later = |secs| FetchError::RetryLater { status: 429, retry_after: Duration::from_secs(secs) };
+    assert_eq!(policy.delay(0, &later(1)), Some(Duration::from_secs(1)));
+    assert_eq!(policy.delay(0, &later(60)), None);
+
+    let jittered = RetryPolicy::default().delay(2, &FetchError::Timeout).unwrap();
+    assert!(jittered >= Duration::from_millis(400) && jittered <= Duration::from_millis(800));
+}
+
+#[tokio::test]
+async fn retries_retryable_statuses_only() {
+    let busy = Flaky::new(2, || FetchError::HttpStatus(503));
+    assert_eq!(busy.fetch_with_policy(&url(), &quick(3)).await.unwrap().body, "ok");
+    assert_eq!(busy.calls(), 3);
+
+    let gone = Flaky::new(1, || FetchError::HttpStatus(404));
+    assert!(matches!(gone.fetch_with_policy(&url(), &quick(3)).await, Err(FetchError::HttpStatus(404))));
+    assert_eq!(gone.calls(), 1);
+
+    let down = Flaky::new(5, || FetchError::Timeout);
+    assert!(matches!(down.fetch_with_policy(&url(), &quick(2)).await, Err(FetchError::Timeout)));
+    assert_eq!(down.calls(), 2);
+}
+
+#[tokio::test]
+async fn http_errors_carry_retry_after() {
+    let server = MockServer::start_async().await;
+    server.mock(|when, then| {
+        when.method(GET).path("/busy.js");
+        then.status(429).header("Retry-After", "7");
+    });
+    server.mock(|when, then| {
+        when.method(httpmock::Method::HEAD).path("/busy.js");
+        then.status(503).header("Retry-After", "7");
+    });
+    let http = HttpFetcher::new(HttpClient::shared());
+    let busy = Url::parse(&server.url("/busy.js")).unwrap();
+
+    let err = http.fetch(&busy).await.unwrap_err();
+    assert!(matches!(err, FetchError::RetryLater { status: 429, .. }));
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(7)));
+    let err = http.check(&busy).await.unwrap_err();
+    assert_eq!((err.status(), err.retry_after()), (Some(503), Some(Duration::from_secs(7))));
+
+    // A wait beyond the policy's maximum is not sat out.
+    let policy = RetryPolicy { max_delay: Duration::from_secs(1), ..Default::default() };
+    let err = http.fetch_with_policy(&busy, &policy).await.unwrap_err();
+    assert_eq!(err.status(), Some(429));
+}
+
+#[tokio::test]
+async fn downloads_and_validation_use_the_policy() {
+    let flaky = Arc::new(Flaky::new(1, || FetchError::RetryLater { status: 503, retry_after: Duration::ZERO }));
+    let cfg = DownloadManagerConfig { fetcher: flaky.clone(), retry: quick(2), ..Default::default() };
+    let results = download_many(vec![url().to_string()], cfg).await;
+    assert_eq!(results[0].content(), Some("ok"));
+    assert_eq!(flaky.calls(), 2);
+
+    let flaky = Flaky::new(1, || FetchError::HttpStatus(429));
+    let live = validate_chunk_urls_with_policy(&flaky, [url()], &quick(2)).await;
+    assert_eq!(live, [url()]);
+    let flaky = Flaky::new(1, || FetchError::HttpStatus(429));
+    assert!(validate_chunk_urls_with_policy(&flaky, [url()], &quick(1)).await.is_empty());
+}
+
+#[tokio::test]
+async fn sourcemaps_and_sections_use_the_dump_policy() {
+    let site = |p: &str| Url::parse("https://site.test/").unwrap().join(p).unwrap();
+    let cases = [("app.js.map", 1, 0), ("app.js.map", 2, 1), ("part.js.map", 1, 0), ("part.js.map", 2, 1)];
+    for (flaky, attempts, written) in cases {
+        let memory = MemoryFetcher::new();
+        memory
+            .insert(&site(""), "<html></html>")
+            .insert(&site("app.js"), "var a;\n//# sourceMappingURL=app.js.map")
+            .insert(&site("app.js.map"), r#"{"version":3,"sections":[{"offset":{"line":0,"column":0},"url":"part.js.map"}]}"#)
+            .insert(&site("part.js.map"), r#"{"version":3,"sources":["src/a.ts"],"sourcesContent":["let a"],"mappings":""}"#);
+        let fetcher = Arc::new(FlakyMap { inner: memory, flaky: site(flaky), refused: AtomicBool::new(false) });
+
+        let out = tempfile::tempdir().unwrap();
+        let options = DumpOptions { retry: quick(attempts), validate_urls: false, ..common::options(out.path()) };
+        let res = common::dump(Listed::new(&["app.js"]), options, Some(fetcher), DumpTarget::Url(site("").to_string())).await;
+        assert_eq!(res.stats.written_sources, written, "{flaky}, {attempts} attempts");
+    }
+}
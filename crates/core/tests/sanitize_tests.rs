@@ -0,0 +1,81 @@
+use resourcer_core::{parse_sourcemap, reconstruct_paths, safe_join, sanitize_relative_path, save_asset_with_maps, write_in_tree, SaveOptions};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+#[test]
+fn dot_segments_are_resolved() {
+    assert_eq!(sanitize_relative_path("./src/./lib/../app.js"), PathBuf::from("src/app.js"));
+    assert_eq!(sanitize_relative_path("src//a.js"), PathBuf::from("src/a.js"));
+}
+
+#[test]
+fn escapes_are_quarantined() {
+    assert_eq!(sanitize_relative_path("../../../../home/user/.bashrc"), PathBuf::from("_outside/home/user/.bashrc"));
+    assert_eq!(sanitize_relative_path("src/../../etc/passwd"), PathBuf::from("_outside/etc/passwd"));
+}
+
+#[test]
+fn absolute_roots_and_drive_letters_are_stripped() {
+    assert_eq!(sanitize_relative_path("/etc/passwd"), PathBuf::from("etc/passwd"));
+    assert_eq!(sanitize_relative_path(r"C:\Users\dev\proj\src\a.ts"), PathBuf::from("Users/dev/proj/src/a.ts"));
+    assert_eq!(sanitize_relative_path(r"\\server\share\x.js"), PathBuf::from("server/share/x.js"));
+}
+
+#[test]
+fn degenerate_paths() {
+    assert_eq!(sanitize_relative_path(""), PathBuf::from("_"));
+    assert_eq!(sanitize_relative_path(".."), PathBuf::from("_outside/_"));
+    assert_eq!(sanitize_relative_path("a\u{0}b.js"), PathBuf::from("a_b.js"));
+}
+
+#[test]
+fn hostile_sourcemap_sources_stay_in_tree() {
+    let json = r#"{"version":3,"sources":["webpack:///../../../../home/user/.bashrc","/etc/passwd","webpack:///./src/ok.js"],"mappings":""}"#;
+    let sm = parse_sourcemap(json).unwrap();
+    let dir = tempdir().unwrap();
+    let paths = reconstruct_paths(dir.path(), &sm);
+    assert_eq!(
+        paths,
+        vec![
+            dir.path().join("_outside/home/user/.bashrc"),
+            dir.path().join("etc/passwd"),
+            dir.path().join("src/ok.js"),
+        ]
+    );
+    assert!(paths.iter().all(|p| p.starts_with(dir.path())));
+}
+
+#[tokio::test]
+async fn hostile_asset_path_is_quarantined() {
+    let dir = tempdir().unwrap();
+    save_asset_with_maps("x", Path::new("../../evil.js"), Vec::new(), dir.path(), &SaveOptions::default()).await.unwrap();
+    assert!(dir.path().join("_outside/evil.js").is_file());
+}
+
+#[test]
+fn write_outside_root_is_refused() {
+    let dir = tempdir().unwrap();
+    let err = write_in_tree(dir.path(), Path::new("/tmp/elsewhere.js"), "x").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinks_in_output_tree_are_not_followed() {
+    use std::os::unix::fs::symlink;
+
+    let out = tempdir().unwrap();
+    let target = tempdir().unwrap();
+    symlink(target.path(), out.path().join("src")).unwrap();
+    symlink(target.path().join("victim.txt"), out.path().join("file.js")).unwrap();
+
+    let err = write_in_tree(out.path(), &safe_join(out.path(), "src/a.js"), "x").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    let err = write_in_tree(out.path(), &out.path().join("file.js"), "x").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    assert!(std::fs::read_dir(target.path()).unwrap().next().is_none());
+
+    write_in_tree(out.path(), &safe_join(out.path(), "lib/b.js"), "ok").unwrap();
+    assert_eq!(std::fs::read_to_string(out.path().join("lib/b.js")).unwrap(), "ok");
+}
@@ -1,6 +1,5 @@
 use std::fs;
 use tempfile::tempdir;
-use sourcemap::SourceMap;
 use resourcer_core::sm_parse::{parse_sourcemap, reconstruct_sources_with_swc};
 
 #[test]
@@ -39,4 +38,70 @@ fn test_reconstruct_with_mapping_stub() {
     let content = fs::read_to_string(&file_path).unwrap();
     // The content will be a stub/placeholder or segment, just check file is written
     assert!(!content.is_empty());
-} 
\ No newline at end of file
+} 
+const SECTION_A: &str = r#"{"version":3,"sources":["src/a.js"],"sourcesContent":["a();"],"mappings":"AAAA","names":[]}"#;
+const SECTION_B: &str = r#"{"version":3,"sources":["src/b.js"],"sourcesContent":["b();"],"mappings":"AAAA","names":[]}"#;
+
+#[test]
+fn index_map_with_embedded_sections_is_flattened() {
+    let json = format!(
+        r#"{{"version":3,"file":"bundle.js","sections":[{{"offset":{{"line":0,"column":0}},"map":{SECTION_A}}},{{"offset":{{"line":10,"column":0}},"map":{SECTION_B}}}]}}"#
+    );
+    let sm = parse_sourcemap(&json).unwrap();
+    assert_eq!(resourcer_core::sources_list(&sm), vec!["src/a.js", "src/b.js"]);
+    assert_eq!(sm.get_source_contents(1), Some("b();"));
+    assert_eq!(sm.lookup_token(10, 0).and_then(|t| t.get_source()), Some("src/b.js"));
+}
+
+#[test]
+fn index_map_with_url_section_needs_loading() {
+    let json = r#"{"version":3,"sections":[{"offset":{"line":0,"column":0},"url":"part.js.map"}]}"#;
+    assert!(parse_sourcemap(json).is_err());
+    assert!(matches!(resourcer_core::parse_decoded_sourcemap(json), Ok(sourcemap::DecodedMap::Index(_))));
+}
+
+#[tokio::test]
+async fn remote_sections_are_fetched_and_reconstructed() {
+    use httpmock::prelude::*;
+    use resourcer_core::{save_asset_with_maps, SaveOptions};
+    use std::path::Path;
+    use url::Url;
+
+    let server = MockServer::start_async().await;
+    let index = format!(
+        r#"{{"version":3,"sections":[{{"offset":{{"line":0,"column":0}},"map":{SECTION_A}}},{{"offset":{{"line":5,"column":0}},"url":"parts/b.js.map"}}]}}"#
+    );
+    server.mock(|when, then| {
+        when.method(GET).path("/js/bundle.js.map");
+        then.status(200).body(&index);
+    });
+    let part = server.mock(|when, then| {
+        when.method(GET).path("/js/parts/b.js.map");
+        then.status(200).body(SECTION_B);
+    });
+
+    let dir = tempdir().unwrap();
+    let map_url = Url::parse(&server.url("/js/bundle.js.map")).unwrap();
+    let outcome = save_asset_with_maps("bundle()", Path::new("js/bundle.js"), vec![map_url.clone()], dir.path(), &SaveOptions::default()).await.unwrap();
+    part.assert_async().await;
+    assert_eq!(outcome.declared_maps, vec![map_url]);
+    assert_eq!(fs::read_to_string(dir.path().join("src/a.js")).unwrap(), "a();");
+    assert_eq!(fs::read_to_string(dir.path().join("src/b.js")).unwrap(), "b();");
+}
+
+#[tokio::test]
+async fn remote_index_maps_cannot_load_local_sections() {
+    use url::Url;
+
+    let dir = tempdir().unwrap();
+    let secret = dir.path().join("secret.js.map");
+    fs::write(&secret, SECTION_B).unwrap();
+    let section = Url::from_file_path(&secret).unwrap();
+    let json = format!(r#"{{"version":3,"sections":[{{"offset":{{"line":0,"column":0}},"url":"{section}"}}]}}"#);
+
+    let remote = Url::parse("https://site.test/bundle.js.map").unwrap();
+    let err = resourcer_core::load_sourcemap(&json, &remote).await.unwrap_err();
+    assert!(err.to_string().contains("refusing"), "{err}");
+    let local = Url::from_file_path(dir.path().join("bundle.js.map")).unwrap();
+    assert!(resourcer_core::load_sourcemap(&json, &local).await.is_ok());
+}
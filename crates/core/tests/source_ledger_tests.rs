@@ -0,0 +1,88 @@
+use resourcer_core::{save_asset_with_maps, save_asset_with_options, ConflictPolicy, SaveOptions, SourceLedger, WriteAction};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+
+#[test]
+fn identical_contents_are_written_once() {
+    let dir = tempdir().unwrap();
+    let ledger = SourceLedger::default();
+    let path = dir.path().join("src/utils.ts");
+
+    assert_eq!(ledger.write_source(dir.path(), &path, "a", "chunk1.js").unwrap(), WriteAction::Written);
+    assert_eq!(ledger.write_source(dir.path(), &path, "a", "chunk2.js").unwrap(), WriteAction::Duplicate);
+
+    let records = ledger.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].path, Path::new("src/utils.ts"));
+    assert_eq!(records[0].versions[0].contributors, vec!["chunk1.js", "chunk2.js"]);
+    assert!(ledger.conflicts().is_empty());
+}
+
+#[test]
+fn keep_first_discards_later_versions() {
+    let dir = tempdir().unwrap();
+    let ledger = SourceLedger::new(ConflictPolicy::KeepFirst);
+    let path = dir.path().join("a.js");
+
+    ledger.write_source(dir.path(), &path, "first", "c1").unwrap();
+    assert_eq!(ledger.write_source(dir.path(), &path, "second, longer", "c2").unwrap(), WriteAction::Discarded);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "first");
+    assert_eq!(ledger.conflicts()[0].versions[1].written_to, None);
+}
+
+#[test]
+fn keep_largest_replaces_smaller_versions() {
+    let dir = tempdir().unwrap();
+    let ledger = SourceLedger::new(ConflictPolicy::KeepLargest);
+    let path = dir.path().join("a.js");
+
+    ledger.write_source(dir.path(), &path, "short", "c1").unwrap();
+    assert_eq!(ledger.write_source(dir.path(), &path, "much longer", "c2").unwrap(), WriteAction::Replaced);
+    assert_eq!(ledger.write_source(dir.path(), &path, "tiny", "c3").unwrap(), WriteAction::Discarded);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "much longer");
+
+    let versions = &ledger.records()[0].versions;
+    assert_eq!(versions[0].written_to, None);
+    assert_eq!(versions[1].written_to.as_deref(), Some(Path::new("a.js")));
+}
+
+#[test]
+fn variants_are_written_next_to_the_original() {
+    let dir = tempdir().unwrap();
+    let ledger = SourceLedger::new(ConflictPolicy::WriteVariants);
+    let path = dir.path().join("src/utils.ts");
+
+    ledger.write_source(dir.path(), &path, "v1", "c1").unwrap();
+    let WriteAction::Variant(variant) = ledger.write_source(dir.path(), &path, "v2", "c2").unwrap() else {
+        panic!("expected a variant");
+    };
+    let name = variant.file_name().unwrap().to_string_lossy().into_owned();
+    assert!(name.starts_with("utils.conflict-") && name.ends_with(".ts"), "{name}");
+    assert_eq!(std::fs::read_to_string(dir.path().join(&variant)).unwrap(), "v2");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "v1");
+}
+
+#[tokio::test]
+async fn shared_sources_across_chunks_are_deduplicated() {
+    let dir = tempdir().unwrap();
+    let map = |content: &str| {
+        let json = format!(r#"{{"version":3,"sources":["src/shared.ts"],"sourcesContent":["{content}"],"mappings":""}}"#);
+        url::Url::parse(&format!("data:application/json,{}", percent_encoding::utf8_percent_encode(&json, percent_encoding::NON_ALPHANUMERIC))).unwrap()
+    };
+    let opts = SaveOptions { ledger: Arc::new(SourceLedger::new(ConflictPolicy::KeepFirst)), ..Default::default() };
+
+    let a = save_asset_with_options("a", Path::new("a.js"), None, vec![map("x")], dir.path(), &opts).await.unwrap();
+    let b = save_asset_with_options("b", Path::new("b.js"), None, vec![map("x")], dir.path(), &opts).await.unwrap();
+    let c = save_asset_with_options("c", Path::new("c.js"), None, vec![map("y")], dir.path(), &opts).await.unwrap();
+    assert_eq!((a.written_sources, b.duplicate_sources, c.conflicting_sources), (1, 1, 1));
+
+    let json = opts.ledger.to_json();
+    assert_eq!(json[0]["path"], "src/shared.ts");
+    assert_eq!(json[0]["versions"][0]["contributors"], serde_json::json!(["a.js", "b.js"]));
+
+    // The convenience helper shares the ledger too, so it cannot overwrite the first version.
+    let d = save_asset_with_maps("d", Path::new("d.js"), vec![map("z")], dir.path(), &opts).await.unwrap();
+    assert_eq!((d.written_sources, d.conflicting_sources), (0, 1));
+    assert_eq!(std::fs::read_to_string(dir.path().join("src/shared.ts")).unwrap(), "x");
+}
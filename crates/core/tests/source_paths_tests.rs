@@ -0,0 +1,70 @@
+use resourcer_core::{MappedSource, SourceNormalizer, SourcePathOptions, SourceUrlRule};
+
+fn norm(source: &str) -> String {
+    SourceNormalizer::default().normalize(source).0
+}
+
+#[test]
+fn bundler_schemes_use_namespace_folders() {
+    assert_eq!(norm("webpack:///./src/a.js"), "src/a.js");
+    assert_eq!(norm("webpack://app/./src/a.js"), "app/src/a.js");
+    assert_eq!(norm("turbopack://[project]/src/a.tsx"), "src/a.tsx");
+    assert_eq!(norm("turbopack://[turbopack]/shims.js"), "turbopack/shims.js");
+    assert_eq!(norm("ng://AppModule/app.component.html"), "AppModule/app.component.html");
+    assert_eq!(norm("rollup:///src/b.js"), "src/b.js");
+}
+
+#[test]
+fn absolute_paths_collapse_to_project() {
+    assert_eq!(norm("file:///Users/alice/proj/src/x.ts"), "src/x.ts");
+    assert_eq!(norm("/home/ci/work/node_modules/react/index.js"), "node_modules/react/index.js");
+    assert_eq!(norm("file:///Users/alice/My%20Proj/src/x.ts"), "src/x.ts");
+
+    let opts = SourcePathOptions { project_root: Some("/Users/alice/proj".into()), ..Default::default() };
+    let n = SourceNormalizer::with_builtin(opts);
+    assert_eq!(n.normalize("file:///Users/alice/proj/config/app.ts"), ("config/app.ts".to_string(), false));
+}
+
+#[test]
+fn virtual_modules_and_http_hosts() {
+    assert_eq!(norm("\0commonjsHelpers.js"), "_virtual/commonjsHelpers.js");
+    assert_eq!(norm("vite/preload-helper"), "_virtual/vite/preload-helper");
+    assert_eq!(norm("https://cdn.example.com/lib/x.js"), "cdn.example.com/lib/x.js");
+}
+
+#[test]
+fn query_strings_become_distinct_files() {
+    assert_eq!(norm("webpack://app/./src/App.vue?3a1b"), "app/src/App.3a1b.vue");
+    let style = norm("webpack://app/./src/App.vue?vue&type=style&index=0");
+    assert!(style.starts_with("app/src/App.") && style.ends_with(".vue"));
+    assert_ne!(style, norm("webpack://app/./src/App.vue?vue&type=script"));
+
+    let opts = SourcePathOptions { query_variants: false, ..Default::default() };
+    let n = SourceNormalizer::with_builtin(opts);
+    assert_eq!(n.normalize("webpack://app/./src/App.vue?3a1b").0, "app/src/App.vue");
+}
+
+#[test]
+fn custom_rules_take_precedence_in_order() {
+    struct Internal;
+    impl SourceUrlRule for Internal {
+        fn name(&self) -> &'static str {
+            "internal"
+        }
+        fn apply(&self, source: &str) -> Option<MappedSource> {
+            let path = source.strip_prefix("webpack://internal/")?;
+            Some(MappedSource { namespace: Some("_internal".into()), path: path.into(), absolute: false })
+        }
+    }
+
+    // Appended, the rule comes after the built-in scheme rule, which handles every webpack:// URL.
+    let mut n = SourceNormalizer::with_builtin(SourcePathOptions::default());
+    n.register(Internal);
+    assert_eq!(n.normalize("webpack://internal/x.js").0, "internal/x.js");
+
+    let mut n = SourceNormalizer::with_builtin(SourcePathOptions::default());
+    n.register_first(Internal);
+    assert_eq!(n.normalize("webpack://internal/x.js").0, "_internal/x.js");
+    assert_eq!(n.normalize("webpack://app/./src/a.js").0, "app/src/a.js");
+    assert_eq!(n.normalize("plain/y.js"), ("plain/y.js".to_string(), true));
+}
@@ -33,4 +33,126 @@ fn validate_and_dedup() {
     assert_eq!(urls.len(), 2);
     assert!(urls.iter().any(|u| u.as_str() == "https://example.com/js/app.js.map"));
     assert!(urls.iter().any(|u| u.as_str() == "https://cdn.com/vendor.map"));
-} 
\ No newline at end of file
+}
+
+#[test]
+fn data_url_inside_eval_string() {
+    let js = r#"eval("var a = 1;\n//# sourceMappingURL=data:application/json;base64,eyJ9\n//# sourceURL=webpack://app/./a.js");"#;
+    assert_eq!(extract_sourcemap_urls(js), vec!["data:application/json;base64,eyJ9"]);
+}
+
+#[test]
+fn header_urls_merge_with_comment() {
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use resourcer_core::{find_sourcemap_urls_with_headers, sourcemap_urls_from_headers};
+
+    let base = Url::parse("https://example.com/js/app.js").unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert("X-SourceMap", HeaderValue::from_static("/maps/legacy.map"));
+    headers.insert("SourceMap", HeaderValue::from_static("app.js.map"));
+
+    let from_headers: Vec<_> = sourcemap_urls_from_headers(&base, &headers).into_iter().map(String::from).collect();
+    assert_eq!(from_headers, vec!["https://example.com/js/app.js.map", "https://example.com/maps/legacy.map"]);
+
+    let js = "x();\n//# sourceMappingURL=app.js.map\n//# sourceMappingURL=other.map";
+    let merged: Vec<_> = find_sourcemap_urls_with_headers(&base, js, &headers).into_iter().map(String::from).collect();
+    assert_eq!(
+        merged,
+        vec!["https://example.com/js/app.js.map", "https://example.com/maps/legacy.map", "https://example.com/js/other.map"]
+    );
+}
+
+#[test]
+fn probe_candidates_cover_hashed_names() {
+    use resourcer_core::sourcemap_probe_candidates;
+
+    let asset = Url::parse("https://cdn.example.com/static/js/main.3f2a1b9c.js?v=1").unwrap();
+    let got: Vec<_> = sourcemap_probe_candidates(&asset).into_iter().map(String::from).collect();
+    assert_eq!(
+        got,
+        vec![
+            "https://cdn.example.com/static/js/main.3f2a1b9c.js.map",
+            "https://cdn.example.com/static/js/main.3f2a1b9c.map",
+            "https://cdn.example.com/static/js/main.3f2a1b9c.mjs.map",
+            "https://cdn.example.com/static/js/main.js.map",
+            "https://cdn.example.com/static/js/main.map",
+        ]
+    );
+
+    let plain = Url::parse("https://example.com/app.mjs").unwrap();
+    assert_eq!(sourcemap_probe_candidates(&plain)[0].as_str(), "https://example.com/app.mjs.map");
+    assert!(sourcemap_probe_candidates(&Url::parse("https://example.com/").unwrap()).is_empty());
+}
+
+#[test]
+fn probed_map_file_field_must_match() {
+    use resourcer_core::sourcemap_file_matches;
+
+    let asset = Url::parse("https://example.com/static/chunks/123-abcdef12.js").unwrap();
+    assert!(sourcemap_file_matches(None, &asset));
+    assert!(sourcemap_file_matches(Some("static/chunks/123-abcdef12.js"), &asset));
+    assert!(sourcemap_file_matches(Some("123.js"), &asset));
+    assert!(!sourcemap_file_matches(Some("other.js"), &asset));
+}
+
+#[tokio::test]
+async fn probing_finds_hidden_map() {
+    use httpmock::prelude::*;
+    use resourcer_core::{save_asset_with_options, SaveOptions};
+    use std::path::Path;
+
+    let server = MockServer::start_async().await;
+    let spa_fallback = server.mock(|when, then| {
+        when.method(GET).path("/js/app.js.map");
+        then.status(200).body("<html>not a map</html>");
+    });
+    let wrong_file = server.mock(|when, then| {
+        when.method(GET).path("/js/app.map");
+        then.status(200).body(r#"{"version":3,"file":"vendor.js","sources":["v.js"],"sourcesContent":["v"],"mappings":"AAAA","names":[]}"#);
+    });
+    let good = server.mock(|when, then| {
+        when.method(GET).path("/js/app.mjs.map");
+        then.status(200).body(r#"{"version":3,"file":"app.js","sources":["src/app.js"],"sourcesContent":["let a;"],"mappings":"AAAA","names":[]}"#);
+    });
+
+    let asset = Url::parse(&server.url("/js/app.js")).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let rel = Path::new("js/app.js");
+
+    let off = save_asset_with_options("x", rel, Some(&asset), Vec::new(), dir.path(), &SaveOptions::default()).await.unwrap();
+    assert!(off.probed_maps.is_empty());
+    good.assert_hits_async(0).await;
+
+    let on = save_asset_with_options("x", rel, Some(&asset), Vec::new(), dir.path(), &SaveOptions { probe_maps: true, ..Default::default() }).await.unwrap();
+    spa_fallback.assert_async().await;
+    wrong_file.assert_async().await;
+    assert!(on.declared_maps.is_empty());
+    assert_eq!(on.probed_maps, vec![Url::parse(&server.url("/js/app.mjs.map")).unwrap()]);
+    assert_eq!(std::fs::read_to_string(dir.path().join("src/app.js")).unwrap(), "let a;");
+}
+
+#[tokio::test]
+async fn maps_resolve_against_the_redirected_url() {
+    use reqwest::header::HeaderMap;
+    use resourcer_core::{save_fetched_asset, FetchResponse, MemoryFetcher, SaveOptions};
+    use std::sync::Arc;
+
+    let fetcher = MemoryFetcher::new();
+    fetcher.insert(
+        &Url::parse("https://cdn.test/v2/app.js.map").unwrap(),
+        r#"{"version":3,"sources":["src/app.ts"],"sourcesContent":["let a"],"mappings":""}"#,
+    );
+    let resp = FetchResponse {
+        url: Url::parse("https://cdn.test/v2/app.js").unwrap(),
+        status: 200,
+        headers: HeaderMap::new(),
+        body: "var a;\n//# sourceMappingURL=app.js.map".into(),
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let opts = SaveOptions { fetcher: Arc::new(fetcher), ..Default::default() };
+    let out = save_fetched_asset("https://site.test/js/app.js", &resp, dir.path(), &opts).await.unwrap();
+
+    assert_eq!(out.declared_maps, vec![Url::parse("https://cdn.test/v2/app.js.map").unwrap()]);
+    assert!(dir.path().join("js/app.js").is_file());
+    assert_eq!(std::fs::read_to_string(dir.path().join("src/app.ts")).unwrap(), "let a");
+}
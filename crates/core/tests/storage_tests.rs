@@ -1,4 +1,8 @@
-use resourcer_core::storage::{HtmlStorage, MemoryStorage};
+use std::sync::Arc;
+
+use httpmock::prelude::*;
+use resourcer_core::storage::{CachingFetcher, FsStorage, HtmlDocument, HtmlStorage, MemoryStorage, SqliteStorage};
+use resourcer_core::{FetchError, Fetcher, HttpClient, HttpFetcher};
 use url::Url;
 
 #[tokio::test]
@@ -11,5 +15,108 @@ async fn save_and_get_round_trip() {
         .unwrap();
     let doc = store.get(&url).await.unwrap();
     assert_eq!(doc.url, url);
-    assert!(doc.content.contains("hello"));
-} 
\ No newline at end of file
+    assert!(doc.text().contains("hello"));
+}
+
+fn document(url: &Url) -> HtmlDocument {
+    let mut doc = HtmlDocument::new(url.clone(), vec![0xff, b'j', b's']);
+    doc.content_type = Some("application/javascript".into());
+    doc.headers = vec![("etag".into(), "\"v1\"".into()), ("content-type".into(), "application/javascript".into())];
+    doc
+}
+
+async fn assert_round_trip(store: &dyn HtmlStorage, url: &Url, saved: &HtmlDocument) {
+    let doc = store.get(url).await.unwrap();
+    assert_eq!(doc.body, saved.body);
+    assert_eq!(doc.content_type, saved.content_type);
+    assert_eq!(doc.headers, saved.headers);
+    assert_eq!(doc.timestamp, saved.timestamp);
+}
+
+#[tokio::test]
+async fn disk_backends_persist_across_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = Url::parse("https://example.com/app.js?v=1").unwrap();
+    let other = Url::parse("https://example.com/other.js").unwrap();
+    let doc = document(&url);
+
+    let cache = dir.path().join("cache");
+    FsStorage::open(&cache).unwrap().save(doc.clone()).await.unwrap();
+    let fs = FsStorage::open(&cache).unwrap();
+    assert_round_trip(&fs, &url, &doc).await;
+    assert!(fs.get(&other).await.is_none());
+
+    let db = dir.path().join("cache.sqlite");
+    SqliteStorage::open(&db).unwrap().save(doc.clone()).await.unwrap();
+    let sqlite = SqliteStorage::open(&db).unwrap();
+    assert_round_trip(&sqlite, &url, &doc).await;
+    assert!(sqlite.get(&other).await.is_none());
+
+    let mut newer = document(&url);
+    newer.body = b"v2".to_vec();
+    sqlite.save(newer.clone()).await.unwrap();
+    assert_round_trip(&sqlite, &url, &newer).await;
+}
+
+#[tokio::test]
+async fn caching_fetcher_serves_repeat_requests_from_storage() {
+    let server = MockServer::start_async().await;
+    let app = server.mock(|when, then| {
+        when.method(GET).path("/app.js");
+        then.status(200).header("content-type", "application/javascript").body("var a;");
+    });
+    let gone = server.mock(|when, then| {
+        when.method(GET).path("/gone.js");
+        then.status(404);
+    });
+    let url = Url::parse(&server.url("/app.js")).unwrap();
+    let missing = Url::parse(&server.url("/gone.js")).unwrap();
+
+    let storage = Arc::new(SqliteStorage::in_memory().unwrap());
+    let fetcher = CachingFetcher::new(Arc::new(HttpFetcher::new(HttpClient::shared())), storage.clone());
+    for _ in 0..2 {
+        let resp = fetcher.fetch(&url).await.unwrap();
+        assert_eq!(resp.body, "var a;");
+        assert_eq!(resp.header("content-type"), Some("application/javascript"));
+        assert!(fetcher.exists(&url).await);
+        assert!(matches!(fetcher.fetch(&missing).await, Err(FetchError::HttpStatus(404))));
+    }
+    app.assert_hits(1);
+    gone.assert_hits(2);
+    assert_eq!(storage.get(&url).await.unwrap().content_type.as_deref(), Some("application/javascript"));
+}
+
+#[tokio::test]
+async fn revalidating_cache_reuses_bodies_on_not_modified() {
+    let server = MockServer::start_async().await;
+    let unchanged = server.mock(|when, then| {
+        when.method(GET).path("/app.js").header("if-none-match", "\"v1\"");
+        then.status(304);
+    });
+    let full = server.mock(|when, then| {
+        when.method(GET).path("/app.js").matches(|req| !req.headers.iter().flatten().any(|(k, _)| k.eq_ignore_ascii_case("if-none-match")));
+        then.status(200).header("etag", "\"v1\"").body("var a;");
+    });
+    let plain = server.mock(|when, then| {
+        when.method(GET).path("/plain.js");
+        then.status(200).body("var p;");
+    });
+    let url = Url::parse(&server.url("/app.js")).unwrap();
+    let no_validators = Url::parse(&server.url("/plain.js")).unwrap();
+
+    let storage = Arc::new(MemoryStorage::new());
+    let fetcher = CachingFetcher::new(Arc::new(HttpFetcher::new(HttpClient::shared())), storage.clone()).revalidating();
+    let first = fetcher.fetch(&url).await.unwrap();
+    assert_eq!(first.status, 200);
+    let second = fetcher.fetch(&url).await.unwrap();
+    assert!(second.is_not_modified());
+    assert_eq!(second.body, "var a;");
+    full.assert_hits(1);
+    unchanged.assert_hits(1);
+
+    // Without validators there is nothing to ask, so the document is downloaded again.
+    for _ in 0..2 {
+        assert_eq!(fetcher.fetch(&no_validators).await.unwrap().status, 200);
+    }
+    plain.assert_hits(2);
+}
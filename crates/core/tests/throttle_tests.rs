@@ -0,0 +1,181 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use common::Listed;
+use resourcer_core::{
+    download_many, DownloadManagerConfig, DumpOptions, DumpTarget, FetchError, FetchResponse, Fetcher, MemoryFetcher,
+    Throttle, ThrottleConfig, ThrottledFetcher,
+};
+use url::Url;
+
+/// Answers after a short delay, tracking the most requests in flight per host.
+#[derive(Default)]
+struct Slow {
+    in_flight: Mutex<Vec<(String, usize)>>,
+    max: AtomicUsize,
+}
+
+#[async_trait]
+impl Fetcher for Slow {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        let host = url.host_str().unwrap().to_string();
+        {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            let n = match in_flight.iter_mut().find(|(h, _)| *h == host) {
+                Some((_, n)) => {
+                    *n += 1;
+                    *n
+                }
+                None => {
+                    in_flight.push((host.clone(), 1));
+                    1
+                }
+            };
+            self.max.fetch_max(n, Ordering::SeqCst);
+        }
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        self.in_flight.lock().unwrap().iter_mut().find(|(h, _)| *h == host).unwrap().1 -= 1;
+        MemoryFetcher::new().insert(url, "ok").fetch(url).await
+    }
+}
+
+/// Answers `429` the first time and `200` afterwards.
+#[derive(Default)]
+struct Busy {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl Fetcher for Busy {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Err(FetchError::HttpStatus(429));
+        }
+        MemoryFetcher::new().insert(url, "ok").fetch(url).await
+    }
+}
+
+/// Serves "ok", noting when each request started.
+#[derive(Default)]
+struct Stamped {
+    starts: Mutex<Vec<Instant>>,
+}
+
+#[async_trait]
+impl Fetcher for Stamped {
+    async fn fetch(&self, url: &Url) -> Result<FetchResponse, FetchError> {
+        self.starts.lock().unwrap().push(Instant::now());
+        MemoryFetcher::new().insert(url, "ok").fetch(url).await
+    }
+}
+
+fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+}
+
+#[tokio::test]
+async fn rate_limits_each_host_separately() {
+    let memory = MemoryFetcher::new();
+    for i in 0..5 {
+        memory.insert(&url(&format!("https://a.test/{i}.js")), "a").insert(&url(&format!("https://b.test/{i}.js")), "b");
+    }
+    let throttle = Arc::new(Throttle::new(ThrottleConfig { requests_per_second: Some(20.0), ..Default::default() }));
+    let fetcher = ThrottledFetcher::new(Arc::new(memory), throttle);
+
+    let start = Instant::now();
+    for i in 0..5 {
+        fetcher.fetch(&url(&format!("https://a.test/{i}.js"))).await.unwrap();
+    }
+    // The first request is free, the other four wait 50ms each.
+    assert!(start.elapsed() >= Duration::from_millis(190), "{:?}", start.elapsed());
+
+    // b.test has its own bucket: its first request does not wait for a.test's.
+    let start = Instant::now();
+    fetcher.fetch(&url("https://b.test/0.js")).await.unwrap();
+    assert!(start.elapsed() < Duration::from_millis(40));
+}
+
+#[tokio::test]
+async fn caps_concurrent_requests_per_host() {
+    let slow = Arc::new(Slow::default());
+    let urls = (0..8).map(|i| format!("https://site.test/{i}.js")).collect();
+    let throttle = Arc::new(Throttle::new(ThrottleConfig { per_host_concurrency: Some(2), ..Default::default() }));
+    let cfg = DownloadManagerConfig { concurrency: 8, fetcher: slow.clone(), throttle: Some(throttle), ..Default::default() };
+    let results = download_many(urls, cfg).await;
+    assert!(results.iter().all(|r| r.content() == Some("ok")));
+    assert_eq!(slow.max.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn slows_down_after_too_many_requests() {
+    let throttle = Arc::new(Throttle::new(ThrottleConfig { max_slow_down: Duration::from_millis(40), ..Default::default() }));
+    let fetcher = ThrottledFetcher::new(Arc::new(Busy::default()), throttle.clone());
+    let page = url("https://site.test/app.js");
+
+    assert!(matches!(fetcher.fetch(&page).await, Err(FetchError::HttpStatus(429))));
+    assert_eq!(throttle.slow_down(&page), Duration::from_millis(40));
+
+    let start = Instant::now();
+    fetcher.fetch(&page).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(40));
+    assert_eq!(throttle.slow_down(&page), Duration::from_millis(20));
+    assert_eq!(throttle.slow_down(&url("https://other.test/")), Duration::ZERO);
+}
+
+#[tokio::test]
+async fn slowed_down_hosts_space_out_concurrent_requests() {
+    let throttle = Arc::new(Throttle::new(ThrottleConfig { max_slow_down: Duration::from_millis(40), ..Default::default() }));
+    throttle.record(&url("https://site.test/"), Some(429));
+    let stamped = Arc::new(Stamped::default());
+    let urls = (0..4).map(|i| format!("https://site.test/{i}.js")).collect();
+    let cfg = DownloadManagerConfig { concurrency: 4, fetcher: stamped.clone(), throttle: Some(throttle), ..Default::default() };
+    let results = download_many(urls, cfg).await;
+    assert!(results.iter().all(|r| r.content() == Some("ok")));
+
+    let mut starts = stamped.starts.lock().unwrap().clone();
+    starts.sort();
+    for pair in starts.windows(2) {
+        // Sleeps may wake late but never early, so allow some slack below the 40ms gap.
+        assert!(pair[1] - pair[0] >= Duration::from_millis(30), "{:?}", pair[1] - pair[0]);
+    }
+}
+
+#[tokio::test]
+async fn dumps_throttle_chunk_downloads() {
+    let memory = MemoryFetcher::new();
+    memory.insert(&url("https://site.test/"), "<html></html>");
+    for i in 0..4 {
+        memory.insert(&url(&format!("https://site.test/{i}.js")), "var a;");
+    }
+    let memory: Arc<dyn Fetcher> = Arc::new(memory);
+    for concurrency in [1, 4] {
+        let out = tempfile::tempdir().unwrap();
+        let throttle = Arc::new(Throttle::new(ThrottleConfig { requests_per_second: Some(20.0), ..Default::default() }));
+        let options = DumpOptions { concurrency, throttle: Some(throttle), ..common::options(out.path()) };
+        let start = Instant::now();
+        let listed = Listed::new(&["0.js", "1.js", "2.js", "3.js"]);
+        let res = common::dump(listed, options, Some(memory.clone()), DumpTarget::Url("https://site.test/".into())).await;
+        assert_eq!(res.report.chunks.len(), 4);
+        // The first download is free, the other three wait 50ms each.
+        assert!(start.elapsed() >= Duration::from_millis(140), "{concurrency}: {:?}", start.elapsed());
+    }
+}
+
+#[tokio::test]
+async fn leaves_other_schemes_alone() {
+    let throttle = Arc::new(Throttle::new(ThrottleConfig { requests_per_second: Some(1.0), ..Default::default() }));
+    let memory = MemoryFetcher::new();
+    let data = url("data:text/javascript,var%20a");
+    memory.insert(&data, "var a");
+    let fetcher = ThrottledFetcher::new(Arc::new(memory), throttle);
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        fetcher.fetch(&data).await.unwrap();
+    }
+    assert!(start.elapsed() < Duration::from_millis(500));
+}
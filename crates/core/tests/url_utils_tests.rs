@@ -0,0 +1,9 @@
+use resourcer_core::derive_base_from_runtime;
+use url::Url;
+
+#[test]
+fn runtime_base_keeps_port_and_drops_query() {
+    let runtime = Url::parse("http://127.0.0.1:8080/app/_next/static/chunks/webpack-abc.js?v=1#x").unwrap();
+    assert_eq!(derive_base_from_runtime(&runtime, "_next/static/chunks/").as_str(), "http://127.0.0.1:8080/app/");
+    assert_eq!(derive_base_from_runtime(&runtime, "static/js/").as_str(), "http://127.0.0.1:8080/");
+}
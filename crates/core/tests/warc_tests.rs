@@ -0,0 +1,128 @@
+use std::io::Write;
+
+mod common;
+
+use common::Listed;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use resourcer_core::{DumpTarget, FetchError, Fetcher, WarcArchive};
+use url::Url;
+
+fn record(kind: &str, url: &str, block: &[u8]) -> Vec<u8> {
+    record_with(kind, url, &[], block)
+}
+
+fn record_with(kind: &str, url: &str, fields: &[(&str, &str)], block: &[u8]) -> Vec<u8> {
+    let extra: String = fields.iter().map(|(k, v)| format!("{k}: {v}\r\n")).collect();
+    let mut out = format!(
+        "WARC/1.1\r\nWARC-Type: {kind}\r\nWARC-Target-URI: {url}\r\n{extra}Content-Type: application/http;msgtype={kind}\r\nContent-Length: {}\r\n\r\n",
+        block.len()
+    )
+    .into_bytes();
+    out.extend_from_slice(block);
+    out.extend_from_slice(b"\r\n\r\n");
+    out
+}
+
+fn response(url: &str, status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+    let mut block = format!("HTTP/1.1 {status}\r\n").into_bytes();
+    for (k, v) in headers {
+        block.extend_from_slice(format!("{k}: {v}\r\n").as_bytes());
+    }
+    block.extend_from_slice(b"\r\n");
+    block.extend_from_slice(body);
+    record("response", url, &block)
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+    enc.write_all(data).unwrap();
+    enc.finish().unwrap()
+}
+
+const MAP: &str = r#"{"version":3,"sources":["src/app.ts"],"sourcesContent":["let a"],"mappings":""}"#;
+
+fn crawl() -> Vec<u8> {
+    let mut warc = Vec::new();
+    warc.extend(record("request", "https://site.test/", b"GET / HTTP/1.1\r\nHost: site.test\r\n\r\n"));
+    warc.extend(response("https://site.test/", "200 OK", &[("Content-Type", "text/html")], b"<html></html>"));
+    warc.extend(response(
+        "https://site.test/app.js",
+        "200 OK",
+        &[("Content-Type", "application/javascript"), ("Transfer-Encoding", "chunked")],
+        b"7\r\nvar a;\n\r\n1F\r\n//# sourceMappingURL=app.js.map\r\n0\r\n\r\n",
+    ));
+    warc.extend(response(
+        "https://site.test/app.js.map",
+        "200 OK",
+        &[("Content-Type", "application/json"), ("Content-Encoding", "gzip")],
+        &gzip(MAP.as_bytes()),
+    ));
+    warc.extend(response("https://site.test/gone.js", "404 Not Found", &[], b"nope"));
+    warc
+}
+
+#[tokio::test]
+async fn indexes_responses_and_decodes_payloads() {
+    let archive = WarcArchive::from_reader(crawl().as_slice()).unwrap();
+    assert_eq!(archive.responses().len(), 4);
+    assert_eq!(archive.page_url().unwrap().as_str(), "https://site.test/");
+
+    let fetcher = archive.into_fetcher();
+    let app = fetcher.fetch(&Url::parse("https://site.test/app.js").unwrap()).await.unwrap();
+    assert_eq!(app.body, "var a;\n//# sourceMappingURL=app.js.map");
+    assert_eq!(app.header("transfer-encoding"), None);
+    let map = fetcher.fetch(&Url::parse("https://site.test/app.js.map").unwrap()).await.unwrap();
+    assert_eq!(map.body, MAP);
+    assert_eq!(map.header("content-encoding"), None);
+    let gone = fetcher.fetch(&Url::parse("https://site.test/gone.js").unwrap()).await;
+    assert!(matches!(gone, Err(FetchError::HttpStatus(404))));
+    let other = fetcher.fetch(&Url::parse("https://site.test/other.js").unwrap()).await;
+    assert!(matches!(other, Err(FetchError::NotRecorded(_))));
+}
+
+#[tokio::test]
+async fn resolves_revisits_of_deduplicated_crawls() {
+    let digest = ("WARC-Payload-Digest", "sha1:APPJS");
+    let mut warc = record_with("response", "https://site.test/app.js", &[digest], b"HTTP/1.1 200 OK\r\nContent-Type: application/javascript\r\n\r\nvar a;");
+    warc.extend(record_with("revisit", "https://site.test/app.js?v=2", &[digest], b"HTTP/1.1 200 OK\r\n\r\n"));
+    warc.extend(record_with("revisit", "https://site.test/copy.js", &[("WARC-Refers-To-Target-URI", "<https://site.test/app.js>")], b""));
+    warc.extend(record_with("revisit", "https://site.test/lost.js", &[("WARC-Payload-Digest", "sha1:GONE")], b""));
+    let archive = WarcArchive::from_reader(warc.as_slice()).unwrap();
+    assert_eq!(archive.responses().len(), 3);
+
+    let fetcher = archive.into_fetcher();
+    for path in ["app.js?v=2", "copy.js"] {
+        let resp = fetcher.fetch(&Url::parse("https://site.test/").unwrap().join(path).unwrap()).await.unwrap();
+        assert_eq!(resp.body, "var a;");
+        assert_eq!(resp.header("content-type"), Some("application/javascript"));
+    }
+    let lost = fetcher.fetch(&Url::parse("https://site.test/lost.js").unwrap()).await;
+    assert!(matches!(lost, Err(FetchError::NotRecorded(_))));
+}
+
+#[test]
+fn reads_gzipped_archives_and_rejects_garbage() {
+    // `.warc.gz` files compress each record as its own gzip member.
+    let mut members = Vec::new();
+    members.extend(gzip(&response("https://site.test/", "200 OK", &[("Content-Type", "text/html")], b"<html></html>")));
+    members.extend(gzip(&response("https://site.test/a.js", "200 OK", &[], b"var a;")));
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), members).unwrap();
+    assert_eq!(WarcArchive::from_path(file.path()).unwrap().responses().len(), 2);
+
+    assert!(WarcArchive::from_reader(&b"not a warc\r\n"[..]).is_err());
+}
+
+#[tokio::test]
+async fn dumps_crawl_without_network() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), crawl()).unwrap();
+    let out = tempfile::tempdir().unwrap();
+
+    let target = DumpTarget::Warc { path: file.path().to_path_buf(), page: None };
+    let res = common::dump(Listed::new(&["app.js"]), common::options(out.path()), None, target).await;
+
+    assert_eq!(res.stats.written_sources, 1);
+    assert_eq!(std::fs::read_to_string(out.path().join("src/app.ts")).unwrap(), "let a");
+}
@@ -0,0 +1,111 @@
+use resourcer_core::webpack_modules::module_file_name;
+use resourcer_core::{save_asset_with_maps, save_asset_with_options, split_webpack_modules, ConflictPolicy, SaveOptions, SourceLedger};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+
+const CHUNK: &str = r#"(self.webpackChunk_N_E=self.webpackChunk_N_E||[]).push([[405],{5557:function(e,t,n){"use strict";var r=n(7294),o=n( 2 );t.default=function(){return r.createElement(o.A,null)}},2:(e,t,n)=>{n.d(t,{A:()=>i});const i=n("abc")},7294:function(e){e.exports=window.React}},function(e){e.O(0,[774],function(){return e(e.s=5557)})}]);"#;
+
+#[test]
+fn splits_push_chunk_and_rewrites_requires() {
+    let modules = split_webpack_modules(CHUNK);
+    let ids: Vec<_> = modules.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, vec!["5557", "2", "7294"]);
+
+    let first = &modules[0].source;
+    assert!(first.starts_with("/* webpack module 5557 (module = e, exports = t, __webpack_require__ = n) */\n"));
+    assert!(first.contains(r#"var r=require("./7294.js"),o=require("./2.js");"#), "{first}");
+    assert!(modules[1].source.contains(r#"const i=require("./abc.js")"#));
+    assert!(modules[1].source.contains("n.d(t,"), "non-literal runtime calls are left alone");
+    assert_eq!(modules[2].source, "/* webpack module 7294 (module = e) */\ne.exports=window.React\n");
+}
+
+#[test]
+fn splits_webpack4_bootstrap_with_named_ids() {
+    let bundle = r#"(function(modules) { function __webpack_require__(id) {} return __webpack_require__(0); })({
+        "./src/index.js": (function(module, __webpack_exports__, __webpack_require__) {
+            var a = __webpack_require__("./src/util.js");
+        }),
+        "./src/util.js": (function(module) { module.exports = 1; })
+    });"#;
+    let modules = split_webpack_modules(bundle);
+    assert_eq!(modules.len(), 2);
+    let util = module_file_name("./src/util.js");
+    assert!(modules[0].file_name().starts_with("_src_index-"));
+    assert!(util.starts_with("_src_util-") && util.ends_with(".js"), "{util}");
+    assert!(modules[0].source.contains(&format!(r#"var a = require("./{util}");"#)));
+}
+
+#[test]
+fn array_tables_skip_holes() {
+    let js = "var __webpack_modules__ = [function(e){e.exports=1},,function(e,t,r){r(0)}];";
+    let modules = split_webpack_modules(js);
+    let ids: Vec<_> = modules.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, vec!["0", "2"]);
+    assert!(modules[1].source.contains(r#"require("./0.js")"#));
+}
+
+#[test]
+fn iife_array_tables_need_a_webpack_bootstrap() {
+    let bootstrap = r#"(function(modules) { var installed = {};
+        function req(id) { var m = installed[id] = { exports: {} }; modules[id].call(m.exports, m, m.exports, req); return m.exports; }
+        return req(0);
+    })([function(module, exports, r) { r(1); }, function(module) { module.exports = 1; }]);"#;
+    let ids: Vec<_> = split_webpack_modules(bootstrap).into_iter().map(|m| m.id).collect();
+    assert_eq!(ids, vec!["0", "1"]);
+
+    let umd = r#"(function(factories) { factories.forEach(function(f) { f(window, document); }); })([
+        function(root, doc) { root.lib = {}; },
+        function(root) { root.lib.ready = true; }
+    ]);"#;
+    assert!(split_webpack_modules(umd).is_empty());
+}
+
+#[test]
+fn ignores_non_module_code() {
+    assert!(split_webpack_modules("(function(cfg){})({onLoad: function(){}});").is_empty());
+    assert!(split_webpack_modules("let x = [1, 2]; x.push([[1], {a: 1}]);").is_empty());
+    assert!(split_webpack_modules("not javascript {").is_empty());
+}
+
+#[test]
+fn module_file_names_stay_in_directory() {
+    assert!(module_file_name("../../etc/passwd").starts_with("_.._etc_passwd-"));
+    assert_eq!(module_file_name("123"), "123.js");
+    assert_eq!(module_file_name("a1b2"), "a1b2.js");
+    assert!(module_file_name("./a.js").starts_with("_a-"));
+}
+
+#[test]
+fn mangled_module_ids_get_distinct_names() {
+    let names = ["./src/a/b.js", "./src/a_b.js", "_src_a_b", "_src_a_b.js", "a", "a.js"].map(module_file_name);
+    for (i, name) in names.iter().enumerate() {
+        assert!(!names[..i].contains(name), "{name} is used twice in {names:?}");
+    }
+    assert_eq!(module_file_name("./src/a/b.js"), module_file_name("./src/a/b.js"));
+}
+
+#[tokio::test]
+async fn save_without_sourcemap_writes_modules() {
+    let dir = tempdir().unwrap();
+    save_asset_with_maps(CHUNK, Path::new("static/chunks/405-abc.js"), Vec::new(), dir.path(), &SaveOptions::default()).await.unwrap();
+    assert!(dir.path().join("static/chunks/405-abc.js").is_file());
+    let module = std::fs::read_to_string(dir.path().join("webpack_modules/7294.js")).unwrap();
+    assert!(module.contains("window.React"));
+}
+
+#[tokio::test]
+async fn modules_seen_again_follow_the_conflict_policy() {
+    let dir = tempdir().unwrap();
+    let opts = SaveOptions { ledger: Arc::new(SourceLedger::new(ConflictPolicy::KeepFirst)), ..Default::default() };
+    let other = CHUNK.replace("window.React", "window.Preact");
+    save_asset_with_options(CHUNK, Path::new("a.js"), None, Vec::new(), dir.path(), &opts).await.unwrap();
+    let outcome = save_asset_with_options(&other, Path::new("b.js"), None, Vec::new(), dir.path(), &opts).await.unwrap();
+    assert_eq!(outcome.split_modules, 3);
+
+    let module = std::fs::read_to_string(dir.path().join("webpack_modules/7294.js")).unwrap();
+    assert!(module.contains("window.React"));
+    let record = opts.ledger.conflicts().into_iter().find(|r| r.path == Path::new("webpack_modules/7294.js")).unwrap();
+    assert_eq!(record.versions[0].contributors, ["a.js"]);
+    assert_eq!(record.versions[1].written_to, None);
+}
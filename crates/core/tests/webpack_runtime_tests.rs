@@ -0,0 +1,97 @@
+use resourcer_core::{analyze_webpack_runtime, extract_chunk_ids, extract_chunk_maps, infer_chunk_filename_template, ChunkFilenameTemplate, ChunkUrlExpr};
+use url::Url;
+
+// Trimmed-down Next.js 14 runtime: minified require binding `r`, single quotes, special-cased
+// chunks and the `(map[e]||e)+"."+map[e]` lookup.
+const NEXT_RUNTIME: &str = r#"!function(){"use strict";var e={},t={};function r(o){var n=t[o];if(void 0!==n)return n.exports;var u=t[o]={exports:{}};return e[o](u,u.exports,r),u.exports}r.m=e,r.d=function(e,t){},r.o=function(e,t){return Object.prototype.hasOwnProperty.call(e,t)},r.u=function(e){return 2272===e?'static/chunks/2272-abc.js':"static/chunks/"+(({1255:"7d0bf13e",5386:"a1b2"})[e]||e)+"."+({43:'7fa619f5',1255:"e8a1",5386:"cc01"})[e]+".js"},r.miniCssF=function(e){return"static/css/"+{43:"f00d",1255:"beef"}[e]+".css"},r.p="/_next/"}();"#;
+
+#[test]
+fn minified_require_name_and_public_path() {
+    let info = analyze_webpack_runtime(NEXT_RUNTIME).expect("parses");
+    assert_eq!(info.require_name.as_deref(), Some("r"));
+    assert_eq!(info.public_path.as_deref(), Some("/_next/"));
+}
+
+#[test]
+fn next_runtime_chunk_urls_include_css() {
+    let info = analyze_webpack_runtime(NEXT_RUNTIME).expect("parses");
+    assert_eq!(info.chunk_ids, vec!["2272", "1255", "5386", "43"]);
+
+    let base = Url::parse("https://example.com/_next/").unwrap();
+    let urls: Vec<String> = info.chunk_urls(&base).into_iter().map(String::from).collect();
+    assert_eq!(
+        urls,
+        vec![
+            "https://example.com/_next/static/chunks/2272-abc.js",
+            "https://example.com/_next/static/chunks/7d0bf13e.e8a1.js",
+            "https://example.com/_next/static/chunks/a1b2.cc01.js",
+            "https://example.com/_next/static/chunks/43.7fa619f5.js",
+            "https://example.com/_next/static/css/beef.css",
+            "https://example.com/_next/static/css/f00d.css",
+        ]
+    );
+}
+
+#[test]
+fn chunk_maps_ignore_special_cases() {
+    let maps = extract_chunk_maps(NEXT_RUNTIME).expect("maps");
+    assert_eq!(maps.prefix, "static/chunks/");
+    assert_eq!(maps.separator, ".");
+    assert_eq!(maps.map_first.get("1255").map(String::as_str), Some("7d0bf13e"));
+    assert_eq!(maps.map_second.get("43").map(String::as_str), Some("7fa619f5"));
+    assert!(infer_chunk_filename_template(NEXT_RUNTIME).is_none());
+}
+
+#[test]
+fn template_with_renamed_require_and_single_quotes() {
+    let runtime = "(()=>{var o={};o.m={},o.d=()=>{},o.p='',o.u=n=>'js/'+n+'.'+'chunk.js';})();";
+    let tpl = infer_chunk_filename_template(runtime).expect("template");
+    assert_eq!(tpl, ChunkFilenameTemplate { prefix: "js/".into(), suffix: ".chunk.js".into() });
+}
+
+#[test]
+fn webpack4_jsonp_script_src() {
+    let runtime = r#"
+    function jsonpScriptSrc(chunkId) {
+        return __webpack_require__.p + "static/js/" + ({"0":"vendors"}[chunkId]||chunkId) + "." + {"0":"aa11","3":"bb22"}[chunkId] + ".chunk.js"
+    }
+    __webpack_require__.p = "/";
+    (window["webpackJsonpapp"] = window["webpackJsonpapp"] || []).push([[7], {}]);
+    "#;
+    let info = analyze_webpack_runtime(runtime).expect("parses");
+    assert_eq!(info.public_path.as_deref(), Some("/"));
+    assert_eq!(info.chunk_ids, vec!["7", "0", "3"]);
+    let js = info.js_filename.expect("jsonpScriptSrc");
+    assert_eq!(js.static_prefix(), "static/js/");
+    assert_eq!(js.eval("0").as_deref(), Some("static/js/vendors.aa11.chunk.js"));
+    assert_eq!(js.eval("7"), None);
+}
+
+#[test]
+fn if_statements_in_function_body() {
+    let runtime = r#"__webpack_require__.u = function (id) {
+        if (id === 5 || id === 6) return `special/${id}.js`;
+        return "chunks/" + id + ".js";
+    };"#;
+    let expr = analyze_webpack_runtime(runtime).and_then(|i| i.js_filename).expect("filename");
+    assert!(matches!(expr, ChunkUrlExpr::IfEq { ref ids, .. } if ids == &["5", "6"]));
+    assert_eq!(expr.eval("6").as_deref(), Some("special/6.js"));
+    assert_eq!(expr.eval("9").as_deref(), Some("chunks/9.js"));
+    assert_eq!(extract_chunk_ids(runtime), vec!["5", "6"]);
+}
+
+#[test]
+fn chunk_ids_of_unparseable_runtimes_come_from_patterns() {
+    // Truncated mid-function, so SWC rejects it.
+    let runtime = r#"self.webpackChunk_N_E.push([[2888,179],{}]);r.u=function(e){return 7561===e?"static/chunks/7561-be856e985935a49b.js":"static/chunks/"+({1255:"7d0bf13e",43:"c7a1"}[e]||e)+"."+{1255:"aa11",43:"7fa619f5d693091a"}[e]+".js"}; function ("#;
+    assert!(analyze_webpack_runtime(runtime).is_none());
+    assert_eq!(extract_chunk_ids(runtime), vec!["2888", "7561", "1255", "43"]);
+}
+
+#[test]
+fn unparseable_or_unrelated_code() {
+    assert!(analyze_webpack_runtime("function (").is_none());
+    let info = analyze_webpack_runtime("window.app = { u: 1 };").expect("parses");
+    assert_eq!(info.require_name, None);
+    assert_eq!(info.js_filename, None);
+}
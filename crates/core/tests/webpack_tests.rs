@@ -66,6 +66,7 @@ async fn validate_urls() {
     assert_eq!(res, vec![ok_url]);
 
     ok_mock.assert_async().await;
+    bad_mock.assert_async().await;
 }
 
 #[test]
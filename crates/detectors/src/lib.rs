@@ -1,15 +1,56 @@
-//! Runtime detector plugins live here. For now this crate is empty and compiles as part of the workspace.
+//! Built-in runtime detectors.
+//!
+//! The [`RuntimeDetector`] trait, [`DetectorRegistry`] and page types live in `resourcer_core`
+//! (see `resourcer_core::detect`) so the core dump pipeline can drive them; they are re-exported
+//! here together with the webpack and Vite implementations.
 
-// A trivial function to satisfy `cargo test`.
-#[allow(dead_code)]
-pub fn detectors_placeholder() {}
+pub use resourcer_core::detect::{ChunkPlan, Detection, DetectorError, DetectorRegistry, PageContext, RuntimeDetector};
+
+pub mod vite;
+pub mod webpack;
+pub use vite::ViteDetector;
+pub use webpack::WebpackDetector;
+
+/// Adds [`BuiltinDetectors::with_builtin`] to [`DetectorRegistry`].
+pub trait BuiltinDetectors {
+    /// A registry pre-populated with every built-in detector.
+    fn with_builtin() -> Self;
+}
+
+impl BuiltinDetectors for DetectorRegistry {
+    fn with_builtin() -> Self {
+        let mut reg = Self::empty();
+        reg.register(WebpackDetector).register(ViteDetector);
+        reg
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use url::Url;
+
+    fn page(html: &str) -> PageContext {
+        PageContext::from_html(Url::parse("https://example.com/").unwrap(), html.to_string())
+    }
+
+    #[test]
+    fn builtin_registry_detects_next_runtime() {
+        let html = r#"<script src="/_next/static/chunks/webpack-abc123.js"></script>
+            <script src="/_next/static/xyz/_buildManifest.js"></script>"#;
+        let reg = DetectorRegistry::with_builtin();
+        let hits = reg.detect(&page(html));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0.name(), "webpack");
+        assert_eq!(
+            hits[0].1.entry.as_ref().map(|u| u.as_str()),
+            Some("https://example.com/_next/static/chunks/webpack-abc123.js")
+        );
+    }
 
     #[test]
-    fn placeholder_compiles() {
-        detectors_placeholder();
+    fn builtin_registry_ignores_plain_pages() {
+        let reg = DetectorRegistry::with_builtin();
+        assert!(reg.detect(&page(r#"<script src="/app.js"></script>"#)).is_empty());
     }
 }
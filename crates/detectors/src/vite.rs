@@ -0,0 +1,346 @@
+//! Vite / Rollup runtime detector.
+//!
+//! Vite builds ship an ES module entry (`/assets/index-<hash>.js`) plus `<link rel="modulepreload">`
+//! hints. Every other chunk is reachable from the entry through static `import` / `export ... from`
+//! statements, dynamic `import()` calls, or the `__vite__mapDeps` preload arrays, so enumeration
+//! walks that graph breadth-first, fetching each JS chunk once.
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use resourcer_core::derive_base_from_runtime;
+use url::Url;
+
+use crate::{ChunkPlan, Detection, DetectorError, PageContext, RuntimeDetector};
+
+/// Upper bound on chunks visited while walking the module graph.
+const MAX_GRAPH_CHUNKS: usize = 5000;
+/// Number of chunks fetched concurrently while walking the graph.
+const GRAPH_FETCH_CONCURRENCY: usize = 8;
+
+static SCRIPT_TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)<script\b[^>]*>"#).unwrap());
+static LINK_TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)<link\b[^>]*>"#).unwrap());
+static ATTR_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)\b([a-z-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap()
+});
+static HASHED_ASSET_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"/assets/[^/]+-[\w-]{8,}\.m?js$").unwrap());
+/// Vite code inlined in the page: preload helpers, the legacy plugin's `import.meta` probe, or the
+/// dev server client.
+static INLINE_MARKER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"__vite__mapDeps|\bimport\.meta\b|/@vite/client").unwrap());
+
+static STATIC_IMPORT_RE: Lazy<Regex> = Lazy::new(|| {
+    // import"./a.js"  import{x as y}from"./a.js"  export*from"./a.js"
+    Regex::new(r#"\b(?:import|export)\s*(?:[^"'`();]*?\bfrom\s*)?["']([^"'\s]+)["']"#).unwrap()
+});
+static DYNAMIC_IMPORT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bimport\(\s*["']([^"'\s]+)["']\s*\)"#).unwrap()
+});
+static MAP_DEPS_ARRAY_RE: Lazy<Regex> = Lazy::new(|| {
+    // Vite >= 5.1: m.f||(m.f=[...])   Vite 4.x: __vite__mapDeps.viteFileDeps=[...]
+    Regex::new(r#"(?:\.f|viteFileDeps)\s*=\s*\[([^\]]*)\]"#).unwrap()
+});
+static STRING_LIT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"["']([^"']+)["']"#).unwrap());
+
+/// Detects Vite (Rollup-based) production builds.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ViteDetector;
+
+fn tag_attrs(tag: &str) -> Vec<(String, String)> {
+    ATTR_RE
+        .captures_iter(tag)
+        .filter_map(|c| {
+            let name = c.get(1)?.as_str().to_ascii_lowercase();
+            let value = c.get(2).or_else(|| c.get(3)).or_else(|| c.get(4))?.as_str().to_string();
+            Some((name, value))
+        })
+        .collect()
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+/// `<script type="module" src>` entries, in document order.
+fn module_entries(page: &PageContext) -> Vec<Url> {
+    SCRIPT_TAG_RE
+        .find_iter(&page.html)
+        .filter_map(|m| {
+            let attrs = tag_attrs(m.as_str());
+            if !attr(&attrs, "type").is_some_and(|t| t.eq_ignore_ascii_case("module")) {
+                return None;
+            }
+            page.page_url.join(attr(&attrs, "src")?).ok()
+        })
+        .collect()
+}
+
+/// `href`s of `<link>` tags whose `rel` matches.
+fn link_hrefs(page: &PageContext, rel: &str) -> Vec<Url> {
+    LINK_TAG_RE
+        .find_iter(&page.html)
+        .filter_map(|m| {
+            let attrs = tag_attrs(m.as_str());
+            let rels = attr(&attrs, "rel")?;
+            if !rels.split_whitespace().any(|r| r.eq_ignore_ascii_case(rel)) {
+                return None;
+            }
+            page.page_url.join(attr(&attrs, "href")?).ok()
+        })
+        .collect()
+}
+
+fn is_asset(u: &Url) -> bool {
+    let p = u.path();
+    p.ends_with(".js") || p.ends_with(".mjs") || p.ends_with(".css")
+}
+
+fn is_script(u: &Url) -> bool {
+    let p = u.path();
+    p.ends_with(".js") || p.ends_with(".mjs")
+}
+
+/// Static and dynamic import specifiers that point at other files (bare specifiers are skipped).
+pub fn extract_import_specifiers(js: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut seen = HashSet::new();
+    for re in [&*STATIC_IMPORT_RE, &*DYNAMIC_IMPORT_RE] {
+        for caps in re.captures_iter(js) {
+            let spec = &caps[1];
+            let is_path = spec.starts_with("./")
+                || spec.starts_with("../")
+                || spec.starts_with('/')
+                || spec.starts_with("http://")
+                || spec.starts_with("https://");
+            if is_path && seen.insert(spec.to_string()) {
+                out.push(spec.to_string());
+            }
+        }
+    }
+    out
+}
+
+/// File paths listed in the `__vite__mapDeps` preload table, relative to the Vite `base`.
+pub fn extract_map_deps(js: &str) -> Vec<String> {
+    if !js.contains("__vite__mapDeps") {
+        return Vec::new();
+    }
+    let mut out = Vec::new();
+    let mut seen = HashSet::new();
+    for caps in MAP_DEPS_ARRAY_RE.captures_iter(js) {
+        for lit in STRING_LIT_RE.captures_iter(&caps[1]) {
+            let s = lit[1].to_string();
+            if seen.insert(s.clone()) {
+                out.push(s);
+            }
+        }
+    }
+    out
+}
+
+/// Resolve a `__vite__mapDeps` entry. Entries are relative to the deploy base rather than the
+/// importing chunk, so the base is recovered by locating the entry's first directory (usually
+/// `assets/`) inside the chunk's own path.
+fn resolve_map_dep(chunk_url: &Url, dep: &str) -> Option<Url> {
+    if dep.starts_with('/') || dep.contains("://") {
+        return chunk_url.join(dep).ok();
+    }
+    let first_dir = dep.split_once('/').map(|(d, _)| format!("{}/", d));
+    let base = match first_dir {
+        Some(prefix) if chunk_url.path().contains(&format!("/{}", prefix)) => derive_base_from_runtime(chunk_url, &prefix),
+        _ => chunk_url.join("./").ok()?,
+    };
+    base.join(dep).ok()
+}
+
+/// Every chunk/asset a single JS chunk refers to.
+fn chunk_references(chunk_url: &Url, js: &str) -> Vec<Url> {
+    let mut refs: Vec<Url> = extract_import_specifiers(js)
+        .iter()
+        .filter_map(|s| chunk_url.join(s).ok())
+        .collect();
+    refs.extend(extract_map_deps(js).iter().filter_map(|d| resolve_map_dep(chunk_url, d)));
+    refs.retain(is_asset);
+    refs
+}
+
+#[async_trait]
+impl RuntimeDetector for ViteDetector {
+    fn name(&self) -> &'static str {
+        "vite"
+    }
+
+    fn detect(&self, page: &PageContext) -> Option<Detection> {
+        let entries = module_entries(page);
+        let entry = entries
+            .iter()
+            .find(|u| HASHED_ASSET_RE.is_match(u.path()))
+            .or_else(|| entries.first())?;
+        let hashed = HASHED_ASSET_RE.is_match(entry.path());
+        let preload = !link_hrefs(page, "modulepreload").is_empty();
+        let confidence = match (hashed, preload) {
+            (true, true) => 0.9,
+            (true, false) | (false, true) => 0.7,
+            // Any ES module app has a module entry; without a Vite marker it is not evidence.
+            (false, false) if INLINE_MARKER_RE.is_match(&page.html) => 0.3,
+            (false, false) => return None,
+        };
+        Some(Detection { confidence, entry: Some(entry.clone()) })
+    }
+
+    async fn enumerate(&self, page: &PageContext, detection: &Detection) -> Result<ChunkPlan, DetectorError> {
+        let mut frontier: Vec<Url> = detection.entry.iter().cloned().collect();
+        frontier.extend(module_entries(page));
+        frontier.extend(link_hrefs(page, "modulepreload"));
+        frontier.extend(link_hrefs(page, "stylesheet").into_iter().filter(is_asset));
+
+        let mut seen: HashSet<Url> = HashSet::new();
+        let mut urls: Vec<Url> = Vec::new();
+
+        while !frontier.is_empty() && seen.len() < MAX_GRAPH_CHUNKS {
+            let mut to_fetch = Vec::new();
+            for u in frontier.drain(..) {
+                if seen.len() >= MAX_GRAPH_CHUNKS {
+                    log::warn!("Vite module graph exceeds {} files; stopping enumeration", MAX_GRAPH_CHUNKS);
+                    break;
+                }
+                if seen.insert(u.clone()) {
+                    urls.push(u.clone());
+                    if is_script(&u) {
+                        to_fetch.push(u);
+                    }
+                }
+            }
+
+            let fetched: Vec<(Url, Result<String, _>)> = stream::iter(to_fetch)
+                .map(|u| async move {
+                    let res = page.fetcher.fetch_text(&u, 1).await;
+                    (u, res)
+                })
+                .buffer_unordered(GRAPH_FETCH_CONCURRENCY)
+                .collect()
+                .await;
+
+            for (u, res) in fetched {
+                match res {
+                    Ok(js) => frontier.extend(chunk_references(&u, &js)),
+                    Err(e) => log::warn!("Could not fetch Vite chunk {}: {}", u, e),
+                }
+            }
+        }
+
+        log::info!("Found {} files in Vite module graph", urls.len());
+        Ok(ChunkPlan { strategy: "vite-module-graph".into(), urls })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn import_specifiers_from_minified_chunk() {
+        let js = r#"import{r as a,j as b}from"./vendor-AbCd1234.js";import"./side-effect-1.js";
+            export*from"../shared.js";import React from "react";
+            const P=()=>import("./Page-XyZ98765.js");"#;
+        assert_eq!(
+            extract_import_specifiers(js),
+            vec!["./vendor-AbCd1234.js", "./side-effect-1.js", "../shared.js", "./Page-XyZ98765.js"]
+        );
+    }
+
+    #[test]
+    fn map_deps_both_generations() {
+        let v5 = r#"const __vite__mapDeps=(i,m=__vite__mapDeps,d=(m.f||(m.f=["assets/About-1.js","assets/About-2.css"])))=>i.map(i=>d[i]);"#;
+        assert_eq!(extract_map_deps(v5), vec!["assets/About-1.js", "assets/About-2.css"]);
+
+        let v4 = r#"function __vite__mapDeps(indexes) {
+          if (!__vite__mapDeps.viteFileDeps) {
+            __vite__mapDeps.viteFileDeps = ["assets/Foo-a.js","assets/Foo-b.css"]
+          }
+          return indexes.map((i) => __vite__mapDeps.viteFileDeps[i])
+        }"#;
+        assert_eq!(extract_map_deps(v4), vec!["assets/Foo-a.js", "assets/Foo-b.css"]);
+    }
+
+    #[test]
+    fn map_deps_resolve_against_deploy_base() {
+        let chunk = Url::parse("https://example.com/app/assets/index-abc.js").unwrap();
+        assert_eq!(
+            resolve_map_dep(&chunk, "assets/About-1.js").unwrap().as_str(),
+            "https://example.com/app/assets/About-1.js"
+        );
+    }
+
+    #[test]
+    fn detects_vite_entry() {
+        let html = r#"<script type="module" crossorigin src="/assets/index-4f8a9c1b.js"></script>
+            <link rel="modulepreload" crossorigin href="/assets/vendor-99aa88bb.js">"#;
+        let page = PageContext::from_html(Url::parse("https://example.com/").unwrap(), html.into());
+        let det = ViteDetector.detect(&page).unwrap();
+        assert_eq!(det.confidence, 0.9);
+        assert_eq!(det.entry.unwrap().as_str(), "https://example.com/assets/index-4f8a9c1b.js");
+
+        let plain = PageContext::from_html(Url::parse("https://example.com/").unwrap(), r#"<script src="/app.js"></script>"#.into());
+        assert!(ViteDetector.detect(&plain).is_none());
+    }
+
+    #[test]
+    fn unhashed_module_entry_needs_a_vite_marker() {
+        let page_url = Url::parse("https://example.com/").unwrap();
+        let html = r#"<script type="module" src="/js/main.js"></script>"#;
+        assert!(ViteDetector.detect(&PageContext::from_html(page_url.clone(), html.into())).is_none());
+
+        let legacy = format!(r#"{html}<script type="module">import.meta.url;import("_").catch(()=>1);</script>"#);
+        let det = ViteDetector.detect(&PageContext::from_html(page_url, legacy)).unwrap();
+        assert_eq!(det.confidence, 0.3);
+        assert_eq!(det.entry.unwrap().path(), "/js/main.js");
+    }
+
+    #[tokio::test]
+    async fn enumerate_walks_module_graph() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(GET).path("/assets/index-11111111.js");
+            then.status(200).body(
+                r#"const __vite__mapDeps=(i,m=__vite__mapDeps,d=(m.f||(m.f=["assets/About-22222222.js","assets/About-33333333.css"])))=>i.map(i=>d[i]);
+                import{a}from"./vendor-44444444.js";const r=()=>import("./About-22222222.js");"#,
+            );
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/assets/vendor-44444444.js");
+            then.status(200).body("export const a=1;");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/assets/About-22222222.js");
+            then.status(200).body(r#"import"./Lazy-55555555.js";"#);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/assets/Lazy-55555555.js");
+            then.status(200).body("console.log(1)");
+        });
+
+        let html = r#"<script type="module" src="/assets/index-11111111.js"></script>
+            <link rel="stylesheet" href="/assets/index-66666666.css">"#;
+        let page = PageContext::from_html(Url::parse(&server.url("/")).unwrap(), html.into());
+        let det = ViteDetector.detect(&page).unwrap();
+        let plan = ViteDetector.enumerate(&page, &det).await.unwrap();
+
+        let mut paths: Vec<_> = plan.urls.iter().map(|u| u.path().to_string()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "/assets/About-22222222.js",
+                "/assets/About-33333333.css",
+                "/assets/Lazy-55555555.js",
+                "/assets/index-11111111.js",
+                "/assets/index-66666666.css",
+                "/assets/vendor-44444444.js",
+            ]
+        );
+    }
+}
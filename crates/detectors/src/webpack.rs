@@ -0,0 +1,163 @@
+//! Webpack / Next.js runtime detector.
+//!
+//! Detection keys off the `webpack-<hash>.js` runtime script. Enumeration prefers a Next.js
+//! `_buildManifest.js` when the page has one, then evaluates the runtime's JS and CSS chunk
+//! filename functions (see [`resourcer_core::webpack_runtime`]) for every known chunk ID, and
+//! finally falls back to literal `static/chunks/*.js` paths in the runtime.
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use resourcer_core::{
+    analyze_webpack_runtime, derive_base_from_runtime, extract_literal_chunk_paths,
+    extract_paths_from_build_manifest, find_sourcemap_url_in_js,
+};
+use url::Url;
+
+use crate::{ChunkPlan, Detection, DetectorError, PageContext, RuntimeDetector};
+
+static RUNTIME_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"/webpack-[^/]+\.js(?:\?.*)?$").unwrap());
+static MANIFEST_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"/_?buildManifest\.js$").unwrap());
+
+/// Detects Webpack runtimes, including the Next.js flavour with `_buildManifest.js`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WebpackDetector;
+
+fn find_manifest(page: &PageContext) -> Option<&Url> {
+    page.scripts.iter().find(|u| MANIFEST_RE.is_match(u.path()))
+}
+
+#[async_trait]
+impl RuntimeDetector for WebpackDetector {
+    fn name(&self) -> &'static str {
+        "webpack"
+    }
+
+    fn detect(&self, page: &PageContext) -> Option<Detection> {
+        let runtime = page.scripts.iter().find(|u| RUNTIME_RE.is_match(u.path()))?;
+        let confidence = if find_manifest(page).is_some() { 1.0 } else { 0.9 };
+        Some(Detection { confidence, entry: Some(runtime.clone()) })
+    }
+
+    async fn enumerate(&self, page: &PageContext, detection: &Detection) -> Result<ChunkPlan, DetectorError> {
+        let runtime_url = detection
+            .entry
+            .clone()
+            .ok_or_else(|| DetectorError::Other("webpack detection is missing the runtime script".into()))?;
+        log::info!("Identified Webpack runtime: {}", runtime_url);
+
+        log::info!("Fetching runtime content...");
+        let runtime_js = page.fetcher.fetch_text(&runtime_url, 1).await?;
+
+        if let Some(sm_url) = find_sourcemap_url_in_js(&runtime_js) {
+            log::info!("Sourcemap enabled in runtime: {}", sm_url);
+        } else {
+            log::info!("No sourcemap comment found in runtime JS");
+        }
+
+        // First attempt: use _buildManifest.js to list all asset paths
+        if let Some(manifest_url) = find_manifest(page) {
+            log::info!("Found build manifest script: {}", manifest_url);
+            let manifest_js = page.fetcher.fetch_text(manifest_url, 1).await?;
+
+            let paths = extract_paths_from_build_manifest(&manifest_js);
+            log::info!("Found {} asset paths in build manifest", paths.len());
+
+            let base_chunks = derive_base_from_runtime(&runtime_url, "static/");
+
+            let mut urls = Vec::new();
+            let mut seen: HashSet<String> = HashSet::new();
+            for p in paths {
+                // We only care about .js files for now
+                if !p.ends_with(".js") { continue; }
+                if let Ok(u) = base_chunks.join(&p) {
+                    if seen.insert(u.as_str().to_string()) {
+                        urls.push(u);
+                    }
+                }
+            }
+            return Ok(ChunkPlan { strategy: "build-manifest".into(), urls });
+        }
+
+        Ok(plan_from_runtime(&page.page_url, &runtime_url, &runtime_js))
+    }
+}
+
+/// Runtime-only inference used when no build manifest is available.
+fn plan_from_runtime(page_url: &Url, runtime_url: &Url, runtime_js: &str) -> ChunkPlan {
+    let info = analyze_webpack_runtime(runtime_js).unwrap_or_default();
+    let public_path = info.public_path.clone().filter(|p| !p.is_empty());
+    // __webpack_require__.p may be absolute or relative to the page
+    let base_for_chunks: Option<Url> = match public_path.as_ref() {
+        Some(pp) => Url::parse(pp).ok().or_else(|| page_url.join(pp).ok()),
+        None => Some(page_url.clone()),
+    };
+    let has_public_path = public_path.is_some();
+
+    if let Some(bu) = &base_for_chunks {
+        log::info!("Public path base: {}", bu);
+    } else {
+        log::info!("No public path detected; chunk URLs may be relative.");
+    }
+
+    if info.js_filename.is_some() && !info.chunk_ids.is_empty() {
+        log::info!(
+            "Runtime analysis: require={:?}, {} chunk IDs, css filenames: {}",
+            info.require_name,
+            info.chunk_ids.len(),
+            info.css_filename.is_some()
+        );
+        let prefix = info.js_filename.as_ref().map_or("", |e| e.static_prefix());
+        let dyn_base = match base_for_chunks {
+            Some(b) if has_public_path => b,
+            // No prefix means chunks sit next to the runtime itself.
+            _ if prefix.is_empty() => runtime_url.join("./").unwrap_or_else(|_| page_url.clone()),
+            _ => derive_base_from_runtime(runtime_url, prefix),
+        };
+        ChunkPlan { strategy: "runtime-ast".into(), urls: info.chunk_urls(&dyn_base) }
+    } else {
+        log::info!("Could not infer chunk filenames; falling back to literal path extraction.");
+        let paths = extract_literal_chunk_paths(runtime_js);
+        log::info!("Found {} literal chunk paths", paths.len());
+
+        let literal_base = if has_public_path {
+            base_for_chunks.unwrap_or_else(|| page_url.clone())
+        } else {
+            derive_base_from_runtime(runtime_url, "static/chunks/")
+        };
+
+        let urls = paths.iter().filter_map(|p| literal_base.join(p).ok()).collect();
+        ChunkPlan { strategy: "literal-paths".into(), urls }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_template_plan_uses_public_path() {
+        let page = Url::parse("https://example.com/app/").unwrap();
+        let runtime = Url::parse("https://example.com/app/webpack-1.js").unwrap();
+        let js = r#"__webpack_require__.p = "/assets/";
+        __webpack_require__.u = (id) => "js/" + id + ".chunk.js";
+        self.webpackChunk_app.push([["42",1],{}]);"#;
+        let plan = plan_from_runtime(&page, &runtime, js);
+        assert_eq!(plan.strategy, "runtime-ast");
+        let urls: Vec<_> = plan.urls.iter().map(|u| u.as_str()).collect();
+        // Every element of the pushed chunk-ID array is an ID, numeric ones included.
+        assert_eq!(urls, vec!["https://example.com/assets/js/42.chunk.js", "https://example.com/assets/js/1.chunk.js"]);
+    }
+
+    #[test]
+    fn runtime_literal_plan_without_public_path() {
+        let page = Url::parse("https://example.com/").unwrap();
+        let runtime = Url::parse("https://example.com/_next/static/chunks/webpack-1.js").unwrap();
+        let js = r#"var a = "static/chunks/123-abc.js";"#;
+        let plan = plan_from_runtime(&page, &runtime, js);
+        assert_eq!(plan.strategy, "literal-paths");
+        assert_eq!(plan.urls[0].as_str(), "https://example.com/_next/static/chunks/123-abc.js");
+    }
+}